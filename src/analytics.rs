@@ -0,0 +1,163 @@
+//! Records where the player takes damage and dies, into a local analytics file.
+//! The `--heatmaps` tool renders heatmap images of every level from it.
+
+pub mod prelude {
+    pub use super::DamageCause;
+    pub use super::DamageRecord;
+    pub use super::PlayerDamage;
+    pub use super::PlayerDamageEvents;
+}
+
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use amethyst::core::transform::Transform;
+use amethyst::utils::application_root_dir;
+
+use crate::components::prelude::{EnemyType, Player};
+use crate::settings::SettingsAnalytics;
+use crate::states::helpers::CampaignType;
+
+/// What dealt damage to the player.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageCause {
+    /// Touched an enemy of the given type.
+    Enemy(String),
+    HarmfulTile,
+    Bullet,
+    DeathFloor,
+}
+
+impl DamageCause {
+    pub fn enemy(enemy_type: &EnemyType) -> Self {
+        DamageCause::Enemy(format!("{:?}", enemy_type))
+    }
+}
+
+/// A single time the player took damage.
+#[derive(Clone)]
+pub struct PlayerDamage {
+    pub cause:    DamageCause,
+    pub position: (f32, f32),
+    pub damage:   u32,
+    pub fatal:    bool,
+}
+
+/// Filled by the systems which deal damage to the player,
+/// and drained by the `DamageAnalyticsSystem`.
+#[derive(Default)]
+pub struct PlayerDamageEvents(pub Vec<PlayerDamage>);
+
+impl PlayerDamageEvents {
+    /// Records the damage the player took, if they lost any health
+    /// since `health_before`.
+    pub fn push(
+        &mut self,
+        cause: DamageCause,
+        health_before: u32,
+        player: &Player,
+        transform: &Transform,
+    ) {
+        if player.health < health_before {
+            let pos = transform.translation();
+            self.0.push(PlayerDamage {
+                cause:    cause,
+                position: (pos.x, pos.y),
+                damage:   health_before - player.health,
+                fatal:    player.is_dead(),
+            });
+        }
+    }
+}
+
+/// A line of the analytics file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DamageRecord {
+    /// Records from before campaigns were recorded belong to the normal campaign.
+    #[serde(default)]
+    pub campaign: CampaignType,
+    pub level:    String,
+    pub x:        f32,
+    pub y:        f32,
+    pub cause:    DamageCause,
+    pub damage:   u32,
+    pub fatal:    bool,
+}
+
+impl DamageRecord {
+    pub fn new(
+        campaign: CampaignType,
+        level: String,
+        damage: PlayerDamage,
+    ) -> Self {
+        Self {
+            campaign: campaign,
+            level:    level,
+            x:        damage.position.0,
+            y:        damage.position.1,
+            cause:    damage.cause,
+            damage:   damage.damage,
+            fatal:    damage.fatal,
+        }
+    }
+}
+
+/// Appends the records to the analytics file.
+pub fn append_records(
+    settings: &SettingsAnalytics,
+    records: &[DamageRecord],
+) -> Result<(), String> {
+    let path = format!("{}/{}", application_root_dir(), settings.damage_file);
+    if let Some(dir) = Path::new(&path).parent() {
+        create_dir_all(dir).map_err(|err| {
+            format!("Couldn't create directory `{}`: {}", dir.display(), err)
+        })?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| format!("Couldn't open `{}`: {}", path, err))?;
+
+    for record in records {
+        let line = serde_json::to_string(record)
+            .map_err(|err| format!("Couldn't serialize record: {}", err))?;
+        writeln!(file, "{}", line)
+            .map_err(|err| format!("Couldn't write `{}`: {}", path, err))?;
+    }
+    Ok(())
+}
+
+/// Reads all records from the analytics file.
+/// Returns no records if the file doesn't exist yet;
+/// invalid lines are skipped with a warning.
+pub fn read_records(
+    settings: &SettingsAnalytics,
+) -> Result<Vec<DamageRecord>, String> {
+    let path = format!("{}/{}", application_root_dir(), settings.damage_file);
+    let file = match OpenOptions::new().read(true).open(&path) {
+        Ok(file) => file,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line =
+            line.map_err(|err| format!("Couldn't read `{}`: {}", path, err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(err) => warn!(
+                "Skipping invalid record in `{}` line {}: {}",
+                path,
+                index + 1,
+                err
+            ),
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,84 @@
+//! A campaign's asset manifest lists the spritesheets, background textures and songs,
+//! which its levels use, so the `Startup` state can preload them.
+//! Manifests are generated from the level files and settings with `--manifests`,
+//! and checked with `--verify-assets`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::resource_helpers::{
+    read_file,
+    resource,
+    resource_exists,
+    write_file,
+};
+
+/// All paths are relative to the resources directory.
+#[derive(Default, PartialEq, Serialize, Deserialize)]
+pub struct AssetManifest {
+    /// Spritesheet images; each has a `.ron` file with the same name next to it.
+    pub spritesheets: BTreeSet<String>,
+    /// Background images of parallax objects.
+    pub textures:     BTreeSet<String>,
+    pub songs:        BTreeSet<String>,
+}
+
+impl AssetManifest {
+    /// Loads the manifest from the given path, relative to the resources directory.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = read_file(resource(path)).map_err(|err| {
+            format!("Couldn't read asset manifest `{}`: {}", path, err)
+        })?;
+        ron::de::from_str(&raw).map_err(|err| {
+            format!("Couldn't parse asset manifest `{}`: {}", path, err)
+        })
+    }
+
+    /// Saves the manifest to the given path, relative to the resources directory.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let serialized =
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|err| err.to_string())?;
+        let path = resource(path);
+        if let Some(dir) = Path::new(&path).parent() {
+            std::fs::create_dir_all(dir).map_err(|err| {
+                format!("Couldn't create directory {:?}: {}", dir, err)
+            })?;
+        }
+        write_file(&path, serialized).map_err(|err| {
+            format!("Couldn't write asset manifest `{}`: {}", path, err)
+        })
+    }
+
+    /// Adds the assets of the other manifest.
+    pub fn merge(&mut self, other: AssetManifest) {
+        self.spritesheets.extend(other.spritesheets);
+        self.textures.extend(other.textures);
+        self.songs.extend(other.songs);
+    }
+
+    /// Returns the paths of all files, which the listed assets consist of.
+    pub fn files(&self) -> Vec<String> {
+        self.spritesheets
+            .iter()
+            .flat_map(|spritesheet| {
+                vec![spritesheet.clone(), spritesheet_ron_path(spritesheet)]
+            })
+            .chain(self.textures.iter().cloned())
+            .chain(self.songs.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the listed files, which don't exist.
+    pub fn missing_files(&self) -> Vec<String> {
+        self.files()
+            .into_iter()
+            .filter(|file| !resource_exists(file))
+            .collect()
+    }
+}
+
+/// Returns the path of the spritesheet image's `.ron` file.
+pub fn spritesheet_ron_path(spritesheet: &str) -> String {
+    format!("{}.ron", spritesheet.trim_end_matches(".png"))
+}
@@ -0,0 +1,251 @@
+use std::env;
+
+use log::LevelFilter;
+
+use crate::logger::parse_level;
+use crate::resolution_parser::parse_resolution;
+use crate::settings::Settings;
+use crate::states::helpers::CampaignType;
+
+const USAGE: &str = "\
+USAGE:
+    stabman [OPTIONS]
+
+OPTIONS:
+    -h, --help               Print this help and exit
+        --campaign <name>    Start the campaign `normal`, `bonus_a`, `bonus_b`
+                             or `mod:<pack>`, skipping the main menu
+        --level <name>       Start at the level with the given name, skipping the
+                             main menu (in the `normal` campaign, unless `--campaign` is given)
+        --new-game           Start a new game, skipping the main menu
+                             (in the `normal` campaign, unless `--campaign` is given)
+        --dev                Enable development mode (requires the `debug` feature)
+        --resolution <WxH>   Set the window resolution, for example `1280x720`
+        --windowed           Don't start in fullscreen
+        --settings <path>    Use the given settings overrides file,
+                             instead of `settings_overrides.ron`
+        --save-slot <n>      Use a separate set of savefiles
+        --set <path=value>   Override a single settings value,
+                             for example `--set player.jump_strength=450`
+        --log-level <level>  Set the default log level: `off`, `error`, `warn`,
+                             `info`, `debug` or `trace`
+        --log <mod=level>    Set the log level of a module and its submodules,
+                             for example `--log ld44_stabman::level_manager=debug`
+        --record <file>      Record the keyboard input of the most recent level run
+                             to the given file, for `--replay`
+
+TOOLS (run instead of the game):
+        --replay <file>      Play back a level run recorded with `--record` without a
+                             window, and report where the player ended up
+        --heatmaps           Render a heatmap of player damage and deaths for every level
+        --preview <name>     Render a preview image of the level with the given name
+        --previews           Render a preview image of every level
+        --output <path>      Write the image of `--preview` to the given path,
+                             instead of the previews directory
+        --economy <name>     Report the health economy (enemy rewards, item costs and
+                             health budget) of every level of the given campaign
+        --benchmark-grid     Benchmark finding nearby entities with the spatial grid,
+                             against checking every entity, on the largest level
+        --manifests          Generate the asset manifest of every campaign,
+                             from its level files and settings
+        --verify-assets      Report missing assets and outdated asset manifests
+        --verify-mods        Report the problems of every installed mod pack
+        --pack-resources     Pack the resources directory into `resources.pak`
+        --no-compression     Don't compress the files packed by `--pack-resources`";
+
+const SET_ARG: &str = "--set";
+const MOD_CAMPAIGN_PREFIX: &str = "mod:";
+
+/// The parsed command line arguments.
+#[derive(Clone, Default)]
+pub struct CliArgs {
+    pub help:           bool,
+    pub campaign:       Option<CampaignType>,
+    pub level:          Option<String>,
+    pub new_game:       bool,
+    pub dev:            bool,
+    pub resolution:     Option<(u32, u32)>,
+    pub windowed:       bool,
+    pub settings:       Option<String>,
+    pub save_slot:      Option<u32>,
+    pub log_level:      Option<LevelFilter>,
+    pub log_modules:    Vec<(String, LevelFilter)>,
+    pub record:         Option<String>,
+    pub replay:         Option<String>,
+    pub heatmaps:       bool,
+    pub preview:        Option<String>,
+    pub previews:       bool,
+    pub output:         Option<String>,
+    pub economy:        Option<CampaignType>,
+    pub benchmark_grid: bool,
+    pub manifests:      bool,
+    pub verify_assets:  bool,
+    pub verify_mods:    bool,
+    pub pack_resources: bool,
+    pub no_compression: bool,
+}
+
+impl CliArgs {
+    /// Parses the process' command line arguments.
+    /// `--set` arguments are skipped; they are handled by the settings loader.
+    fn parse() -> Result<Self, String> {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    fn parse_from<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut cli_args = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value_for = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for `{}`", name))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => cli_args.help = true,
+                "--campaign" => {
+                    let name = value_for(&arg)?;
+                    cli_args.campaign = Some(parse_campaign(&name)?);
+                }
+                "--level" => {
+                    let name = value_for(&arg)?;
+                    cli_args.level = Some(if name.ends_with(".json") {
+                        name
+                    } else {
+                        format!("{}.json", name)
+                    });
+                }
+                "--new-game" => cli_args.new_game = true,
+                "--dev" => cli_args.dev = true,
+                "--resolution" => {
+                    let text = value_for(&arg)?;
+                    cli_args.resolution =
+                        Some(parse_resolution(&text)?.ok_or_else(|| {
+                            format!("Invalid resolution `{}`", text)
+                        })?);
+                }
+                "--windowed" => cli_args.windowed = true,
+                "--settings" => cli_args.settings = Some(value_for(&arg)?),
+                "--save-slot" => {
+                    let text = value_for(&arg)?;
+                    cli_args.save_slot =
+                        Some(text.parse::<u32>().map_err(|err| {
+                            format!("Invalid save slot `{}`: {}", text, err)
+                        })?);
+                }
+                "--log-level" => {
+                    cli_args.log_level = Some(parse_level(&value_for(&arg)?)?);
+                }
+                "--log" => {
+                    let text = value_for(&arg)?;
+                    let mut split = text.splitn(2, '=');
+                    match (split.next(), split.next()) {
+                        (Some(module), Some(level)) if !module.is_empty() => {
+                            cli_args.log_modules.push((
+                                module.to_string(),
+                                parse_level(level)?,
+                            ));
+                        }
+                        _ => {
+                            return Err(format!(
+                            "Invalid log level `{}`, expected `module=level`",
+                            text
+                        ))
+                        }
+                    }
+                }
+                "--record" => cli_args.record = Some(value_for(&arg)?),
+                "--replay" => cli_args.replay = Some(value_for(&arg)?),
+                "--heatmaps" => cli_args.heatmaps = true,
+                "--preview" => cli_args.preview = Some(value_for(&arg)?),
+                "--previews" => cli_args.previews = true,
+                "--output" => cli_args.output = Some(value_for(&arg)?),
+                "--benchmark-grid" => cli_args.benchmark_grid = true,
+                "--manifests" => cli_args.manifests = true,
+                "--verify-assets" => cli_args.verify_assets = true,
+                "--verify-mods" => cli_args.verify_mods = true,
+                "--pack-resources" => cli_args.pack_resources = true,
+                "--no-compression" => cli_args.no_compression = true,
+                "--economy" => {
+                    let name = value_for(&arg)?;
+                    cli_args.economy = Some(parse_campaign(&name)?);
+                }
+                SET_ARG => {
+                    value_for(&arg)?;
+                }
+                arg if arg.starts_with(&format!("{}=", SET_ARG)) => (),
+                arg => return Err(format!("Unknown argument `{}`", arg)),
+            }
+        }
+
+        Ok(cli_args)
+    }
+
+    /// Returns `true` if the game should skip the main menu,
+    /// and start the campaign right away.
+    pub fn starts_ingame(&self) -> bool {
+        self.campaign.is_some() || self.level.is_some() || self.new_game
+    }
+
+    /// Applies the arguments, which change settings, to the settings.
+    pub fn apply_to_settings(&self, settings: &mut Settings) {
+        if let Some(slot) = self.save_slot {
+            for campaign_settings in vec![
+                &mut settings.level_manager.normal,
+                &mut settings.level_manager.bonus_a,
+                &mut settings.level_manager.bonus_b,
+            ] {
+                campaign_settings.savefile_path = format!(
+                    "{}_slot_{}",
+                    campaign_settings.savefile_path, slot
+                );
+            }
+        }
+    }
+}
+
+lazy_static! {
+    /// The command line arguments, parsed once on first access.
+    static ref ARGS: Result<CliArgs, String> = CliArgs::parse();
+    static ref DEFAULT_ARGS: CliArgs = CliArgs::default();
+}
+
+/// Returns the command line arguments, or the error if they are invalid.
+/// `main` calls this first, to report invalid arguments.
+pub fn parsed_args() -> Result<&'static CliArgs, String> {
+    ARGS.as_ref().map_err(Clone::clone)
+}
+
+/// Returns the command line arguments.
+/// Invalid arguments are reported in `main`, so they are ignored here.
+pub fn args() -> &'static CliArgs {
+    ARGS.as_ref().unwrap_or(&DEFAULT_ARGS)
+}
+
+pub fn usage() -> &'static str {
+    USAGE
+}
+
+fn parse_campaign(name: &str) -> Result<CampaignType, String> {
+    match name {
+        "normal" => Ok(CampaignType::Normal),
+        "bonus_a" => Ok(CampaignType::BonusA),
+        "bonus_b" => Ok(CampaignType::BonusB),
+        _ if name.starts_with(MOD_CAMPAIGN_PREFIX)
+            && name.len() > MOD_CAMPAIGN_PREFIX.len() =>
+        {
+            Ok(CampaignType::Mod(
+                name[MOD_CAMPAIGN_PREFIX.len() ..].to_string(),
+            ))
+        }
+        _ => Err(format!(
+            "Unknown campaign `{}`, expected `normal`, `bonus_a`, `bonus_b` \
+             or `{}<pack>`",
+            name, MOD_CAMPAIGN_PREFIX
+        )),
+    }
+}
@@ -32,7 +32,7 @@ impl Component for EnemyAi {
     type Storage = VecStorage<Self>;
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum EnemyType {
     Normal,
     Charger,
@@ -68,6 +68,17 @@ impl Enemy {
         }
     }
 
+    /// Applies changed settings to this enemy, keeping its current health.
+    pub fn reapply_settings(&mut self, settings: &SettingsEnemy) {
+        self.damage = settings.damage;
+        self.reward = settings.reward;
+        self.knockback = settings.knockback;
+        self.trigger_distance = settings.trigger_distance;
+        self.acceleration = settings.acceleration;
+        self.max_velocity = settings.max_velocity;
+        self.affected_by_knockback = settings.affected_by_knockback;
+    }
+
     pub fn deal_damage_to(&self, player: &mut Player) {
         player.take_damage(self.damage);
     }
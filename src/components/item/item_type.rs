@@ -7,7 +7,7 @@ use crate::settings::{SettingsItem, SettingsItems};
 
 const SPRITESHEET_FILENAME: &str = "items.png";
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum ItemType {
     ExtraJump,
     WallJump,
@@ -37,6 +37,23 @@ impl ItemType {
         }
     }
 
+    /// Returns the `ItemType` with the given name, if it exists.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ExtraJump" => Some(ItemType::ExtraJump),
+            "WallJump" => Some(ItemType::WallJump),
+            "Knockback" => Some(ItemType::Knockback),
+            "BulletShoot" => Some(ItemType::BulletShoot),
+            "Dash" => Some(ItemType::Dash),
+            "BulletDeflect" => Some(ItemType::BulletDeflect),
+            "Thrust" => Some(ItemType::Thrust),
+            "SpeedUp" => Some(ItemType::SpeedUp),
+            "JumpUp" => Some(ItemType::JumpUp),
+            "DamageUp" => Some(ItemType::DamageUp),
+            _ => None,
+        }
+    }
+
     pub fn sprite_id(&self) -> usize {
         match self {
             ItemType::ExtraJump => 0,
@@ -52,6 +69,11 @@ impl ItemType {
         }
     }
 
+    /// The spritesheet's filename, which all item types share.
+    pub fn spritesheet_filename() -> &'static str {
+        SPRITESHEET_FILENAME
+    }
+
     pub fn sprite_render(&self, world: &mut World) -> SpriteRender {
         let mut spritesheet_handles =
             world.write_resource::<SpriteSheetHandles>();
@@ -72,18 +94,9 @@ where
     T: ToString,
 {
     fn from(name: T) -> Self {
-        match name.to_string().as_str() {
-            "ExtraJump" => ItemType::ExtraJump,
-            "WallJump" => ItemType::WallJump,
-            "Knockback" => ItemType::Knockback,
-            "BulletShoot" => ItemType::BulletShoot,
-            "Dash" => ItemType::Dash,
-            "BulletDeflect" => ItemType::BulletDeflect,
-            "Thrust" => ItemType::Thrust,
-            "SpeedUp" => ItemType::SpeedUp,
-            "JumpUp" => ItemType::JumpUp,
-            "DamageUp" => ItemType::DamageUp,
-            n => panic!(format!("Item '{}' does not exist", n)),
-        }
+        let name = name.to_string();
+        ItemType::from_name(&name).unwrap_or_else(|| {
+            panic!(format!("Item '{}' does not exist", name))
+        })
     }
 }
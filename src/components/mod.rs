@@ -32,6 +32,7 @@ pub mod prelude {
     pub use super::loader::Loader;
     pub use super::no_attack::NoAttack;
     pub use super::noclip::Noclip;
+    pub use super::player::ItemsData;
     pub use super::player::Player;
     pub use super::player_attack::PlayerAttack;
     pub use super::player_heart::PlayerHeart;
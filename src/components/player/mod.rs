@@ -53,6 +53,44 @@ impl Player {
         }
     }
 
+    /// Applies changed settings to this player, keeping its current state.
+    /// Values which items increase are changed by the difference between
+    /// the old and new settings, so collected items aren't lost.
+    pub fn reapply_settings(
+        &mut self,
+        old: &SettingsPlayer,
+        new: &SettingsPlayer,
+    ) {
+        self.acceleration = new.acceleration;
+        self.air_acceleration = new.acceleration; // TODO
+        self.wall_jump_strength = new.wall_jump_strength;
+        self.decr_jump_strength = new.decr_jump_strength;
+        self.min_jump_velocity = new.min_jump_velocity;
+        self.gravity = new.gravity;
+        self.jump_gravity = new.jump_gravity;
+        self.slide_strength = new.slide_strength;
+        self.quick_turnaround = new.quick_turnaround.clone();
+        self.air_quick_turnaround = new.air_quick_turnaround.clone();
+        self.decrease_x_velocity_in_air = new.decrease_x_velocity_in_air;
+        self.max_health = new.max_health;
+        self.health = self.health.min(self.max_health);
+
+        self.jump_strength += new.jump_strength - old.jump_strength;
+        self.damage = (self.damage as i32 + new.damage as i32
+            - old.damage as i32)
+            .max(0) as u32;
+        self.max_velocity = (
+            match (self.max_velocity.0, old.max_velocity.0, new.max_velocity.0)
+            {
+                (Some(current), Some(old), Some(new)) => {
+                    Some(current + new - old)
+                }
+                (_, _, new) => new,
+            },
+            new.max_velocity.1,
+        );
+    }
+
     pub fn deal_damage_to(&self, enemy: &mut Enemy) {
         enemy.take_damage(self.damage);
     }
@@ -0,0 +1,257 @@
+//! Writes a self-contained crash report file, when the game panics.
+//! The report contains the panic message and a backtrace, the game version,
+//! the hash of the active settings, a snapshot of the game state
+//! (updated when a level is loaded, and periodically while ingame)
+//! and the last log lines.
+
+use std::collections::VecDeque;
+use std::fs::create_dir_all;
+use std::panic::{self, PanicInfo};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread;
+
+use amethyst::utils::application_root_dir;
+use log::{Log, Metadata, Record};
+
+use crate::components::prelude::ItemsData;
+use crate::level_manager::LEVEL_PARSER_THREAD_NAME;
+use crate::resource_helpers::write_new_file;
+
+const CRASH_REPORTS_DIRNAME: &str = "logs/crashes";
+/// Amount of recent log lines included in a crash report.
+const RECENT_LOG_LINES: usize = 100;
+
+/// The ingame state at the time of the crash.
+#[derive(Clone, Default)]
+pub struct GameSnapshot {
+    pub campaign:   Option<String>,
+    pub level_name: Option<String>,
+    pub player:     Option<PlayerSnapshot>,
+}
+
+#[derive(Clone)]
+pub struct PlayerSnapshot {
+    pub position:   (f32, f32),
+    pub health:     u32,
+    pub max_health: u32,
+    pub items:      ItemsData,
+}
+
+#[derive(Default)]
+struct CrashData {
+    snapshot:      GameSnapshot,
+    settings_hash: Option<u64>,
+    log_lines:     VecDeque<String>,
+}
+
+/// Collects the data for crash reports, and writes them from the panic hook.
+/// Cloning returns a handle to the same data.
+#[derive(Clone, Default)]
+pub struct CrashReporter {
+    data: Arc<Mutex<CrashData>>,
+}
+
+impl CrashReporter {
+    /// Replaces the panic hook with one, which writes a crash report.
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        panic::set_hook(Box::new(move |info| reporter.on_panic(info)));
+    }
+
+    /// Returns a logger, which records the last log lines for crash reports.
+    pub fn log_recorder(&self) -> Box<dyn Log> {
+        Box::new(LogRecorder {
+            data: self.data.clone(),
+        })
+    }
+
+    /// Sets the snapshot's campaign and level, and clears its player.
+    pub fn set_snapshot_level(
+        &self,
+        campaign: String,
+        level_name: Option<String>,
+    ) {
+        if let Some(mut data) = self.lock() {
+            data.snapshot = GameSnapshot {
+                campaign:   Some(campaign),
+                level_name: level_name,
+                player:     None,
+            };
+        }
+    }
+
+    pub fn set_snapshot_player(&self, player: Option<PlayerSnapshot>) {
+        if let Some(mut data) = self.lock() {
+            data.snapshot.player = player;
+        }
+    }
+
+    pub fn clear_snapshot(&self) {
+        if let Some(mut data) = self.lock() {
+            data.snapshot = GameSnapshot::default();
+        }
+    }
+
+    pub fn set_settings_hash(&self, hash: u64) {
+        if let Some(mut data) = self.lock() {
+            data.settings_hash = Some(hash);
+        }
+    }
+
+    /// Doesn't block, so a panic while the data is locked
+    /// (on the same thread) doesn't deadlock the panic hook.
+    fn lock(&self) -> Option<MutexGuard<CrashData>> {
+        match self.data.try_lock() {
+            Ok(data) => Some(data),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    fn on_panic(&self, info: &PanicInfo) {
+        // Panics while parsing a level are caught, and the level fails to load;
+        // the game keeps running, so they aren't crashes.
+        if thread::current().name() == Some(LEVEL_PARSER_THREAD_NAME) {
+            error!("{}", info);
+            return;
+        }
+
+        let report = self.report(info);
+        match write_report(&report) {
+            Ok(path) => eprintln!(
+                "{}\nThe game crashed; a crash report was written to `{}`",
+                info, path
+            ),
+            Err(err) => eprintln!(
+                "{}\nThe game crashed; couldn't write crash report: {}\n\n{}",
+                info, err, report
+            ),
+        }
+    }
+
+    fn report(&self, info: &PanicInfo) -> String {
+        let mut lines = vec![
+            "Stabman crash report".to_string(),
+            "====================".to_string(),
+            format!("Date:          {}", chrono::Local::now().to_rfc3339()),
+            format!(
+                "Version:       {} v{}",
+                crate::meta::NAME,
+                crate::meta::VERSION
+            ),
+        ];
+
+        match self.lock() {
+            Some(data) => {
+                lines.push(format!(
+                    "Settings hash: {}",
+                    data.settings_hash
+                        .map(|hash| format!("{:016x}", hash))
+                        .unwrap_or_else(|| "unknown".to_string())
+                ));
+                lines.push(String::new());
+                lines.push(section("Panic"));
+                lines.push(info.to_string());
+                lines.push(String::new());
+                lines.push(section("Game state"));
+                lines.append(&mut snapshot_lines(&data.snapshot));
+                lines.push(String::new());
+                lines.push(section("Last log lines"));
+                lines.extend(data.log_lines.iter().cloned());
+            }
+            None => {
+                lines.push(String::new());
+                lines.push(section("Panic"));
+                lines.push(info.to_string());
+                lines.push(String::new());
+                lines.push(
+                    "The game state and log lines are unavailable, because \
+                     the game crashed while updating them."
+                        .to_string(),
+                );
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(section("Backtrace"));
+        lines.push(format!("{:?}", backtrace::Backtrace::new()));
+        lines.join("\n")
+    }
+}
+
+struct LogRecorder {
+    data: Arc<Mutex<CrashData>>,
+}
+
+impl Log for LogRecorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        // The record's message is already formatted by the logger.
+        if let Ok(mut data) = self.data.lock() {
+            if data.log_lines.len() >= RECENT_LOG_LINES {
+                data.log_lines.pop_front();
+            }
+            data.log_lines.push_back(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn section(title: &str) -> String {
+    format!("{}\n{}", title, "-".repeat(title.len()))
+}
+
+fn snapshot_lines(snapshot: &GameSnapshot) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "Campaign: {}",
+            snapshot.campaign.as_ref().map_or("none", String::as_str)
+        ),
+        format!(
+            "Level:    {}",
+            snapshot.level_name.as_ref().map_or("none", String::as_str)
+        ),
+    ];
+    match &snapshot.player {
+        Some(player) => {
+            lines.push(format!(
+                "Player:   position ({:.1}, {:.1}), health {}/{}",
+                player.position.0,
+                player.position.1,
+                player.health,
+                player.max_health
+            ));
+            lines.push(format!(
+                "Items:\n{}",
+                ron::ser::to_string_pretty(
+                    &player.items,
+                    ron::ser::PrettyConfig::default()
+                )
+                .unwrap_or_else(|err| format!(
+                    "Couldn't serialize items: {}",
+                    err
+                ))
+            ));
+        }
+        None => lines.push("Player:   none".to_string()),
+    }
+    lines
+}
+
+/// Writes the report to a new file, and returns its path.
+fn write_report(report: &str) -> Result<String, String> {
+    let dir = format!("{}/{}", application_root_dir(), CRASH_REPORTS_DIRNAME);
+    create_dir_all(&dir).map_err(|err| {
+        format!("Couldn't create directory `{}`: {}", dir, err)
+    })?;
+    // Multiple panics within the same second get separate files.
+    let name =
+        format!("crash_{}", chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"));
+    write_new_file(&dir, &name, "txt", report).map_err(|err| {
+        format!("Couldn't write crash report to `{}`: {}", dir, err)
+    })
+}
@@ -0,0 +1,238 @@
+use amethyst::core::timing::Time;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::{Entities, Join, ReadStorage, World, WriteStorage};
+use deathframe::geo::Vector;
+use json::JsonValue;
+
+use super::{DevCommand, DevCommands};
+use crate::components::prelude::*;
+use crate::level_manager::build_enemy;
+use crate::states::helpers::{LoadLevelRequest, UpdateHealthDisplay};
+use crate::world_helpers::*;
+
+const ENEMY_TYPES: [&str; 5] =
+    ["Normal", "Charger", "Flying", "Reaper", "Turret"];
+const SPAWN_SIZE: (f32, f32) = (32.0, 32.0);
+/// Enemies are spawned this far to the right of the player.
+const SPAWN_OFFSET_X: f32 = 64.0;
+
+#[rustfmt::skip]
+pub fn builtin() -> Vec<DevCommand> {
+    vec![
+        DevCommand { name: "help",      usage: "help",               description: "List all commands",                          run: help },
+        DevCommand { name: "level",     usage: "level <name>",       description: "Load the level with the given name",         run: level },
+        DevCommand { name: "give",      usage: "give <item>",        description: "Give the player an item, e.g. `give Dash`",  run: give },
+        DevCommand { name: "health",    usage: "health <n>",         description: "Set the player's health",                    run: health },
+        DevCommand { name: "kill_all",  usage: "kill_all",           description: "Kill all (non-invincible) enemies",          run: kill_all },
+        DevCommand { name: "god",       usage: "god",                description: "Toggle player invincibility",                run: god },
+        DevCommand { name: "tp",        usage: "tp <x> <y>",         description: "Teleport the player",                        run: tp },
+        DevCommand { name: "spawn",     usage: "spawn <enemy_type>", description: "Spawn an enemy next to the player",          run: spawn },
+        DevCommand { name: "timescale", usage: "timescale [scale]",  description: "Show or set the game speed multiplier",      run: timescale },
+    ]
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    Ok(world
+        .read_resource::<DevCommands>()
+        .commands()
+        .iter()
+        .map(|command| format!("{:<20} {}", command.usage, command.description))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+fn level(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = single_arg(args, "level <name>")?;
+    let name = if name.ends_with(".json") {
+        name.to_string()
+    } else {
+        format!("{}.json", name)
+    };
+    world.write_resource::<LoadLevelRequest>().0 = Some(name.clone());
+    Ok(format!("Loading level {}", name))
+}
+
+fn give(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = single_arg(args, "give <item>")?;
+    let item_type = ItemType::from_name(name)
+        .ok_or_else(|| format!("Item `{}` does not exist", name))?;
+    let settings = world.settings();
+    let item = Item {
+        item_type: item_type,
+        cost:      0,
+    };
+    with_player(world, |player| item.apply(player, &settings.items))?;
+    Ok(format!("Gave item {}", name))
+}
+
+fn health(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let health = single_arg(args, "health <n>")?
+        .parse::<u32>()
+        .map_err(|err| format!("Invalid health: {}", err))?;
+    with_player(world, |player| {
+        player.max_health = player.max_health.max(health);
+        player.health = health;
+    })?;
+    world.write_resource::<UpdateHealthDisplay>().0 = true;
+    Ok(format!("Set health to {}", health))
+}
+
+fn kill_all(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let killed = world.exec(
+        |(mut enemies, invincibles): (
+            WriteStorage<Enemy>,
+            ReadStorage<Invincible>,
+        )| {
+            let mut killed = 0;
+            for (enemy, _) in (&mut enemies, !&invincibles).join() {
+                enemy.health = 0;
+                killed += 1;
+            }
+            killed
+        },
+    );
+    Ok(format!("Killed {} enemies", killed))
+}
+
+fn god(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let enabled = world.exec(
+        |(entities, players, mut invincibles): (
+            Entities,
+            ReadStorage<Player>,
+            WriteStorage<Invincible>,
+        )| {
+            (&entities, &players).join().next().map(|(entity, _)| {
+                if invincibles.contains(entity) {
+                    invincibles.remove(entity);
+                    false
+                } else {
+                    invincibles
+                        .insert(entity, Invincible::default())
+                        .expect("Should insert Invincible");
+                    true
+                }
+            })
+        },
+    );
+    match enabled {
+        Some(true) => Ok("God mode ENABLED".to_string()),
+        Some(false) => Ok("God mode DISABLED".to_string()),
+        None => Err(no_player()),
+    }
+}
+
+fn tp(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let (x, y) = match args {
+        [x, y] => (
+            x.parse::<f32>()
+                .map_err(|err| format!("Invalid x: {}", err))?,
+            y.parse::<f32>()
+                .map_err(|err| format!("Invalid y: {}", err))?,
+        ),
+        _ => return Err("Usage: tp <x> <y>".to_string()),
+    };
+    let teleported = world.exec(
+        |(players, mut transforms, mut velocities): (
+            ReadStorage<Player>,
+            WriteStorage<Transform>,
+            WriteStorage<Velocity>,
+        )| {
+            (&players, &mut transforms, (&mut velocities).maybe())
+                .join()
+                .next()
+                .map(|(_, transform, velocity_opt)| {
+                    transform.set_x(x);
+                    transform.set_y(y);
+                    if let Some(velocity) = velocity_opt {
+                        velocity.x = 0.0;
+                        velocity.y = 0.0;
+                    }
+                })
+                .is_some()
+        },
+    );
+    if teleported {
+        Ok(format!("Teleported to {} {}", x, y))
+    } else {
+        Err(no_player())
+    }
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let enemy_type = single_arg(args, "spawn <enemy_type>")?;
+    if !ENEMY_TYPES.contains(&enemy_type) {
+        return Err(format!(
+            "Enemy type `{}` does not exist, available types: {}",
+            enemy_type,
+            ENEMY_TYPES.join(", ")
+        ));
+    }
+
+    let player_pos = world
+        .exec(
+            |(players, transforms): (
+                ReadStorage<Player>,
+                ReadStorage<Transform>,
+            )| {
+                (&players, &transforms).join().next().map(|(_, transform)| {
+                    let translation = transform.translation();
+                    (translation.x, translation.y)
+                })
+            },
+        )
+        .ok_or_else(no_player)?;
+
+    let mut properties = JsonValue::new_object();
+    properties["enemy_type"] = enemy_type.into();
+    let pos = Vector::new(player_pos.0 + SPAWN_OFFSET_X, player_pos.1);
+    let size = Vector::new(SPAWN_SIZE.0, SPAWN_SIZE.1);
+    let settings = world.settings();
+    let entity = build_enemy(world, &settings, &pos, &size, &properties);
+
+    // Turrets' shot timers are started when the level is loaded,
+    // so spawned turrets need to start their timer themselves.
+    if let Some(EnemyAi::Turret(ai_data)) =
+        world.write_storage::<EnemyAi>().get_mut(entity)
+    {
+        ai_data.shot_timer.start().unwrap();
+    }
+
+    Ok(format!("Spawned {} at {} {}", enemy_type, pos.0, pos.1))
+}
+
+fn timescale(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut time = world.write_resource::<Time>();
+    match args {
+        [] => Ok(format!("Time scale is {}", time.time_scale())),
+        [scale] => {
+            let scale = scale
+                .parse::<f32>()
+                .map_err(|err| format!("Invalid time scale: {}", err))?;
+            if scale <= 0.0 {
+                return Err("Time scale must be greater than 0".to_string());
+            }
+            time.set_time_scale(scale);
+            Ok(format!("Set time scale to {}", scale))
+        }
+        _ => Err("Usage: timescale [scale]".to_string()),
+    }
+}
+
+fn single_arg<'a>(args: &[&'a str], usage: &str) -> Result<&'a str, String> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(format!("Usage: {}", usage)),
+    }
+}
+
+fn with_player<F>(world: &mut World, f: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Player),
+{
+    let mut players = world.write_storage::<Player>();
+    (&mut players).join().next().map(f).ok_or_else(no_player)
+}
+
+fn no_player() -> String {
+    "There is no player".to_string()
+}
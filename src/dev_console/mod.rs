@@ -0,0 +1,86 @@
+mod commands;
+
+use amethyst::ecs::World;
+
+pub mod prelude {
+    pub use super::run_command;
+    pub use super::DevCommand;
+    pub use super::DevCommandFn;
+    pub use super::DevCommands;
+    pub use super::DevConsoleLog;
+}
+
+/// A developer console command's function.
+/// Receives the command's arguments (without the command name),
+/// and returns the text to print to the console, or an error message.
+pub type DevCommandFn = fn(&mut World, &[&str]) -> Result<String, String>;
+
+#[derive(Clone)]
+pub struct DevCommand {
+    pub name:        &'static str,
+    pub usage:       &'static str,
+    pub description: &'static str,
+    pub run:         DevCommandFn,
+}
+
+/// The registry of all developer console commands.
+/// This is a resource, so anything with access to the `World`
+/// can register additional commands.
+#[derive(Default)]
+pub struct DevCommands {
+    commands: Vec<DevCommand>,
+}
+
+impl DevCommands {
+    /// Creates a new registry with all built-in commands.
+    pub fn with_builtin() -> Self {
+        let mut dev_commands = Self::default();
+        for command in commands::builtin() {
+            dev_commands.register(command);
+        }
+        dev_commands
+    }
+
+    /// Registers the command, replacing any command with the same name.
+    pub fn register(&mut self, command: DevCommand) {
+        self.commands
+            .retain(|existing| existing.name != command.name);
+        self.commands.push(command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DevCommand> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    pub fn commands(&self) -> &[DevCommand] {
+        &self.commands
+    }
+}
+
+/// The developer console's output and command history;
+/// a resource, so they are kept when the console is closed.
+#[derive(Default)]
+pub struct DevConsoleLog {
+    pub output:  Vec<String>,
+    pub history: Vec<String>,
+}
+
+/// Parses the given command line and runs the command
+/// from the `DevCommands` resource.
+pub fn run_command(world: &mut World, line: &str) -> Result<String, String> {
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return Ok(String::new()),
+    };
+    let args = words.collect::<Vec<&str>>();
+
+    let command = world
+        .read_resource::<DevCommands>()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| {
+            format!("Unknown command `{}`, see `help` for all commands", name)
+        })?;
+    (command.run)(world, &args)
+}
@@ -0,0 +1,221 @@
+use amethyst::core::transform::Transform;
+use amethyst::ecs::{Component, Entity, World};
+
+use super::InspectorField;
+use crate::components::prelude::*;
+
+type GetFn = fn(&World, Entity) -> Option<String>;
+type SetFn = fn(&mut World, Entity, f32);
+
+/// All fields the inspector can display; only the fields of components
+/// the inspected entity has are displayed.
+pub fn all() -> Vec<InspectorField> {
+    vec![
+        // Transform
+        editable(
+            "position x",
+            |w, e| get::<Transform, _>(w, e, |t| num(t.translation().x)),
+            |w, e, v| {
+                set::<Transform, _>(w, e, |t| {
+                    t.set_x(v);
+                })
+            },
+        ),
+        editable(
+            "position y",
+            |w, e| get::<Transform, _>(w, e, |t| num(t.translation().y)),
+            |w, e, v| {
+                set::<Transform, _>(w, e, |t| {
+                    t.set_y(v);
+                })
+            },
+        ),
+        // Size
+        editable(
+            "width",
+            |w, e| get::<Size, _>(w, e, |s| num(s.w)),
+            |w, e, v| set::<Size, _>(w, e, |s| s.w = v),
+        ),
+        editable(
+            "height",
+            |w, e| get::<Size, _>(w, e, |s| num(s.h)),
+            |w, e, v| set::<Size, _>(w, e, |s| s.h = v),
+        ),
+        // Velocity
+        editable(
+            "velocity x",
+            |w, e| get::<Velocity, _>(w, e, |vel| num(vel.x)),
+            |w, e, v| set::<Velocity, _>(w, e, |vel| vel.x = v),
+        ),
+        editable(
+            "velocity y",
+            |w, e| get::<Velocity, _>(w, e, |vel| num(vel.y)),
+            |w, e, v| set::<Velocity, _>(w, e, |vel| vel.y = v),
+        ),
+        // Player
+        editable(
+            "player health",
+            |w, e| get::<Player, _>(w, e, |p| p.health.to_string()),
+            |w, e, v| set::<Player, _>(w, e, |p| p.health = uint(v)),
+        ),
+        editable(
+            "player damage",
+            |w, e| get::<Player, _>(w, e, |p| p.damage.to_string()),
+            |w, e, v| set::<Player, _>(w, e, |p| p.damage = uint(v)),
+        ),
+        editable(
+            "player jump strength",
+            |w, e| get::<Player, _>(w, e, |p| num(p.jump_strength)),
+            |w, e, v| set::<Player, _>(w, e, |p| p.jump_strength = v),
+        ),
+        editable(
+            "player extra jumps",
+            |w, e| {
+                get::<Player, _>(w, e, |p| {
+                    p.items_data.extra_jump.extra_jumps.to_string()
+                })
+            },
+            |w, e, v| {
+                set::<Player, _>(w, e, |p| {
+                    p.items_data.extra_jump.extra_jumps = uint(v)
+                })
+            },
+        ),
+        editable(
+            "player dashes",
+            |w, e| {
+                get::<Player, _>(w, e, |p| p.items_data.dash.dashes.to_string())
+            },
+            |w, e, v| {
+                set::<Player, _>(w, e, |p| p.items_data.dash.dashes = uint(v))
+            },
+        ),
+        // Enemy
+        readonly("enemy type", |w, e| {
+            get::<Enemy, _>(w, e, |enemy| format!("{:?}", enemy.enemy_type))
+        }),
+        editable(
+            "enemy health",
+            |w, e| get::<Enemy, _>(w, e, |enemy| enemy.health.to_string()),
+            |w, e, v| set::<Enemy, _>(w, e, |enemy| enemy.health = uint(v)),
+        ),
+        editable(
+            "enemy damage",
+            |w, e| get::<Enemy, _>(w, e, |enemy| enemy.damage.to_string()),
+            |w, e, v| set::<Enemy, _>(w, e, |enemy| enemy.damage = uint(v)),
+        ),
+        editable(
+            "enemy reward",
+            |w, e| get::<Enemy, _>(w, e, |enemy| enemy.reward.to_string()),
+            |w, e, v| set::<Enemy, _>(w, e, |enemy| enemy.reward = uint(v)),
+        ),
+        editable(
+            "enemy trigger distance x",
+            |w, e| get::<Enemy, _>(w, e, |enemy| num(enemy.trigger_distance.0)),
+            |w, e, v| {
+                set::<Enemy, _>(w, e, |enemy| enemy.trigger_distance.0 = v)
+            },
+        ),
+        editable(
+            "enemy trigger distance y",
+            |w, e| get::<Enemy, _>(w, e, |enemy| num(enemy.trigger_distance.1)),
+            |w, e, v| {
+                set::<Enemy, _>(w, e, |enemy| enemy.trigger_distance.1 = v)
+            },
+        ),
+        editable(
+            "enemy acceleration x",
+            |w, e| get::<Enemy, _>(w, e, |enemy| num(enemy.acceleration.0)),
+            |w, e, v| set::<Enemy, _>(w, e, |enemy| enemy.acceleration.0 = v),
+        ),
+        editable(
+            "enemy acceleration y",
+            |w, e| get::<Enemy, _>(w, e, |enemy| num(enemy.acceleration.1)),
+            |w, e, v| set::<Enemy, _>(w, e, |enemy| enemy.acceleration.1 = v),
+        ),
+        // EnemyAi
+        readonly("enemy ai", |w, e| {
+            get::<EnemyAi, _>(w, e, |ai| match ai {
+                EnemyAi::Tracer => "Tracer".to_string(),
+                EnemyAi::Charger(data) => format!(
+                    "Charger (moving: {}, velocity: {} {})",
+                    data.is_moving,
+                    num(data.velocity.0),
+                    num(data.velocity.1)
+                ),
+                EnemyAi::Turret(data) => format!(
+                    "Turret (facing: {:?}, shot timer running: {})",
+                    data.facing,
+                    data.shot_timer.state.is_running()
+                ),
+            })
+        }),
+        // Item
+        readonly("item type", |w, e| {
+            get::<Item, _>(w, e, |item| format!("{:?}", item.item_type))
+        }),
+        editable(
+            "item cost",
+            |w, e| get::<Item, _>(w, e, |item| item.cost.to_string()),
+            |w, e, v| set::<Item, _>(w, e, |item| item.cost = uint(v)),
+        ),
+        // Loading and invincibility
+        readonly("loadable", |w, e| Some(has::<Loadable>(w, e).to_string())),
+        readonly("loaded", |w, e| {
+            if has::<Loadable>(w, e) {
+                Some(has::<Loaded>(w, e).to_string())
+            } else {
+                None
+            }
+        }),
+        readonly("invincible", |w, e| {
+            Some(has::<Invincible>(w, e).to_string())
+        }),
+    ]
+}
+
+fn readonly(label: &'static str, get: GetFn) -> InspectorField {
+    InspectorField {
+        label: label,
+        get:   get,
+        set:   None,
+    }
+}
+
+fn editable(label: &'static str, get: GetFn, set: SetFn) -> InspectorField {
+    InspectorField {
+        label: label,
+        get:   get,
+        set:   Some(set),
+    }
+}
+
+fn get<T, F>(world: &World, entity: Entity, f: F) -> Option<String>
+where
+    T: Component,
+    F: FnOnce(&T) -> String,
+{
+    world.read_storage::<T>().get(entity).map(f)
+}
+
+fn set<T, F>(world: &mut World, entity: Entity, f: F)
+where
+    T: Component,
+    F: FnOnce(&mut T),
+{
+    if let Some(component) = world.write_storage::<T>().get_mut(entity) {
+        f(component);
+    }
+}
+
+fn has<T: Component>(world: &World, entity: Entity) -> bool {
+    world.read_storage::<T>().contains(entity)
+}
+
+fn num(value: f32) -> String {
+    format!("{:.2}", value)
+}
+
+fn uint(value: f32) -> u32 {
+    value.max(0.0).round() as u32
+}
@@ -0,0 +1,432 @@
+mod fields;
+
+use amethyst::core::transform::Transform;
+use amethyst::ecs::{Entities, Entity, Join, ReadStorage, World};
+use amethyst::input::InputHandler;
+use amethyst::renderer::{DebugLines, ScreenDimensions, VirtualKeyCode};
+use amethyst::ui::{Anchor as AmethystAnchor, UiText};
+use amethyst::winit::{
+    ElementState,
+    Event,
+    KeyboardInput,
+    MouseButton,
+    WindowEvent,
+};
+
+use crate::components::prelude::*;
+use crate::states::helpers::{get_font, new_ui_transform};
+use crate::systems::draw_debug_rect;
+use crate::world_helpers::*;
+
+const LINE_ID_PREFIX: &str = "entity_inspector_line";
+const ENTITY_INSPECTOR_Z: f32 = 20.0;
+const PADDING: f32 = 8.0;
+const HELP_TEXT: &str = "Click/Tab: select, PgUp/PgDn: field, Return: edit";
+
+/// A field of a component, displayed by the entity inspector.
+pub struct InspectorField {
+    pub label: &'static str,
+    /// Returns the field's value, or `None` if the entity doesn't have the component.
+    pub get:   fn(&World, Entity) -> Option<String>,
+    /// Sets the field's value; `None` for read-only fields.
+    pub set:   Option<fn(&mut World, Entity, f32)>,
+}
+
+/// Displays the components of the selected entity in a panel,
+/// and lets numeric fields be edited live (development mode only).
+/// Entities are selected by clicking on them, or cycled through with `Tab`.
+/// Owned by the `Ingame` state, which forwards its window events.
+pub struct EntityInspector {
+    fields:        Vec<InspectorField>,
+    entity:        Option<Entity>,
+    /// Index into the displayed (not all) fields.
+    selected_row:  usize,
+    /// The typed value, while editing the selected field.
+    editing:       Option<String>,
+    message:       Option<String>,
+    line_entities: Vec<Entity>,
+}
+
+impl EntityInspector {
+    pub fn new(world: &mut World) -> Self {
+        let mut inspector = Self {
+            fields:        fields::all(),
+            entity:        None,
+            selected_row:  0,
+            editing:       None,
+            message:       None,
+            line_entities: Vec::new(),
+        };
+        inspector.create_ui(world);
+        inspector
+    }
+
+    fn create_ui(&mut self, world: &mut World) {
+        let settings = world.settings().entity_inspector;
+        let font = get_font(&settings.font_file, world);
+
+        self.line_entities = (0 .. settings.lines)
+            .map(|i| {
+                let mut ui_text = UiText::new(
+                    font.clone(),
+                    String::new(),
+                    settings.font_color,
+                    settings.font_size,
+                );
+                ui_text.align = AmethystAnchor::MiddleLeft;
+                world
+                    .create_entity()
+                    .with(new_ui_transform(
+                        format!("{}_{}", LINE_ID_PREFIX, i),
+                        AmethystAnchor::TopRight,
+                        (
+                            -settings.width * 0.5 - PADDING,
+                            -(i as f32 + 0.5) * settings.line_height - PADDING,
+                            ENTITY_INSPECTOR_Z,
+                            settings.width,
+                            settings.line_height,
+                            0,
+                        ),
+                    ))
+                    .with(ui_text)
+                    .build()
+            })
+            .collect();
+    }
+
+    /// Deletes the panel's entities; call before dropping the inspector.
+    pub fn delete_ui(&mut self, world: &mut World) {
+        world
+            .delete_entities(&self.line_entities)
+            .expect("Should delete entity inspector entities");
+        self.line_entities.clear();
+    }
+
+    pub fn handle_event(&mut self, world: &mut World, event: &Event) {
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } => {
+                if let Some(input) = self.editing.as_mut() {
+                    if c.is_ascii_digit() || *c == '.' || *c == '-' {
+                        input.push(*c);
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                ..
+            } => self.select_at_mouse(world),
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                modifiers,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => match key {
+                VirtualKeyCode::Tab => {
+                    self.cycle_entity(world, !modifiers.shift)
+                }
+                VirtualKeyCode::PageUp => {
+                    self.selected_row = self.selected_row.saturating_sub(1);
+                    self.editing = None;
+                }
+                VirtualKeyCode::PageDown => {
+                    self.selected_row += 1;
+                    self.editing = None;
+                }
+                VirtualKeyCode::Return => self.toggle_editing(world),
+                VirtualKeyCode::Back => {
+                    if let Some(input) = self.editing.as_mut() {
+                        input.pop();
+                    }
+                }
+                VirtualKeyCode::Escape => self.editing = None,
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    /// Updates the panel's text, and highlights the inspected entity.
+    pub fn update(&mut self, world: &mut World) {
+        if let Some(entity) = self.entity {
+            if !world.entities().is_alive(entity) {
+                self.select(None);
+            }
+        }
+
+        let rows = self.rows(world);
+        if self.selected_row >= rows.len() {
+            self.selected_row = rows.len().saturating_sub(1);
+        }
+
+        let settings = world.settings().entity_inspector;
+        let mut lines = vec![(
+            match self.entity {
+                Some(entity) => {
+                    format!("ENTITY INSPECTOR - entity {}", entity.id())
+                }
+                None => "ENTITY INSPECTOR - no entity selected".to_string(),
+            },
+            false,
+        )];
+        // Keep the selected row visible.
+        let visible_rows = settings.lines.saturating_sub(2);
+        let offset =
+            (self.selected_row + 1).saturating_sub(visible_rows.max(1));
+        for (i, (field_index, value)) in
+            rows.iter().enumerate().skip(offset).take(visible_rows)
+        {
+            let field = &self.fields[*field_index];
+            let is_selected = i == self.selected_row;
+            let value = match (&self.editing, is_selected) {
+                (Some(input), true) => format!("{}_", input),
+                _ => value.clone(),
+            };
+            let marker = match (is_selected, field.set.is_some()) {
+                (true, _) => ">",
+                (false, true) => "*",
+                (false, false) => " ",
+            };
+            lines.push((
+                format!("{} {}: {}", marker, field.label, value),
+                is_selected,
+            ));
+        }
+        while lines.len() < settings.lines.saturating_sub(1) {
+            lines.push((String::new(), false));
+        }
+        lines.push((
+            self.message.clone().unwrap_or(HELP_TEXT.to_string()),
+            false,
+        ));
+
+        {
+            let mut ui_texts = world.write_storage::<UiText>();
+            for (entity, (text, is_selected)) in
+                self.line_entities.iter().zip(lines)
+            {
+                if let Some(ui_text) = ui_texts.get_mut(*entity) {
+                    ui_text.text = text;
+                    ui_text.color = if is_selected {
+                        settings.selected_font_color
+                    } else {
+                        settings.font_color
+                    };
+                }
+            }
+        }
+
+        self.highlight_entity(world, settings.highlight_color);
+    }
+
+    /// Returns the index and value of all fields the inspected entity has.
+    fn rows(&self, world: &World) -> Vec<(usize, String)> {
+        match self.entity {
+            Some(entity) => self
+                .fields
+                .iter()
+                .enumerate()
+                .filter_map(|(i, field)| {
+                    (field.get)(world, entity).map(|value| (i, value))
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn select(&mut self, entity: Option<Entity>) {
+        self.entity = entity;
+        self.selected_row = 0;
+        self.editing = None;
+        self.message = None;
+    }
+
+    /// Starts editing the selected field, or applies the typed value.
+    fn toggle_editing(&mut self, world: &mut World) {
+        let entity = match self.entity {
+            Some(entity) => entity,
+            None => return,
+        };
+        let field_index = match self.rows(world).get(self.selected_row) {
+            Some((field_index, _)) => *field_index,
+            None => return,
+        };
+        let set = match self.fields[field_index].set {
+            Some(set) => set,
+            None => {
+                self.message = Some("This field is read-only".to_string());
+                return;
+            }
+        };
+
+        match self.editing.take() {
+            None => {
+                self.editing = Some(String::new());
+                self.message = None;
+            }
+            Some(input) => match input.parse::<f32>() {
+                Ok(value) => {
+                    set(world, entity, value);
+                    self.message = None;
+                }
+                Err(err) => {
+                    self.message = Some(format!("Invalid number: {}", err))
+                }
+            },
+        }
+    }
+
+    /// Selects the next (or previous) inspectable entity;
+    /// the player, enemies, items, bullets and goals, which are loaded.
+    fn cycle_entity(&mut self, world: &mut World, forwards: bool) {
+        let candidates = world.exec(
+            |(
+                entities,
+                transforms,
+                players,
+                enemies,
+                items,
+                bullets,
+                goals,
+                loadables,
+                loadeds,
+            ): (
+                Entities,
+                ReadStorage<Transform>,
+                ReadStorage<Player>,
+                ReadStorage<Enemy>,
+                ReadStorage<Item>,
+                ReadStorage<Bullet>,
+                ReadStorage<Goal>,
+                ReadStorage<Loadable>,
+                ReadStorage<Loaded>,
+            )| {
+                (&entities, &transforms)
+                    .join()
+                    .map(|(entity, _)| entity)
+                    .filter(|&entity| {
+                        (players.contains(entity)
+                            || enemies.contains(entity)
+                            || items.contains(entity)
+                            || bullets.contains(entity)
+                            || goals.contains(entity))
+                            && (!loadables.contains(entity)
+                                || loadeds.contains(entity))
+                    })
+                    .collect::<Vec<Entity>>()
+            },
+        );
+        if candidates.is_empty() {
+            self.select(None);
+            return;
+        }
+
+        let current_index = self
+            .entity
+            .and_then(|entity| candidates.iter().position(|&e| e == entity));
+        let index = match (current_index, forwards) {
+            (None, true) => 0,
+            (None, false) => candidates.len() - 1,
+            (Some(i), true) => (i + 1) % candidates.len(),
+            (Some(i), false) => (i + candidates.len() - 1) % candidates.len(),
+        };
+        self.select(Some(candidates[index]));
+    }
+
+    /// Selects the smallest loaded entity under the mouse cursor.
+    fn select_at_mouse(&mut self, world: &mut World) {
+        let mouse_pos = match world
+            .read_resource::<InputHandler<String, String>>()
+            .mouse_position()
+        {
+            Some(pos) => pos,
+            None => return,
+        };
+        let (screen_size, hidpi) = {
+            let screen = world.read_resource::<ScreenDimensions>();
+            (
+                (screen.width(), screen.height()),
+                screen.hidpi_factor() as f32,
+            )
+        };
+
+        let entity = world.exec(
+            |(entities, cameras, transforms, sizes, loadables, loadeds): (
+                Entities,
+                ReadStorage<Camera>,
+                ReadStorage<Transform>,
+                ReadStorage<Size>,
+                ReadStorage<Loadable>,
+                ReadStorage<Loaded>,
+            )| {
+                // The camera's position's origin is bottom-left,
+                // and the mouse position's origin is top-left.
+                let (_, camera_transform, camera_size) =
+                    (&cameras, &transforms, &sizes).join().next()?;
+                let camera_pos = camera_transform.translation();
+                let point = (
+                    camera_pos.x
+                        + mouse_pos.0 as f32 * hidpi / screen_size.0
+                            * camera_size.w,
+                    camera_pos.y
+                        + (1.0 - mouse_pos.1 as f32 * hidpi / screen_size.1)
+                            * camera_size.h,
+                );
+
+                (&entities, &transforms, &sizes, !&cameras)
+                    .join()
+                    .filter(|(entity, transform, size, _)| {
+                        let pos = transform.translation();
+                        (!loadables.contains(*entity)
+                            || loadeds.contains(*entity))
+                            && (point.0 - pos.x).abs() <= size.w * 0.5
+                            && (point.1 - pos.y).abs() <= size.h * 0.5
+                    })
+                    .min_by(|(_, _, size_a, _), (_, _, size_b, _)| {
+                        (size_a.w * size_a.h)
+                            .partial_cmp(&(size_b.w * size_b.h))
+                            .unwrap()
+                    })
+                    .map(|(entity, _, _, _)| entity)
+            },
+        );
+        self.select(entity);
+    }
+
+    fn highlight_entity(&self, world: &mut World, color: [f32; 4]) {
+        let entity = match self.entity {
+            Some(entity) => entity,
+            None => return,
+        };
+        let z = world.settings().debug_overlay.z;
+        let transforms = world.read_storage::<Transform>();
+        let sizes = world.read_storage::<Size>();
+        if let (Some(transform), Some(size)) =
+            (transforms.get(entity), sizes.get(entity))
+        {
+            let pos = transform.translation();
+            draw_debug_rect(
+                &mut world.write_resource::<DebugLines>(),
+                z,
+                (pos.x, pos.y),
+                (size.w * 0.5, size.h * 0.5),
+                color,
+            );
+        }
+    }
+}
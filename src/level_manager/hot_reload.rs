@@ -0,0 +1,268 @@
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+use amethyst::ecs::{Entities, Join, ReadStorage, WriteStorage};
+use amethyst::input::InputHandler;
+use amethyst::utils::application_root_dir;
+
+use super::level_loader::try_animations_container_from_file;
+use crate::crash_report::CrashReporter;
+use crate::settings::SettingsEnemy;
+use crate::settings_loader::load_settings_with_hash;
+use crate::states::state_prelude::*;
+use crate::user_bindings::load_bindings;
+
+/// How often the watched files' modification times are checked.
+const CHECK_INTERVAL_MS: u64 = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum WatchedKind {
+    Settings,
+    Bindings,
+    Animations,
+    Level,
+}
+
+struct WatchedFile {
+    path:     String,
+    kind:     WatchedKind,
+    modified: Option<SystemTime>,
+}
+
+/// Watches config, animation and the current level's files for changes, by polling their
+/// modification times. Only used in development mode.
+pub struct HotReloader {
+    watched:    Vec<WatchedFile>,
+    last_check: Instant,
+}
+
+impl HotReloader {
+    pub fn new() -> Self {
+        let mut watched_paths = vec![
+            (resource("config/settings.ron"), WatchedKind::Settings),
+            (
+                format!("{}/settings_overrides.ron", application_root_dir()),
+                WatchedKind::Settings,
+            ),
+            (resource("config/bindings.ron"), WatchedKind::Bindings),
+        ];
+        if let Ok(entries) = fs::read_dir(resource("animations")) {
+            for entry in entries.filter_map(Result::ok) {
+                let path = entry.path();
+                if path.extension().map_or(false, |ext| ext == "ron") {
+                    watched_paths.push((
+                        path.to_string_lossy().to_string(),
+                        WatchedKind::Animations,
+                    ));
+                }
+            }
+        }
+
+        Self {
+            watched:    watched_paths
+                .into_iter()
+                .map(|(path, kind)| WatchedFile {
+                    modified: modified_time(&path),
+                    path:     path,
+                    kind:     kind,
+                })
+                .collect(),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Watches the given level file, instead of the previously watched one.
+    pub fn watch_level(&mut self, path: String) {
+        self.watched.retain(|file| file.kind != WatchedKind::Level);
+        self.watched.push(WatchedFile {
+            modified: modified_time(&path),
+            path:     path,
+            kind:     WatchedKind::Level,
+        });
+    }
+
+    /// Returns the kinds of watched files, which were modified
+    /// since the last check.
+    pub fn changed(&mut self) -> Vec<WatchedKind> {
+        if self.last_check.elapsed() < Duration::from_millis(CHECK_INTERVAL_MS)
+        {
+            return Vec::new();
+        }
+        self.last_check = Instant::now();
+
+        let mut changed = Vec::new();
+        for file in self.watched.iter_mut() {
+            let modified = modified_time(&file.path);
+            if modified != file.modified {
+                file.modified = modified;
+                if !changed.contains(&file.kind) {
+                    changed.push(file.kind);
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Reloads the `Settings` resource, and applies the changed tunables
+/// to the existing `Player` and `Enemy` components,
+/// and to the given player checkpoint.
+pub fn reload_settings(
+    world: &mut World,
+    player_checkpoint_opt: Option<&mut Player>,
+) {
+    let (new_settings, settings_hash) = match load_settings_with_hash() {
+        Ok(settings_and_hash) => settings_and_hash,
+        Err(err) => {
+            error!("Couldn't reload settings: {}", err);
+            return;
+        }
+    };
+    let old_settings = world.settings();
+
+    world.exec(
+        |(mut players, mut enemies, mut decr_velocities, mut gravities): (
+            WriteStorage<Player>,
+            WriteStorage<Enemy>,
+            WriteStorage<DecreaseVelocity>,
+            WriteStorage<Gravity>,
+        )| {
+            for (player, decr_velocity_opt) in
+                (&mut players, (&mut decr_velocities).maybe()).join()
+            {
+                player.reapply_settings(
+                    &old_settings.player,
+                    &new_settings.player,
+                );
+                if let Some(decr_velocity) = decr_velocity_opt {
+                    *decr_velocity = DecreaseVelocity::from(
+                        new_settings.player.decr_velocity,
+                    );
+                }
+            }
+            for (enemy, decr_velocity_opt, gravity_opt) in (
+                &mut enemies,
+                (&mut decr_velocities).maybe(),
+                (&mut gravities).maybe(),
+            )
+                .join()
+            {
+                let enemy_settings =
+                    enemy_settings_for(&new_settings, &enemy.enemy_type);
+                enemy.reapply_settings(enemy_settings);
+                if let Some(decr_velocity) = decr_velocity_opt {
+                    *decr_velocity =
+                        DecreaseVelocity::from(enemy_settings.decr_velocity);
+                }
+                if let Some(gravity) = gravity_opt {
+                    *gravity = Gravity::from(new_settings.enemies.gravity);
+                }
+            }
+        },
+    );
+
+    if let Some(player_checkpoint) = player_checkpoint_opt {
+        player_checkpoint
+            .reapply_settings(&old_settings.player, &new_settings.player);
+    }
+
+    world.add_resource(new_settings);
+    world
+        .read_resource::<CrashReporter>()
+        .set_settings_hash(settings_hash);
+    info!("Reloaded settings");
+}
+
+/// Reloads the default bindings merged with the user's bindings.
+pub fn reload_bindings(world: &mut World) {
+    match load_bindings() {
+        Ok(bindings) => {
+            world
+                .write_resource::<InputHandler<String, String>>()
+                .bindings = bindings;
+            info!("Reloaded bindings");
+        }
+        Err(err) => {
+            error!("Couldn't reload bindings: {}", err)
+        }
+    }
+}
+
+/// Reloads the `AnimationsContainer`s of the player, the player's attack
+/// and all enemies from their animation files.
+pub fn reload_animations(world: &mut World) {
+    world.exec(
+        |(
+            entities,
+            players,
+            player_attacks,
+            enemies,
+            sprite_renders,
+            mut animations_containers,
+        ): (
+            Entities,
+            ReadStorage<Player>,
+            ReadStorage<PlayerAttack>,
+            ReadStorage<Enemy>,
+            ReadStorage<SpriteRender>,
+            WriteStorage<AnimationsContainer>,
+        )| {
+            for (entity, sprite_render) in (&entities, &sprite_renders).join() {
+                let file = if players.contains(entity) {
+                    "player.ron"
+                } else if player_attacks.contains(entity) {
+                    "player_attack.ron"
+                } else if let Some(enemy) = enemies.get(entity) {
+                    enemy_animations_file(&enemy.enemy_type)
+                } else {
+                    continue;
+                };
+
+                match try_animations_container_from_file(
+                    resource(format!("animations/{}", file)),
+                    sprite_render.sprite_sheet.clone(),
+                ) {
+                    Ok(animations_container) => {
+                        animations_containers
+                            .insert(entity, animations_container)
+                            .expect("Should insert AnimationsContainer");
+                    }
+                    Err(err) => {
+                        error!("Couldn't reload animations: {}", err);
+                        return;
+                    }
+                }
+            }
+            info!("Reloaded animations");
+        },
+    );
+}
+
+fn enemy_settings_for<'a>(
+    settings: &'a Settings,
+    enemy_type: &EnemyType,
+) -> &'a SettingsEnemy {
+    match enemy_type {
+        EnemyType::Normal => &settings.enemies.normal,
+        EnemyType::Charger => &settings.enemies.charger,
+        EnemyType::Flying => &settings.enemies.flying,
+        EnemyType::Reaper => &settings.enemies.reaper,
+        EnemyType::Turret => &settings.enemies.turret,
+    }
+}
+
+fn enemy_animations_file(enemy_type: &EnemyType) -> &'static str {
+    match enemy_type {
+        EnemyType::Normal => "enemy_normal.ron",
+        EnemyType::Charger => "enemy_charger.ron",
+        EnemyType::Flying => "enemy_flying.ron",
+        EnemyType::Reaper => "enemy_reaper.ron",
+        EnemyType::Turret => "enemy_turret.ron",
+    }
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
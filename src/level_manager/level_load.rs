@@ -0,0 +1,114 @@
+//! A level, which is loaded by the `LevelLoading` state.
+//! The level file is read and parsed on a background thread,
+//! the entities are built on the main thread once parsing has finished.
+
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use climer::Time;
+
+use super::level_loader::LevelLoader;
+use crate::settings::SettingsLevelManagerCampaign;
+
+/// Name of the background thread, which parses the level file.
+/// Its panics are caught, so the panic hook doesn't report them as crashes.
+pub const LEVEL_PARSER_THREAD_NAME: &str = "level_parser";
+
+/// Everything needed to load a level, and to display it on the loading screen.
+pub struct LevelLoad {
+    pub level_name:    String,
+    pub best_time:     Option<Time>,
+    pub song_filepath: String,
+    settings:          SettingsLevelManagerCampaign,
+    level_filepath:    String,
+}
+
+/// The state of the background thread, which parses the level file.
+pub enum ParseStatus {
+    Parsing,
+    Parsed(LevelLoader),
+    Failed(String),
+}
+
+/// Receives the parsed level, or the reason parsing failed,
+/// from the background thread.
+pub struct LevelParser {
+    receiver: Receiver<Result<LevelLoader, String>>,
+}
+
+impl LevelLoad {
+    pub fn new(
+        level_name: String,
+        best_time: Option<Time>,
+        song_filepath: String,
+        settings: SettingsLevelManagerCampaign,
+        level_filepath: String,
+    ) -> Self {
+        Self {
+            level_name:     level_name,
+            best_time:      best_time,
+            song_filepath:  song_filepath,
+            settings:       settings,
+            level_filepath: level_filepath,
+        }
+    }
+
+    /// Starts reading and parsing the level file on a background thread.
+    /// A level file, which can't be read or parsed, is sent as an error;
+    /// so is a panic while loading the parsed data (which the panic hook logs),
+    /// so the thread always sends a result.
+    pub fn start_parsing(&self) -> LevelParser {
+        let (sender, receiver) = mpsc::channel();
+        let settings = self.settings.clone();
+        let level_filepath = self.level_filepath.clone();
+
+        thread::Builder::new()
+            .name(LEVEL_PARSER_THREAD_NAME.to_string())
+            .spawn(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(
+                    || -> Result<LevelLoader, String> {
+                        let mut level_loader = LevelLoader::new(settings);
+                        level_loader.load_level(&level_filepath)?;
+                        Ok(level_loader)
+                    },
+                ))
+                .unwrap_or_else(|payload| {
+                    Err(format!(
+                        "Panicked while loading level {}: {}",
+                        level_filepath,
+                        panic_message(&*payload)
+                    ))
+                });
+                // The receiver is gone if the loading state was left early.
+                let _ = sender.send(result);
+            })
+            .expect("Should spawn level parser thread");
+
+        LevelParser { receiver: receiver }
+    }
+}
+
+impl LevelParser {
+    /// Returns the parsed level, once the background thread has finished.
+    pub fn poll(&self) -> ParseStatus {
+        match self.receiver.try_recv() {
+            Ok(Ok(level_loader)) => ParseStatus::Parsed(level_loader),
+            Ok(Err(err)) => ParseStatus::Failed(err),
+            Err(TryRecvError::Empty) => ParseStatus::Parsing,
+            Err(TryRecvError::Disconnected) => ParseStatus::Failed(
+                "Level parser thread stopped without a result".to_string(),
+            ),
+        }
+    }
+}
+
+/// The message of a caught panic, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| *message)
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Unknown panic")
+}
@@ -17,6 +17,18 @@ const ENEMY_FLYING_SPRITESHEET_FILENAME: &str = "enemy_flying.png";
 const ENEMY_REAPER_SPRITESHEET_FILENAME: &str = "enemy_reaper.png";
 const ENEMY_TURRET_SPRITESHEET_FILENAME: &str = "enemy_turret.png";
 
+/// Returns the spritesheet filename of the enemy type, if the type exists.
+pub fn enemy_spritesheet_filename(enemy_type: &str) -> Option<&'static str> {
+    match enemy_type {
+        "Normal" => Some(ENEMY_NORMAL_SPRITESHEET_FILENAME),
+        "Charger" => Some(ENEMY_CHARGER_SPRITESHEET_FILENAME),
+        "Flying" => Some(ENEMY_FLYING_SPRITESHEET_FILENAME),
+        "Reaper" => Some(ENEMY_REAPER_SPRITESHEET_FILENAME),
+        "Turret" => Some(ENEMY_TURRET_SPRITESHEET_FILENAME),
+        _ => None,
+    }
+}
+
 pub fn enemy_components_from(
     world: &mut World,
     properties: &JsonValue,
@@ -269,7 +281,20 @@ pub fn animations_container_from_file<T>(
 where
     T: ToString,
 {
-    let animations_container_config = load_animations_container_config(file);
+    try_animations_container_from_file(file, spritesheet_handle)
+        .unwrap_or_else(|err| panic!(err))
+}
+
+/// Generate a AnimationsContainer from the the given animations ron file,
+/// returning an error if the file couldn't be read or parsed.
+pub fn try_animations_container_from_file<T>(
+    file: T,
+    spritesheet_handle: SpriteSheetHandle,
+) -> Result<AnimationsContainer, String>
+where
+    T: ToString,
+{
+    let animations_container_config = load_animations_container_config(file)?;
     let mut animations_container = AnimationsContainer::new();
 
     for animation_config in animations_container_config.animations {
@@ -291,7 +316,7 @@ where
         animations_container = animations_container.current(current);
     }
 
-    animations_container.build()
+    Ok(animations_container.build())
 }
 
 #[derive(Deserialize)]
@@ -308,14 +333,16 @@ struct AnimationsContainerConfig {
     pub current:    Option<String>,
 }
 
-fn load_animations_container_config<T>(file: T) -> AnimationsContainerConfig
+fn load_animations_container_config<T>(
+    file: T,
+) -> Result<AnimationsContainerConfig, String>
 where
     T: ToString,
 {
-    let settings_raw = read_file(file.to_string())
-        .expect(&format!("Couldn't read file {}", file.to_string()));
+    let file = file.to_string();
+    let settings_raw = read_file(&file)
+        .map_err(|err| format!("Couldn't read file {}: {}", file, err))?;
     ron::Value::from_str(&settings_raw)
-        .unwrap()
-        .into_rust()
-        .unwrap()
+        .and_then(|value| value.into_rust())
+        .map_err(|err| format!("Couldn't parse file {}: {}", file, err))
 }
@@ -1,7 +1,5 @@
 mod helpers;
-
-use std::fs::File;
-use std::io::prelude::*;
+mod tile_colliders;
 
 use amethyst::ecs::world::Index;
 use deathframe::geo::{Anchor, Rect, Vector};
@@ -12,6 +10,31 @@ use crate::settings::SettingsLevelManagerCampaign;
 use crate::solid_tag::SolidTag;
 use crate::states::state_prelude::*;
 use helpers::*;
+use tile_colliders::{max_merged_tiles, merge_solid_tiles, TileCollider};
+
+pub use helpers::{
+    enemy_spritesheet_filename,
+    try_animations_container_from_file,
+};
+
+/// An asset, which a level object uses.
+/// Paths are relative to the resources directory.
+pub enum ObjectAsset {
+    Spritesheet(String),
+    Texture(String),
+}
+
+/// The names of the components, which a tile's `components` property may list;
+/// they are added by deathframe's `add_component_to_entity_by_name`,
+/// which panics for any other name.
+pub const TILE_COMPONENT_NAMES: [&str; 6] = [
+    "Velocity",
+    "MaxVelocity",
+    "Gravity",
+    "Push",
+    "Collision",
+    "CheckCollision",
+];
 
 const PROPERTY_Z_KEY: &str = "z";
 const PLAYER_Z: f32 = 0.5;
@@ -78,22 +101,23 @@ impl LevelLoader {
     }
 
     /// Start loading the level data from the given level filename.
-    pub fn load_level<T>(&mut self, filepath: T)
+    /// Returns an error, if the level file can't be read or isn't valid JSON.
+    pub fn load_level<T>(&mut self, filepath: T) -> Result<(), String>
     where
         T: ToString,
     {
         let filepath = filepath.to_string();
-        let mut file = File::open(&filepath)
-            .expect(&format!("Should open file for reading: {}", filepath));
-        let mut json_raw = String::new();
-        file.read_to_string(&mut json_raw)
-            .expect(&format!("Should read file content: {}", filepath));
-        let json = json::parse(&json_raw)
-            .expect(&format!("Could not parse JSON for level: {}", filepath));
+        let json_raw = read_file(&filepath).map_err(|err| {
+            format!("Couldn't read level file {}: {}", filepath, err)
+        })?;
+        let json = json::parse(&json_raw).map_err(|err| {
+            format!("Couldn't parse JSON for level {}: {}", filepath, err)
+        })?;
 
         self.load_level_data(&json["level"]);
         self.load_objects(&json["objects"]);
         self.load_tiles(&json["tiles"]);
+        Ok(())
     }
 
     /// Builds the loaded data using the given `StateData`.
@@ -202,7 +226,7 @@ impl LevelLoader {
                 tile_data["ts"].as_str(),
             ) {
                 let spritesheet_path =
-                    resource(format!("spritesheets/{}.png", tileset_name));
+                    self.settings.resource(tileset_spritesheet(tileset_name));
 
                 let size = self.settings.tile_size;
                 let pos = Vector::new(x + size.0 * 0.5, y - size.1 * 0.5);
@@ -396,12 +420,30 @@ impl LevelLoader {
     }
 
     fn build_tiles(&self, data: &mut StateData<CustomGameData<CustomData>>) {
-        for EntityData {
-            pos,
-            size,
-            properties,
-            graphic,
-        } in &self.tiles_data
+        let max_tiles = max_merged_tiles(
+            &data.world.settings().entity_loader,
+            self.settings.tile_size,
+        );
+        let (colliders, merged_tiles) = merge_solid_tiles(
+            &self.tiles_data,
+            self.settings.tile_size,
+            max_tiles,
+        );
+        debug!(
+            "Merged {} solid tiles into {} colliders",
+            merged_tiles.len(),
+            colliders.len()
+        );
+
+        for (
+            index,
+            EntityData {
+                pos,
+                size,
+                properties,
+                graphic,
+            },
+        ) in self.tiles_data.iter().enumerate()
         {
             let mut transform = Transform::default();
             transform.set_xyz(
@@ -471,27 +513,32 @@ impl LevelLoader {
                 entity = entity.with(Loadable);
             }
 
-            if let Some(is_solid) = properties["solid"].as_bool() {
-                if is_solid {
-                    entity = entity
-                        .with(Solid::new(SolidTag::default()))
-                        .with(Collision::new());
+            // Merged tiles' collisions are handled by their `TileCollider`.
+            if !merged_tiles.contains(&index) {
+                if let Some(is_solid) = properties["solid"].as_bool() {
+                    if is_solid {
+                        entity = entity
+                            .with(Solid::new(SolidTag::default()))
+                            .with(Collision::new());
+                    }
                 }
-            }
 
-            if let Some(harmful_damage) = properties["harmful"].as_u32() {
-                entity = entity
-                    .with(Collision::new())
-                    .with(Harmful::with_damage(harmful_damage));
+                if let Some(harmful_damage) = properties["harmful"].as_u32() {
+                    entity = entity
+                        .with(Collision::new())
+                        .with(Harmful::with_damage(harmful_damage));
+                }
             }
 
             entity.build();
         }
+
+        for collider in colliders {
+            build_tile_collider(data, collider);
+        }
     }
 
     fn build_parallax(&self, data: &mut StateData<CustomGameData<CustomData>>) {
-        let bg_dir = resource(BACKGROUNDS_DIR);
-
         for EntityData {
             pos,
             size,
@@ -512,13 +559,13 @@ impl LevelLoader {
                 {
                     let mut texture_handles =
                         data.world.write_resource::<TextureHandles>();
-                    let filepath = format!(
+                    let filepath = self.settings.resource(format!(
                         "{}/{}",
-                        bg_dir,
+                        BACKGROUNDS_DIR,
                         bg_filename.as_str().expect(
                             "Couldn't parse background image filename as str"
                         )
-                    );
+                    ));
                     Some(texture_handles.get_or_load(filepath, data.world))
                 } else {
                     None
@@ -682,70 +729,7 @@ impl LevelLoader {
             graphic: _,
         } in &self.enemies_data
         {
-            let (
-                enemy_type,
-                enemy_settings,
-                enemy_ai,
-                sprite_render,
-                animations_container,
-                flipped_opt,
-            ) = enemy_components_from(&mut data.world, properties);
-
-            let mut transform = Transform::default();
-            transform.set_xyz(
-                pos.0,
-                pos.1,
-                properties[PROPERTY_Z_KEY].as_f32().unwrap_or(ENEMY_Z),
-            );
-
-            let heart_size = Vector::new(16.0, 16.0);
-
-            let mut entity = data
-                .world
-                .create_entity()
-                .with(transform)
-                .with(Size::from(*size))
-                .with(ScaleOnce)
-                .with(
-                    HeartsContainer::new()
-                        .health(enemy_settings.health)
-                        .heart_offset(Vector::new(
-                            0.0,
-                            size.1 * -0.5 + heart_size.1 * -0.5,
-                        ))
-                        .heart_size(heart_size)
-                        .build(),
-                )
-                .with(Enemy::new(enemy_type.clone(), enemy_settings.clone()))
-                .with(sprite_render)
-                .with(flipped_opt.unwrap_or(Flipped::None))
-                .with(animations_container)
-                .with(Transparent)
-                .with(enemy_ai);
-
-            entity = match enemy_type {
-                EnemyType::Turret => {
-                    entity.with(NoAttack::default()).with(Invincible::default())
-                }
-                enemy_type => {
-                    let mut e = entity
-                        .with(Harmable)
-                        .with(Velocity::default())
-                        .with(DecreaseVelocity::from(
-                            enemy_settings.decr_velocity,
-                        ))
-                        .with(Collision::new())
-                        .with(CheckCollision)
-                        .with(Solid::new(SolidTag::Enemy))
-                        .with(Loadable);
-                    if enemy_type != EnemyType::Flying {
-                        e = e.with(Gravity::from(settings.enemies.gravity));
-                    }
-                    e
-                }
-            };
-
-            entity.build();
+            build_enemy(&mut data.world, &settings, pos, size, properties);
         }
     }
 
@@ -831,6 +815,33 @@ impl LevelLoader {
     }
 }
 
+/// Creates an invisible entity, which collides for the merged solid tiles it covers.
+fn build_tile_collider(
+    data: &mut StateData<CustomGameData<CustomData>>,
+    collider: TileCollider,
+) {
+    let mut transform = Transform::default();
+    transform.set_xyz(collider.pos.0, collider.pos.1, TILE_Z);
+
+    let mut entity = data
+        .world
+        .create_entity()
+        .with(transform)
+        .with(Size::from(collider.size))
+        .with(Solid::new(SolidTag::default()))
+        .with(Collision::new());
+
+    if !collider.always_loaded {
+        entity = entity.with(Loadable);
+    }
+
+    if let Some(harmful_damage) = collider.harmful {
+        entity = entity.with(Harmful::with_damage(harmful_damage));
+    }
+
+    entity.build();
+}
+
 fn parse_string_to_vector<T>(string: T) -> Vector
 where
     T: ToString,
@@ -854,3 +865,109 @@ where
         ));
     }
 }
+
+/// Returns the spritesheet of the tileset, relative to the resources directory.
+pub fn tileset_spritesheet(tileset_name: &str) -> String {
+    format!("spritesheets/{}.png", tileset_name)
+}
+
+/// Returns the assets, which the `LevelLoader` loads for the level object
+/// with the given type and properties.
+pub fn object_assets(
+    object_type: &str,
+    properties: &JsonValue,
+) -> Vec<ObjectAsset> {
+    let spritesheet = |filename: &str| {
+        ObjectAsset::Spritesheet(format!("spritesheets/{}", filename))
+    };
+    match object_type {
+        "Player" => vec![spritesheet(PLAYER_SPRITESHEET_FILENAME)],
+        "Enemy" => properties["enemy_type"]
+            .as_str()
+            .and_then(enemy_spritesheet_filename)
+            .map(spritesheet)
+            .into_iter()
+            .collect(),
+        "Item" => vec![spritesheet(ItemType::spritesheet_filename())],
+        "Parallax" => properties["image"]
+            .as_str()
+            .map(|image| {
+                ObjectAsset::Texture(format!("{}/{}", BACKGROUNDS_DIR, image))
+            })
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Creates an enemy entity, with the given position, size and
+/// level object properties (`enemy_type`, `facing`, `z`).
+pub fn build_enemy(
+    world: &mut World,
+    settings: &Settings,
+    pos: &Vector,
+    size: &Vector,
+    properties: &JsonValue,
+) -> Entity {
+    let (
+        enemy_type,
+        enemy_settings,
+        enemy_ai,
+        sprite_render,
+        animations_container,
+        flipped_opt,
+    ) = enemy_components_from(world, properties);
+
+    let mut transform = Transform::default();
+    transform.set_xyz(
+        pos.0,
+        pos.1,
+        properties[PROPERTY_Z_KEY].as_f32().unwrap_or(ENEMY_Z),
+    );
+
+    let heart_size = Vector::new(16.0, 16.0);
+
+    let mut entity = world
+        .create_entity()
+        .with(transform)
+        .with(Size::from(*size))
+        .with(ScaleOnce)
+        .with(
+            HeartsContainer::new()
+                .health(enemy_settings.health)
+                .heart_offset(Vector::new(
+                    0.0,
+                    size.1 * -0.5 + heart_size.1 * -0.5,
+                ))
+                .heart_size(heart_size)
+                .build(),
+        )
+        .with(Enemy::new(enemy_type.clone(), enemy_settings.clone()))
+        .with(sprite_render)
+        .with(flipped_opt.unwrap_or(Flipped::None))
+        .with(animations_container)
+        .with(Transparent)
+        .with(enemy_ai);
+
+    entity = match enemy_type {
+        EnemyType::Turret => {
+            entity.with(NoAttack::default()).with(Invincible::default())
+        }
+        enemy_type => {
+            let mut e = entity
+                .with(Harmable)
+                .with(Velocity::default())
+                .with(DecreaseVelocity::from(enemy_settings.decr_velocity))
+                .with(Collision::new())
+                .with(CheckCollision)
+                .with(Solid::new(SolidTag::Enemy))
+                .with(Loadable);
+            if enemy_type != EnemyType::Flying {
+                e = e.with(Gravity::from(settings.enemies.gravity));
+            }
+            e
+        }
+    };
+
+    entity.build()
+}
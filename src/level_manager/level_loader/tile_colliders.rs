@@ -0,0 +1,298 @@
+//! Merges contiguous solid tiles into larger collision rectangles,
+//! so collision checking and the `LoaderSystem` deal with fewer entities.
+//! The tiles themselves are still built as separate entities, for their sprites.
+
+use std::collections::{HashMap, HashSet};
+
+use deathframe::geo::Vector;
+
+use super::EntityData;
+use crate::settings::SettingsEntityLoader;
+
+/// Tiles which are this close to the tile grid are considered aligned to it.
+const GRID_EPSILON: f32 = 0.01;
+
+/// A collision rectangle, covering one or more solid tiles.
+pub struct TileCollider {
+    /// Center position.
+    pub pos:           Vector,
+    pub size:          Vector,
+    pub always_loaded: bool,
+    pub harmful:       Option<u32>,
+}
+
+/// Tiles can only be merged if these properties are the same.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct MergeKey {
+    always_loaded: bool,
+    harmful:       Option<u32>,
+}
+
+/// Column and row of a tile on the tile grid;
+/// the row is counted from the bottom of the level, at the tile's top edge.
+type Cell = (i32, i32);
+
+/// Returns the maximum width and height of a collider, in tiles.
+/// The `LoaderSystem` loads entities by the distance to their center, and bullets
+/// only load entities within `bullet_load_distance` of them, so a collider may
+/// only extend that far from its center, or bullets hitting its edges wouldn't load it.
+/// At least a single tile is always allowed.
+pub fn max_merged_tiles(
+    entity_loader: &SettingsEntityLoader,
+    tile_size: Vector,
+) -> (i32, i32) {
+    let max_tiles = |distance: f32, tile_size: f32| {
+        ((distance * 2.0 / tile_size + GRID_EPSILON).floor() as i32).max(1)
+    };
+    let distance = entity_loader.bullet_load_distance;
+    (
+        max_tiles(distance.0, tile_size.0),
+        max_tiles(distance.1, tile_size.1),
+    )
+}
+
+/// Merges the solid tiles into collision rectangles, using greedy meshing:
+/// starting at the top-left, each rectangle is extended to the right as far as possible,
+/// and then downwards for as long as the full row is covered,
+/// up to `max_tiles` (see `max_merged_tiles`) in each direction.
+/// Returns the colliders, and the indices of the tiles they cover;
+/// these tiles must not get their own `Solid` and `Collision` components.
+pub fn merge_solid_tiles(
+    tiles: &[EntityData],
+    tile_size: Vector,
+    max_tiles: (i32, i32),
+) -> (Vec<TileCollider>, HashSet<usize>) {
+    let mut groups: HashMap<MergeKey, HashSet<Cell>> = HashMap::new();
+    let mut merged_tiles = HashSet::new();
+
+    for (index, tile) in tiles.iter().enumerate() {
+        if !is_mergeable(tile) {
+            continue;
+        }
+        if let Some(cell) = grid_cell(tile, tile_size) {
+            let key = MergeKey {
+                always_loaded: tile.properties["always_loaded"]
+                    .as_bool()
+                    .unwrap_or(false),
+                harmful:       tile.properties["harmful"].as_u32(),
+            };
+            // Stacked tiles at the same cell share a collider.
+            groups.entry(key).or_insert_with(HashSet::new).insert(cell);
+            merged_tiles.insert(index);
+        }
+    }
+
+    let mut colliders = Vec::new();
+    for (key, mut remaining) in groups {
+        let mut cells: Vec<Cell> = remaining.iter().cloned().collect();
+        cells.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for (col, row) in cells {
+            if !remaining.contains(&(col, row)) {
+                continue;
+            }
+
+            let mut width = 1;
+            while width < max_tiles.0 && remaining.contains(&(col + width, row))
+            {
+                width += 1;
+            }
+            let mut height = 1;
+            while height < max_tiles.1
+                && (col .. col + width)
+                    .all(|c| remaining.contains(&(c, row - height)))
+            {
+                height += 1;
+            }
+
+            for r in (row - height + 1) ..= row {
+                for c in col .. col + width {
+                    remaining.remove(&(c, r));
+                }
+            }
+
+            colliders.push(TileCollider {
+                pos:           Vector::new(
+                    (col as f32 + width as f32 * 0.5) * tile_size.0,
+                    (row as f32 - height as f32 * 0.5) * tile_size.1,
+                ),
+                size:          Vector::new(
+                    width as f32 * tile_size.0,
+                    height as f32 * tile_size.1,
+                ),
+                always_loaded: key.always_loaded,
+                harmful:       key.harmful,
+            });
+        }
+    }
+
+    (colliders, merged_tiles)
+}
+
+/// Only plain solid tiles are merged; tiles with custom components
+/// or a different size keep their own collider.
+fn is_mergeable(tile: &EntityData) -> bool {
+    tile.properties["solid"].as_bool().unwrap_or(false)
+        && tile.properties["components"].is_empty()
+}
+
+/// Returns the tile's cell on the tile grid,
+/// or `None` if it isn't aligned to the grid.
+fn grid_cell(tile: &EntityData, tile_size: Vector) -> Option<Cell> {
+    if (tile.size.0 - tile_size.0).abs() > GRID_EPSILON
+        || (tile.size.1 - tile_size.1).abs() > GRID_EPSILON
+    {
+        return None;
+    }
+    let col = (tile.pos.0 - tile_size.0 * 0.5) / tile_size.0;
+    let row = (tile.pos.1 + tile_size.1 * 0.5) / tile_size.1;
+    if (col - col.round()).abs() > GRID_EPSILON
+        || (row - row.round()).abs() > GRID_EPSILON
+    {
+        return None;
+    }
+    Some((col.round() as i32, row.round() as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE_SIZE: (f32, f32) = (32.0, 32.0);
+
+    /// A tile at the given column and row (see `Cell`).
+    fn tile(col: i32, row: i32, properties: &str) -> EntityData {
+        EntityData {
+            pos:        Vector::new(
+                (col as f32 + 0.5) * TILE_SIZE.0,
+                (row as f32 - 0.5) * TILE_SIZE.1,
+            ),
+            size:       TILE_SIZE.into(),
+            properties: json::parse(properties).unwrap(),
+            graphic:    None,
+        }
+    }
+
+    fn solid(col: i32, row: i32) -> EntityData {
+        tile(col, row, r#"{ "solid": true }"#)
+    }
+
+    /// Returns the colliders' sizes in tiles, sorted.
+    fn sizes_in_tiles(colliders: &[TileCollider]) -> Vec<(i32, i32)> {
+        let mut sizes = colliders
+            .iter()
+            .map(|collider| {
+                (
+                    (collider.size.0 / TILE_SIZE.0).round() as i32,
+                    (collider.size.1 / TILE_SIZE.1).round() as i32,
+                )
+            })
+            .collect::<Vec<_>>();
+        sizes.sort();
+        sizes
+    }
+
+    #[test]
+    fn max_merged_tiles_fit_bullet_load_distance() {
+        let entity_loader = SettingsEntityLoader {
+            load_distance:                  (256.0, 256.0),
+            enemy_load_distance_difference: (72.0, 72.0),
+            bullet_load_distance:           (64.0, 40.0),
+            grid_cell_size:                 256.0,
+        };
+        assert_eq!(max_merged_tiles(&entity_loader, TILE_SIZE.into()), (4, 2));
+        assert_eq!(
+            max_merged_tiles(&entity_loader, (256.0, 256.0).into()),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn merges_rectangle() {
+        let tiles = (0 .. 3)
+            .flat_map(|col| (0 .. 2).map(move |row| solid(col, row)))
+            .collect::<Vec<_>>();
+        let (colliders, merged_tiles) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+
+        assert_eq!(sizes_in_tiles(&colliders), vec![(3, 2)]);
+        assert_eq!(merged_tiles.len(), tiles.len());
+        let collider = &colliders[0];
+        assert_eq!((collider.pos.0, collider.pos.1), (48.0, 0.0));
+    }
+
+    #[test]
+    fn splits_at_cap() {
+        let tiles = (0 .. 10).map(|col| solid(col, 0)).collect::<Vec<_>>();
+        let (colliders, _) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+        assert_eq!(sizes_in_tiles(&colliders), vec![(2, 1), (4, 1), (4, 1)]);
+
+        let tiles = (0 .. 3).map(|row| solid(0, row)).collect::<Vec<_>>();
+        let (colliders, _) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 2));
+        assert_eq!(sizes_in_tiles(&colliders), vec![(1, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn splits_at_gaps() {
+        let tiles = vec![solid(0, 0), solid(1, 0), solid(3, 0)];
+        let (colliders, _) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+        assert_eq!(sizes_in_tiles(&colliders), vec![(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn keeps_harmful_tiles_separate() {
+        let tiles = vec![
+            solid(0, 0),
+            tile(1, 0, r#"{ "solid": true, "harmful": 1 }"#),
+            tile(2, 0, r#"{ "solid": true, "harmful": 1 }"#),
+            tile(3, 0, r#"{ "solid": true, "harmful": 2 }"#),
+        ];
+        let (colliders, _) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+
+        assert_eq!(colliders.len(), 3);
+        let mut harmful = colliders
+            .iter()
+            .map(|collider| {
+                (collider.harmful, (collider.size.0 / TILE_SIZE.0) as i32)
+            })
+            .collect::<Vec<_>>();
+        harmful.sort();
+        assert_eq!(harmful, vec![(None, 1), (Some(1), 2), (Some(2), 1)]);
+    }
+
+    #[test]
+    fn keeps_always_loaded_tiles_separate() {
+        let tiles = vec![
+            solid(0, 0),
+            tile(1, 0, r#"{ "solid": true, "always_loaded": true }"#),
+            tile(2, 0, r#"{ "solid": true, "always_loaded": true }"#),
+        ];
+        let (colliders, _) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+
+        assert_eq!(colliders.len(), 2);
+        let always_loaded = colliders
+            .iter()
+            .find(|collider| collider.always_loaded)
+            .unwrap();
+        assert_eq!(always_loaded.size.0, 2.0 * TILE_SIZE.0);
+    }
+
+    #[test]
+    fn skips_unmergeable_tiles() {
+        let tiles = vec![
+            solid(0, 0),
+            tile(1, 0, r#"{ "solid": false }"#),
+            tile(2, 0, r#"{ "solid": true, "components": ["Gravity"] }"#),
+        ];
+        let (colliders, merged_tiles) =
+            merge_solid_tiles(&tiles, TILE_SIZE.into(), (4, 4));
+
+        assert_eq!(colliders.len(), 1);
+        assert_eq!(merged_tiles.into_iter().collect::<Vec<_>>(), vec![0]);
+    }
+}
@@ -1,15 +1,34 @@
+mod hot_reload;
+mod level_load;
 mod level_loader;
 mod savefile;
 
 use std::collections::HashMap;
 
 use amethyst::audio::{output::Output, AudioSink, Source};
+use amethyst::core::transform::Transform;
 use amethyst::ecs::{Entities, Join, ReadStorage, WriteStorage};
 
 use climer::Time;
 
 use crate::states::state_prelude::*;
+use hot_reload::{HotReloader, WatchedKind};
 use level_loader::LevelLoader;
+
+pub use level_load::{
+    LevelLoad,
+    LevelParser,
+    ParseStatus,
+    LEVEL_PARSER_THREAD_NAME,
+};
+pub use level_loader::{
+    build_enemy,
+    enemy_spritesheet_filename,
+    object_assets,
+    tileset_spritesheet,
+    ObjectAsset,
+    TILE_COMPONENT_NAMES,
+};
 use savefile::TimeData;
 
 const TIMER_Z: f32 = 10.0;
@@ -27,6 +46,26 @@ pub struct LevelManager {
     player_checkpoint_opt: Option<Player>,
     completed_levels:      Vec<String>,
     current_song:          Option<String>,
+    /// Only exists in development mode.
+    hot_reloader:          Option<HotReloader>,
+    /// The level to load, taken by `Ingame` to push the `LevelLoading` state.
+    level_load:            Option<LevelLoad>,
+    after_level_load:      Vec<AfterLevelLoad>,
+}
+
+/// Changes to the new level's entities, which are applied
+/// once the level has finished loading.
+enum AfterLevelLoad {
+    /// Increase the player's health, and make it the new checkpoint.
+    IncreasePlayerHealth(u32),
+    /// Move the player and camera back to their positions before hot reloading,
+    /// and restore the actual checkpoint and the running timers.
+    RestoreHotReload {
+        player_pos_opt:        Option<(f32, f32)>,
+        camera_pos_opt:        Option<(f32, f32)>,
+        player_checkpoint_opt: Option<Player>,
+        timers:                Timers,
+    },
 }
 
 impl LevelManager {
@@ -44,6 +83,13 @@ impl LevelManager {
             player_checkpoint_opt: None,
             completed_levels:      Vec::new(),
             current_song:          None,
+            hot_reloader:          if crate::in_development_mode() {
+                Some(HotReloader::new())
+            } else {
+                None
+            },
+            level_load:            None,
+            after_level_load:      Vec::new(),
         };
         level_manager.load_from_savefile(data);
         if new_game {
@@ -52,6 +98,33 @@ impl LevelManager {
         level_manager
     }
 
+    /// Sets the level, which is loaded next with `load_current_level`.
+    /// Used to start at a specific level, with the `--level` command line argument.
+    pub fn set_current_level(
+        &mut self,
+        level_name: &str,
+    ) -> Result<(), String> {
+        if self
+            .settings
+            .level_names
+            .iter()
+            .any(|name| name == level_name)
+        {
+            self.level_index = self.level_index_from_name(level_name);
+            self.player_checkpoint_opt = None;
+            Ok(())
+        } else {
+            Err(format!(
+                "Level with name '{}' doesn't exist in this campaign",
+                level_name
+            ))
+        }
+    }
+
+    /// Removes the current level's entities, and prepares loading the current level.
+    /// The level is loaded by the `LevelLoading` state, which `Ingame` pushes
+    /// with the `LevelLoad` from `take_level_load`;
+    /// afterwards, `finish_loading_level` must be called.
     pub fn load_current_level(
         &mut self,
         data: &mut StateData<CustomGameData<CustomData>>,
@@ -74,15 +147,35 @@ impl LevelManager {
         data.world.maintain();
 
         let current_level_name = self.level_name();
-        let level_filepath = resource(format!(
+        let level_filepath = self.settings.resource(format!(
             "{}/{}",
             self.settings.levels_dir, current_level_name
         ));
 
-        let mut level_loader = LevelLoader::new(self.settings.clone());
-        level_loader.load_level(level_filepath);
-        level_loader.build(data);
+        if let Some(hot_reloader) = self.hot_reloader.as_mut() {
+            hot_reloader.watch_level(level_filepath.clone());
+        }
+
+        self.level_load = Some(LevelLoad::new(
+            current_level_name,
+            self.best_level_time(),
+            self.settings
+                .resource(format!("audio/{}", self.current_song_name())),
+            self.settings.clone(),
+            level_filepath,
+        ));
+    }
+
+    /// Returns the level to load, if one should be loaded.
+    pub fn take_level_load(&mut self) -> Option<LevelLoad> {
+        self.level_load.take()
+    }
 
+    /// Sets up the level, after the `LevelLoading` state has built its entities.
+    pub fn finish_loading_level(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
         // Load checkpoint / player data
         if let Some(player_checkpoint) = &self.player_checkpoint_opt {
             data.world.exec(|mut players: WriteStorage<Player>| {
@@ -92,15 +185,6 @@ impl LevelManager {
             });
         }
 
-        // Start all turret timers
-        data.world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
-            (&mut enemy_ais).join().for_each(|enemy_ai| {
-                if let EnemyAi::Turret(ai_data) = enemy_ai {
-                    ai_data.shot_timer.start().unwrap();
-                }
-            });
-        });
-
         {
             // (Re)start level timer
             let mut timers = data.world.write_resource::<Timers>();
@@ -123,21 +207,7 @@ impl LevelManager {
             create_timer_ui(
                 TimerType::Level,
                 &self.settings.level_timer_ui,
-                {
-                    if let Some(times) =
-                        self.level_times.get(&self.level_name())
-                    {
-                        Some(
-                            if times.general < times.first {
-                                times.general.clone()
-                            } else {
-                                times.first.clone()
-                            },
-                        )
-                    } else {
-                        None
-                    }
-                },
+                self.best_level_time(),
                 data,
             );
         }
@@ -168,6 +238,15 @@ impl LevelManager {
         data.world.write_resource::<CurrentLevelName>().0 =
             Some(self.level_name());
 
+        for after_level_load in
+            std::mem::replace(&mut self.after_level_load, Vec::new())
+        {
+            self.apply_after_level_load(data, after_level_load);
+        }
+
+        // Force update `HealthDisplay`
+        data.world.write_resource::<UpdateHealthDisplay>().0 = true;
+
         // Set LoadingLevel resource to `false` again.
         data.world.write_resource::<LoadingLevel>().0 = false;
 
@@ -186,6 +265,9 @@ impl LevelManager {
     }
 
     pub fn update(&mut self, data: &mut StateData<CustomGameData<CustomData>>) {
+        self.hot_reload(data);
+        self.handle_load_level_request(data);
+
         // Check if the level was beaten and if the player has died
         let (player_in_goal, next_level, player_dead) = data.world.exec(
             |(goals, players, animations_containers, invincibles): (
@@ -224,7 +306,7 @@ impl LevelManager {
             let mut timers = data.world.write_resource::<Timers>();
             timers.level.finish().unwrap();
             let time = timers.level.time_output();
-            println!("LEVEL TIME: {}", &time); // TODO
+            info!("LEVEL TIME: {}", &time);
             let time_entry = self
                 .level_times
                 .entry(level_name.clone())
@@ -275,16 +357,8 @@ impl LevelManager {
             timers.global = Some(timer);
         }
 
+        // The global timer is started once the level has loaded.
         self.load_current_level(&mut data);
-        // Force update `HealthDisplay`
-        data.world.write_resource::<UpdateHealthDisplay>().0 = true;
-
-        // Now start the global timer
-        data.world
-            .write_resource::<Timers>()
-            .global
-            .as_mut()
-            .map(|timer| timer.start().unwrap());
     }
 
     pub fn on_stop(
@@ -303,18 +377,18 @@ impl LevelManager {
         // Set _lower_ music volume
         data.world
             .write_resource::<AudioSink>()
-            .set_volume(data.world.settings().music_volume_paused);
+            .set_volume(data.world.music_volume_paused());
     }
 
     pub fn on_resume(&self, data: &mut StateData<CustomGameData<CustomData>>) {
         // Set _regular_ music volume
         data.world
             .write_resource::<AudioSink>()
-            .set_volume(data.world.settings().music_volume);
+            .set_volume(data.world.music_volume());
     }
 
     fn win_game(&mut self, data: &mut StateData<CustomGameData<CustomData>>) {
-        println!("You win!"); // TODO
+        info!("You win!");
 
         // Stop global timer
         {
@@ -324,7 +398,7 @@ impl LevelManager {
             if let Some(global_timer) = timers.global.as_mut() {
                 global_timer.finish().unwrap();
                 let time = global_timer.time_output();
-                println!("GLOBAL TIME: {}", &time); // TODO
+                info!("GLOBAL TIME: {}", &time);
                 if self.global_time.is_none() {
                     self.global_time = Some(TimeData {
                         general: time,
@@ -382,22 +456,80 @@ impl LevelManager {
 
         // Restart level and load player from checkoint
         self.restart_level(data);
-        data.world.maintain();
 
         let health_increase = self.settings.health_increase_on_death;
         if health_increase > 0 {
-            data.world.exec(|mut players: WriteStorage<Player>| {
-                if let Some(player) = (&mut players).join().next() {
-                    // Increase player's health
-                    player.add_health(health_increase);
-                    // Set player checkpoint
-                    self.player_checkpoint_opt = Some(player.clone());
-                }
-            });
+            // The new player only exists once the level has loaded.
+            self.after_level_load
+                .push(AfterLevelLoad::IncreasePlayerHealth(health_increase));
+        } else {
+            self.save_to_savefile(data);
         }
+    }
 
-        data.world.maintain();
-        self.save_to_savefile(data);
+    fn apply_after_level_load(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+        after_level_load: AfterLevelLoad,
+    ) {
+        match after_level_load {
+            AfterLevelLoad::IncreasePlayerHealth(health_increase) => {
+                data.world.exec(|mut players: WriteStorage<Player>| {
+                    if let Some(player) = (&mut players).join().next() {
+                        // Increase player's health
+                        player.add_health(health_increase);
+                        // Set player checkpoint
+                        self.player_checkpoint_opt = Some(player.clone());
+                    }
+                });
+                self.save_to_savefile(data);
+            }
+            AfterLevelLoad::RestoreHotReload {
+                player_pos_opt,
+                camera_pos_opt,
+                player_checkpoint_opt,
+                timers,
+            } => {
+                self.player_checkpoint_opt = player_checkpoint_opt;
+                // Loading the level restarted the timers; keep timing the
+                // current run instead, so hot reloading doesn't affect best times.
+                let mut timers = timers;
+                if timers.level.state.is_paused() {
+                    timers.level.resume().unwrap();
+                }
+                timers.global.as_mut().map(|timer| {
+                    if timer.state.is_paused() {
+                        timer.resume().unwrap();
+                    }
+                });
+                *data.world.write_resource::<Timers>() = timers;
+                data.world.exec(
+                    |(players, cameras, mut transforms): (
+                        ReadStorage<Player>,
+                        ReadStorage<AmethystCamera>,
+                        WriteStorage<Transform>,
+                    )| {
+                        if let Some((x, y)) = player_pos_opt {
+                            for (_, transform) in
+                                (&players, &mut transforms).join()
+                            {
+                                transform.set_x(x);
+                                transform.set_y(y);
+                            }
+                        }
+                        if let Some((x, y)) = camera_pos_opt {
+                            for (_, transform) in
+                                (&cameras, &mut transforms).join()
+                            {
+                                transform.set_x(x);
+                                transform.set_y(y);
+                            }
+                        }
+                    },
+                );
+                info!("Hot reloaded level {}", self.level_name());
+            }
+        }
     }
 
     fn play_current_song(
@@ -415,10 +547,10 @@ impl LevelManager {
 
         let asset = data.world.read_resource::<AssetStorage<Source>>();
         let name = self.current_song_name();
-        let handle = data
-            .world
-            .write_resource::<AudioHandles>()
-            .get_or_load(resource(format!("audio/{}", name)), &data.world);
+        let handle = data.world.write_resource::<AudioHandles>().get_or_load(
+            self.settings.resource(format!("audio/{}", name)),
+            &data.world,
+        );
         if let Some(sound) = asset.get(&handle) {
             sink.append(sound).unwrap();
             self.current_song = Some(name.to_string());
@@ -443,7 +575,7 @@ impl LevelManager {
     }
 
     fn stop_song(&self, data: &mut StateData<CustomGameData<CustomData>>) {
-        let music_volume = data.world.settings().music_volume;
+        let music_volume = data.world.music_volume();
         let output = data.world.read_resource::<Output>();
         let mut sink = data.world.write_resource::<AudioSink>();
         sink.stop();
@@ -451,6 +583,101 @@ impl LevelManager {
         sink.set_volume(music_volume);
     }
 
+    /// Reloads any changed config, animation or level files
+    /// (development mode only).
+    fn hot_reload(&mut self, data: &mut StateData<CustomGameData<CustomData>>) {
+        let changed = match self.hot_reloader.as_mut() {
+            Some(hot_reloader) => hot_reloader.changed(),
+            None => return,
+        };
+        for kind in changed {
+            match kind {
+                WatchedKind::Settings => hot_reload::reload_settings(
+                    &mut data.world,
+                    self.player_checkpoint_opt.as_mut(),
+                ),
+                WatchedKind::Bindings => {
+                    hot_reload::reload_bindings(&mut data.world)
+                }
+                WatchedKind::Animations => {
+                    hot_reload::reload_animations(&mut data.world)
+                }
+                WatchedKind::Level => self.reload_current_level(data),
+            }
+        }
+    }
+
+    /// Loads the level requested with the `LoadLevelRequest` resource
+    /// (from the developer console).
+    fn handle_load_level_request(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        let level_name_opt =
+            data.world.write_resource::<LoadLevelRequest>().0.take();
+        if let Some(level_name) = level_name_opt {
+            if self.settings.level_names.contains(&level_name) {
+                self.level_index = self.level_index_from_name(level_name);
+                self.load_current_level(data);
+            } else {
+                error!(
+                    "Level with name '{}' doesn't exist in this campaign",
+                    level_name
+                );
+            }
+        }
+    }
+
+    /// Rebuilds the current level from its level file, keeping the player's
+    /// current `Player` state and position, and the camera's position.
+    fn reload_current_level(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        let (player_opt, camera_pos_opt) = data.world.exec(
+            |(players, cameras, transforms): (
+                ReadStorage<Player>,
+                ReadStorage<AmethystCamera>,
+                ReadStorage<Transform>,
+            )| {
+                (
+                    (&players, &transforms).join().next().map(
+                        |(player, transform)| {
+                            (player.clone(), position_of(transform))
+                        },
+                    ),
+                    (&cameras, &transforms)
+                        .join()
+                        .next()
+                        .map(|(_, transform)| position_of(transform)),
+                )
+            },
+        );
+
+        // The level loading applies the checkpoint to the new player,
+        // so temporarily use the current player as the checkpoint,
+        // until the level has loaded.
+        let player_checkpoint_opt = self.player_checkpoint_opt.clone();
+        let player_pos_opt = player_opt.map(|(player, pos)| {
+            self.player_checkpoint_opt = Some(player);
+            pos
+        });
+        // Keep the running timers, paused while the level loads.
+        pause_game_timers(&mut data.world);
+        let timers = std::mem::replace(
+            &mut *data.world.write_resource::<Timers>(),
+            Timers::default(),
+        );
+        self.load_current_level(data);
+        self.after_level_load
+            .push(AfterLevelLoad::RestoreHotReload {
+                player_pos_opt:        player_pos_opt,
+                camera_pos_opt:        camera_pos_opt,
+                player_checkpoint_opt: player_checkpoint_opt,
+                timers:                timers,
+            });
+    }
+
     fn set_player_checkpoint(
         &mut self,
         data: &mut StateData<CustomGameData<CustomData>>,
@@ -489,6 +716,17 @@ impl LevelManager {
         self.load_current_level(data);
     }
 
+    /// Returns the best time of the current level, if it has been completed before.
+    fn best_level_time(&self) -> Option<Time> {
+        self.level_times.get(&self.level_name()).map(|times| {
+            if times.general < times.first {
+                times.general.clone()
+            } else {
+                times.first.clone()
+            }
+        })
+    }
+
     fn has_completed_current_level(&self) -> bool {
         self.completed_levels.contains(&self.level_name())
     }
@@ -555,14 +793,14 @@ impl LevelManager {
                         Ok(encrypted) => {
                             write_file(savefile_path, encrypted).unwrap()
                         }
-                        Err(err) => eprintln!(
+                        Err(err) => error!(
                             "An error occured while encrypting savefile: {}",
                             err
                         ),
                     }
                 }
             }
-            Err(err) => eprintln!(
+            Err(err) => error!(
                 "Couldn't save savefile data to file, an error occured while \
                  serializing save data:\n{:#?}",
                 err
@@ -581,7 +819,7 @@ impl LevelManager {
             let mut json_raw = match decrypt(&raw) {
                 Ok(s) => s,
                 Err(err) => {
-                    eprintln!(
+                    warn!(
                         "An error occured while decrypting savefile: \
                          {}\nTrying to load savefile without decrypting...",
                         err
@@ -605,12 +843,12 @@ impl LevelManager {
                         if let Some(stats) = deserialized.stats {
                             *data.world.write_resource::<Stats>() = stats;
                         }
-                        eprintln!("Successfully loaded savefile!");
+                        info!("Successfully loaded savefile!");
                     }
                     Err(err) => {
                         if tried_unencrypted {
                             retry = false;
-                            eprintln!(
+                            error!(
                                 "Couldn't load savefile data from file, an \
                                  error occured while deserializing save \
                                  data:\n{:#?}",
@@ -648,6 +886,38 @@ impl LevelManager {
     }
 }
 
+/// Builds the entities of the level from the given level file,
+/// and starts all turret timers.
+/// Only the headless `Simulation` loads levels synchronously.
+pub fn build_level(
+    data: &mut StateData<CustomGameData<CustomData>>,
+    settings: SettingsLevelManagerCampaign,
+    level_filepath: String,
+) -> Result<(), String> {
+    let mut level_loader = LevelLoader::new(settings);
+    level_loader.load_level(level_filepath)?;
+    build_parsed_level(data, level_loader);
+    Ok(())
+}
+
+/// Builds the entities of the already parsed level,
+/// and starts all turret timers.
+pub fn build_parsed_level(
+    data: &mut StateData<CustomGameData<CustomData>>,
+    mut level_loader: LevelLoader,
+) {
+    level_loader.build(data);
+
+    // Start all turret timers
+    data.world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
+        (&mut enemy_ais).join().for_each(|enemy_ai| {
+            if let EnemyAi::Turret(ai_data) = enemy_ai {
+                ai_data.shot_timer.start().unwrap();
+            }
+        });
+    });
+}
+
 fn create_timer_ui(
     timer_type: TimerType,
     ui_settings: &crate::settings::SettingsTimerUi,
@@ -782,3 +1052,8 @@ where
         Err(err) => Err(format!("Error decoding base64: {}", err)),
     }
 }
+
+fn position_of(transform: &Transform) -> (f32, f32) {
+    let translation = transform.translation();
+    (translation.x, translation.y)
+}
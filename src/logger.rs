@@ -0,0 +1,146 @@
+//! Routes all log messages (from the game and from amethyst) through the `log` facade,
+//! to stdout and to a session log file, with per-module levels.
+//! The levels are configured in the `logging` section of `settings.ron`,
+//! and can be overridden with the `--log-level` and `--log` command line arguments.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+
+use amethyst::utils::application_root_dir;
+use log::LevelFilter;
+
+use crate::crash_report::CrashReporter;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+
+const SESSION_LOG_NAME: &str = "session";
+/// Used if the settings can't be loaded.
+const FALLBACK_LEVEL: LevelFilter = LevelFilter::Warn;
+
+/// Starts the logger. Should be called once, as early as possible.
+/// The crash reporter records the last log lines.
+pub fn start_logger(crash_reporter: &CrashReporter) {
+    let cli_args = crate::cli::args();
+    // If the settings can't be loaded, the error is reported when the game starts.
+    let settings = load_settings().ok().map(|settings| settings.logging);
+
+    let mut level = settings
+        .as_ref()
+        .map(|settings| parse_level_or_fallback(&settings.level))
+        .unwrap_or(FALLBACK_LEVEL);
+    let mut module_levels: Vec<(String, LevelFilter)> = settings
+        .as_ref()
+        .map(|settings| {
+            settings
+                .modules
+                .iter()
+                .map(|(module, level)| {
+                    (module.clone(), parse_level_or_fallback(level))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Command line arguments take precedence over the settings.
+    if let Some(cli_level) = cli_args.log_level {
+        level = cli_level;
+    }
+    module_levels.extend(cli_args.log_modules.iter().cloned());
+
+    let start = Instant::now();
+    let mut dispatch = fern::Dispatch::new()
+        .format(move |out, message, record| {
+            let elapsed = start.elapsed();
+            out.finish(format_args!(
+                "[{:>4}.{:03}][{}][{}] {}",
+                elapsed.as_secs(),
+                elapsed.subsec_millis(),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .level(level);
+    for (module, module_level) in module_levels {
+        dispatch = dispatch.level_for(module, module_level);
+    }
+    dispatch = dispatch
+        .chain(io::stdout())
+        .chain(crash_reporter.log_recorder());
+
+    let mut session_log_err = None;
+    if let Some(settings) = settings.as_ref() {
+        if settings.max_session_logs > 0 {
+            match open_session_log(settings) {
+                Ok(file) => dispatch = dispatch.chain(file),
+                Err(err) => session_log_err = Some(err),
+            }
+        }
+    }
+
+    if let Err(err) = dispatch.apply() {
+        eprintln!("Couldn't start logger: {}", err);
+        return;
+    }
+    if let Some(err) = session_log_err {
+        warn!("Couldn't open session log file: {}", err);
+    }
+}
+
+/// Parses a level like `warn` or `DEBUG`.
+pub fn parse_level(text: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(text).map_err(|_| {
+        format!(
+            "Invalid log level `{}`, expected `off`, `error`, `warn`, \
+             `info`, `debug` or `trace`",
+            text
+        )
+    })
+}
+
+fn parse_level_or_fallback(text: &str) -> LevelFilter {
+    parse_level(text).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        FALLBACK_LEVEL
+    })
+}
+
+/// Rotates the previous sessions' log files (`session.log` is moved to
+/// `session.1.log`, and so on), deleting the oldest one,
+/// and creates a new `session.log` for this session.
+fn open_session_log(settings: &SettingsLogging) -> Result<File, String> {
+    let dir = PathBuf::from(application_root_dir()).join(&settings.log_dir);
+    fs::create_dir_all(&dir).map_err(|err| {
+        format!("Couldn't create directory `{}`: {}", dir.display(), err)
+    })?;
+
+    let path_for = |index: usize| {
+        if index == 0 {
+            dir.join(format!("{}.log", SESSION_LOG_NAME))
+        } else {
+            dir.join(format!("{}.{}.log", SESSION_LOG_NAME, index))
+        }
+    };
+
+    let oldest = path_for(settings.max_session_logs - 1);
+    if oldest.exists() {
+        fs::remove_file(&oldest).map_err(|err| {
+            format!("Couldn't delete `{}`: {}", oldest.display(), err)
+        })?;
+    }
+    for index in (0 .. settings.max_session_logs - 1).rev() {
+        let path = path_for(index);
+        if path.exists() {
+            fs::rename(&path, path_for(index + 1)).map_err(|err| {
+                format!("Couldn't move `{}`: {}", path.display(), err)
+            })?;
+        }
+    }
+
+    let path = path_for(0);
+    File::create(&path)
+        .map_err(|err| format!("Couldn't create `{}`: {}", path.display(), err))
+}
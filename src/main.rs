@@ -1,33 +1,56 @@
 extern crate amethyst;
-#[cfg(feature = "debug")]
 extern crate backtrace;
 #[cfg(feature = "encrypt_savefile")]
 extern crate base64;
-#[cfg(feature = "debug")]
 extern crate chrono;
 extern crate climer;
 extern crate deathframe;
+extern crate deflate;
+extern crate fern;
+extern crate image;
+extern crate inflate;
 extern crate json;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
 extern crate regex;
 #[macro_use]
 extern crate serde;
 
+mod analytics;
+mod asset_manifest;
 mod bullet_creator;
+mod cli;
 mod components;
+mod crash_report;
+mod dev_console;
+mod entity_inspector;
 mod level_manager;
-mod misc;
+mod logger;
+mod mod_packs;
+mod profiler;
+mod replay;
 mod resolution_parser;
+mod resource_archive;
 mod resource_helpers;
 mod settings;
+mod settings_loader;
+mod simulation;
 mod solid_tag;
+mod spatial_grid;
 mod states;
 mod systems;
+mod tools;
+mod user_bindings;
+mod user_settings;
 mod world_helpers;
 
 use std::env;
 use std::time::Duration;
 
-use amethyst::audio::AudioBundle;
+use amethyst::assets::Processor;
+use amethyst::audio::{AudioBundle, Source};
 use amethyst::core::frame_limiter::FrameRateLimitStrategy;
 use amethyst::core::transform::TransformBundle;
 use amethyst::input::InputBundle;
@@ -36,19 +59,22 @@ use amethyst::renderer::{
     ColorMask,
     DepthMode,
     DisplayConfig,
+    DrawDebugLines,
     DrawFlat2D,
     Pipeline,
+    PosColorNorm,
     RenderBundle,
     Stage,
     ALPHA,
 };
 use amethyst::ui::{DrawUi, UiBundle};
 use amethyst::utils::fps_counter::FPSCounterBundle;
-use amethyst::{LogLevelFilter, LoggerConfig};
 
 use deathframe::custom_game_data::prelude::*;
 use deathframe::handlers::AudioHandles;
 
+use crash_report::CrashReporter;
+use profiler::prelude::*;
 use resource_helpers::*;
 use systems::prelude::*;
 
@@ -57,23 +83,31 @@ pub mod meta {
     pub const NAME: &str = env!("CARGO_PKG_NAME");
 }
 
-const FPS: u32 = 60;
+pub const FPS: u32 = 60;
 const SLEEP_AND_YIELD_MS: u64 = 2;
 
 #[derive(Clone)]
 pub struct CustomData {
     pub display_config: DisplayConfig,
+    pub profiler:       SystemProfiler,
+    pub crash_reporter: CrashReporter,
 }
 
 fn main() -> Result<(), String> {
-    #[cfg(feature = "debug")]
-    std::panic::set_hook(Box::new(misc::on_panic));
+    let crash_reporter = crash_report::CrashReporter::default();
+    crash_reporter.install_panic_hook();
 
     print_welcome_message();
 
+    handle_cli_args()?;
+
+    logger::start_logger(&crash_reporter);
+
+    tools::run_requested_tool();
+
     maybe_exit();
 
-    init_game().map_err(|e| e.to_string())
+    init_game(crash_reporter).map_err(|e| e.to_string())
 }
 
 fn print_welcome_message() {
@@ -85,6 +119,29 @@ fn print_welcome_message() {
     );
 }
 
+/// Validates the command line arguments, and prints the help if requested.
+fn handle_cli_args() -> Result<(), String> {
+    let cli_args = match cli::parsed_args() {
+        Ok(cli_args) => cli_args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::usage());
+            std::process::exit(2);
+        }
+    };
+
+    if cli_args.help {
+        println!("{}", cli::usage());
+        std::process::exit(0);
+    }
+    if cli_args.dev && !cfg!(feature = "debug") {
+        eprintln!(
+            "`--dev` requires the `debug` feature, development mode is disabled"
+        );
+    }
+
+    Ok(())
+}
+
 fn maybe_exit() {
     // Exit game if environment variable `EXIT` is set.
     // Used for validating a correct build, without a graphical environment.
@@ -92,46 +149,42 @@ fn maybe_exit() {
     if env::vars()
         .any(|(key, val)| key == EXIT_VAR_NAME && !val.is_empty() && val != "0")
     {
-        println!("Environment variable `{}` is set, exiting.", EXIT_VAR_NAME);
+        info!("Environment variable `{}` is set, exiting.", EXIT_VAR_NAME);
         std::process::exit(0);
     }
 }
 
-fn init_game() -> amethyst::Result<()> {
-    start_logger();
-
-    let game_data = build_game_data()?;
+fn init_game(crash_reporter: CrashReporter) -> amethyst::Result<()> {
+    let user_settings = user_settings::UserSettings::load();
+    let profiler = SystemProfiler::from_env();
+    let game_data = build_game_data(&user_settings, &profiler, crash_reporter)?;
+    let (frame_limit_strategy, frame_limit) = get_frame_limit(&user_settings);
 
     let mut game: amethyst::CoreApplication<CustomGameData<CustomData>> =
         Application::build("./", states::Startup::default())?
             // https://docs-src.amethyst.rs/stable/amethyst_core/frame_limiter
-            .with_frame_limit(
-                FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(
-                    SLEEP_AND_YIELD_MS,
-                )),
-                FPS,
-            )
+            .with_frame_limit(frame_limit_strategy, frame_limit)
             .build(game_data)?;
     game.run();
 
-    Ok(())
-}
+    profiler.dump();
 
-fn start_logger() {
-    amethyst::start_logger(LoggerConfig {
-        level_filter: LogLevelFilter::Error,
-        ..Default::default()
-    });
+    Ok(())
 }
 
 fn build_game_data<'a, 'b>(
+    user_settings: &user_settings::UserSettings,
+    profiler: &SystemProfiler,
+    crash_reporter: CrashReporter,
 ) -> amethyst::Result<CustomGameDataBuilder<'a, 'b, CustomData>> {
     // Display config
-    let display_config = get_display_config();
+    let display_config = get_display_config(user_settings);
 
     // CustomGameData CustomData
     let custom_data = CustomData {
         display_config: display_config.clone(),
+        profiler:       profiler.clone(),
+        crash_reporter: crash_reporter,
     };
 
     // Pipeline
@@ -148,8 +201,8 @@ fn build_game_data<'a, 'b>(
             //       but sprite ordering via their z positions only works with this `DepthMode` variant.
             Some(DepthMode::LessEqualWrite),
         ))
+        .with_pass(DrawDebugLines::<PosColorNorm>::new())
         .with_pass(DrawUi::new()); // NOTE: "It's recommended this be your last pass."
-                                   // .with_pass(DrawDebugLines::<PosColorNorm>::new()); // TODO
     let pipeline = Pipeline::build().with_stage(stage);
 
     // Bundles
@@ -158,8 +211,12 @@ fn build_game_data<'a, 'b>(
         RenderBundle::new(pipeline, Some(display_config.clone()))
             .with_sprite_sheet_processor()
             .with_sprite_visibility_sorting(&["transform_system"]);
-    let input_bundle = InputBundle::<String, String>::new()
-        .with_bindings_from_file(&resource("config/bindings.ron"))?;
+    let bindings = match user_bindings::load_bindings() {
+        Ok(bindings) => bindings,
+        Err(err) => panic!(err),
+    };
+    let input_bundle =
+        InputBundle::<String, String>::new().with_bindings(bindings);
     let ui_bundle = UiBundle::<String, String>::new();
     let audio_bundle = AudioBundle::new(|_: &mut AudioHandles| None); // I hate this
     let fps_bundle = FPSCounterBundle;
@@ -174,27 +231,103 @@ fn build_game_data<'a, 'b>(
         .dispatcher("continue_or_new_game_menu")?
         .dispatcher("win_game_menu")?
         .dispatcher("bonus_select_menu")?
+        .dispatcher("options_menu")?
+        .dispatcher("controls_menu")?
+        .dispatcher("dev_console")?
+        .dispatcher("frame_step")?
+        .dispatcher("level_loading")?
+        .dispatcher("mod_select_menu")?
         .with_bundle("ingame", audio_bundle)? // initialize before input_bundle; https://github.com/amethyst/amethyst/issues/1779
+        // Songs are also loaded while the `ingame` dispatcher doesn't run.
+        .with(
+            "startup",
+            Processor::<Source>::new(),
+            "source_processor",
+            &[],
+        )?
+        .with(
+            "level_loading",
+            Processor::<Source>::new(),
+            "source_processor",
+            &[],
+        )?
         .with_core_bundle(transform_bundle)?
         .with_core_bundle(render_bundle)?
         .with_core_bundle(input_bundle)?
         .with_core_bundle(ui_bundle)?
         .with_core_bundle(fps_bundle)?
-        .with_core(InputManagerSystem, "input_manager_system", &[
-            "input_system",
-        ])?
-        .with_core(ScaleSpritesSystem, "scale_sprites_system", &[])?
-        .with_core(TimerSystem::default(), "timer_system", &[])?
-        .with("ingame", PlayerControlsSystem, "player_controls_system", &[
+        .with_core_profiled(
+            profiler,
+            InputManagerSystem,
+            "input_manager_system",
+            &["input_system"],
+        )?
+        .with_core_profiled(
+            profiler,
+            ScaleSpritesSystem,
+            "scale_sprites_system",
+            &[],
+        )?
+        .with_core_profiled(
+            profiler,
+            TimerSystem::default(),
+            "timer_system",
+            &[],
+        )?;
+
+    game_data = with_ingame_systems(game_data, profiler)?;
+
+    if in_development_mode() {
+        game_data = game_data
+            .with_core_profiled(
+                profiler,
+                DebugSystem::default(),
+                "debug_system",
+                &[],
+            )?
+            .with_core_profiled(
+                profiler,
+                DebugOverlaySystem::default(),
+                "debug_overlay_system",
+                &["input_manager_system"],
+            )?
+            .with_profiled(
+                profiler,
+                "ingame",
+                NoclipSystem::default(),
+                "noclip_system",
+                &[],
+            )?;
+    }
+
+    Ok(game_data)
+}
+
+/// Adds the gameplay systems to the `ingame` dispatcher.
+/// Also used by the headless `Simulation`.
+pub fn with_ingame_systems<'a, 'b>(
+    game_data: CustomGameDataBuilder<'a, 'b, CustomData>,
+    profiler: &SystemProfiler,
+) -> amethyst::Result<CustomGameDataBuilder<'a, 'b, CustomData>> {
+    game_data
+        .with_profiled(
+            profiler,
+            "ingame",
+            PlayerControlsSystem,
+            "player_controls_system",
+            &[],
+        )?
+        .with_profiled(profiler, "ingame", GravitySystem, "gravity_system", &[
         ])?
-        .with("ingame", GravitySystem, "gravity_system", &[])?
-        .with(
+        .with_profiled(
+            profiler,
             "ingame",
             LimitVelocitiesSystem,
             "limit_velocities_system",
             &["gravity_system", "player_controls_system"],
         )?
-        .with(
+        .with_profiled(
+            profiler,
             "ingame",
             MoveEntitiesSystem::<solid_tag::SolidTag>::default(),
             "move_entities_system",
@@ -204,23 +337,39 @@ fn build_game_data<'a, 'b>(
                 "player_controls_system",
             ],
         )?
-        .with("ingame", CameraSystem, "camera_system", &[
+        .with_profiled(profiler, "ingame", CameraSystem, "camera_system", &[
             "move_entities_system",
         ])?
-        .with(
+        .with_profiled(
+            profiler,
             "ingame",
             ConfineEntitiesSystem,
             "confine_entities_system",
             &["move_entities_system", "camera_system"],
         )?
-        .with("ingame", ParallaxSystem, "parallax_system", &[
-            "move_entities_system",
-            "camera_system",
-        ])?
-        .with("ingame", CollisionSystem, "collision_system", &[
-            "move_entities_system",
-        ])?
-        .with(
+        .with_profiled(
+            profiler,
+            "ingame",
+            SpatialGridSystem::default(),
+            "spatial_grid_system",
+            &["move_entities_system", "confine_entities_system"],
+        )?
+        .with_profiled(
+            profiler,
+            "ingame",
+            ParallaxSystem,
+            "parallax_system",
+            &["move_entities_system", "camera_system"],
+        )?
+        .with_profiled(
+            profiler,
+            "ingame",
+            GridCollisionSystem,
+            "collision_system",
+            &["move_entities_system", "spatial_grid_system"],
+        )?
+        .with_profiled(
+            profiler,
             "ingame",
             DecreaseVelocitiesSystem,
             "decrease_velocities_system",
@@ -231,41 +380,70 @@ fn build_game_data<'a, 'b>(
                 "player_controls_system",
             ],
         )?
-        .with("ingame", AnimationSystem, "animation_system", &[])?
-        .with("ingame", PlayerAttackSystem, "player_attack_system", &[
-            "player_controls_system",
-            "decrease_velocities_system",
-            "limit_velocities_system",
-            "collision_system",
-        ])?
-        .with(
+        .with_profiled(
+            profiler,
+            "ingame",
+            AnimationSystem,
+            "animation_system",
+            &[],
+        )?
+        .with_profiled(
+            profiler,
+            "ingame",
+            PlayerAttackSystem,
+            "player_attack_system",
+            &[
+                "player_controls_system",
+                "decrease_velocities_system",
+                "limit_velocities_system",
+                "collision_system",
+            ],
+        )?
+        .with_profiled(
+            profiler,
             "ingame",
             PlayerTakeDamageSystem,
             "player_take_damage_system",
             &["player_controls_system", "collision_system"],
         )?
-        .with(
+        .with_profiled(
+            profiler,
             "ingame",
             HealthDisplaySystem::default(),
             "health_display_system",
             &["player_take_damage_system"],
         )?
-        .with("ingame", GoalSystem, "goal_system", &["collision_system"])?
-        .with("ingame", BulletSystem, "bullet_system", &[
+        .with_profiled(profiler, "ingame", GoalSystem, "goal_system", &[
             "collision_system",
         ])?
-        .with("ingame", EnemyAiSystem, "enemy_ai_system", &[
-            "decrease_velocities_system",
-            "limit_velocities_system",
-            "player_attack_system",
+        .with_profiled(profiler, "ingame", BulletSystem, "bullet_system", &[
             "collision_system",
         ])?
-        .with("ingame", HeartsSystem::default(), "hearts_system", &[
-            "move_entities_system",
-            "player_attack_system",
+        .with_profiled(
+            profiler,
+            "ingame",
+            EnemyAiSystem,
             "enemy_ai_system",
-        ])?
-        .with(
+            &[
+                "decrease_velocities_system",
+                "limit_velocities_system",
+                "player_attack_system",
+                "collision_system",
+            ],
+        )?
+        .with_profiled(
+            profiler,
+            "ingame",
+            HeartsSystem::default(),
+            "hearts_system",
+            &[
+                "move_entities_system",
+                "player_attack_system",
+                "enemy_ai_system",
+            ],
+        )?
+        .with_profiled(
+            profiler,
             "ingame",
             SyncHeartsContainersWithHealthSystem,
             "sync_hearts_containers_with_health",
@@ -278,30 +456,40 @@ fn build_game_data<'a, 'b>(
                 "enemy_ai_system",
             ],
         )?
-        .with("ingame", BulletCreatorSystem, "bullet_creator_system", &[
-            "player_controls_system",
-            "enemy_ai_system",
-        ])?
-        .with("ingame", HarmfulSystem, "harmful_system", &[
+        .with_profiled(
+            profiler,
+            "ingame",
+            BulletCreatorSystem,
+            "bullet_creator_system",
+            &["player_controls_system", "enemy_ai_system"],
+        )?
+        .with_profiled(profiler, "ingame", HarmfulSystem, "harmful_system", &[
             "collision_system",
+            "spatial_grid_system",
         ])?
-        .with(
+        .with_profiled(
+            profiler,
             "ingame",
             PlayerDashSystem::default(),
             "player_dash_system",
             &["move_entities_system"],
         )?
-        .with("ingame", LoaderSystem, "loader_system", &[
+        .with_profiled(profiler, "ingame", LoaderSystem, "loader_system", &[
             "move_entities_system",
-        ])?;
-
-    if in_development_mode() {
-        game_data = game_data
-            .with_core(DebugSystem::default(), "debug_system", &[])?
-            .with("ingame", NoclipSystem::default(), "noclip_system", &[])?;
-    }
-
-    Ok(game_data)
+            "spatial_grid_system",
+        ])?
+        .with_profiled(
+            profiler,
+            "ingame",
+            DamageAnalyticsSystem,
+            "damage_analytics_system",
+            &[
+                "player_controls_system",
+                "player_take_damage_system",
+                "bullet_system",
+                "harmful_system",
+            ],
+        )
 }
 
 #[cfg(feature = "debug")]
@@ -309,6 +497,7 @@ pub fn in_development_mode() -> bool {
     const DEV_VAR_NAME: &str = "STABMAN_DEV";
     env::vars()
         .any(|(key, val)| key == DEV_VAR_NAME && !val.is_empty() && val != "0")
+        || cli::args().dev
 }
 
 #[cfg(not(feature = "debug"))]
@@ -316,24 +505,50 @@ pub fn in_development_mode() -> bool {
     false
 }
 
-fn get_display_config() -> DisplayConfig {
-    let mut display_config =
-        DisplayConfig::load(&resource("config/display.ron"));
+fn get_display_config(
+    user_settings: &user_settings::UserSettings,
+) -> DisplayConfig {
+    let mut display_config = load_display_config();
 
-    // Overwrite dimensions with resolution specified in `resolution.txt` file.
-    match resolution_parser::get_resolution() {
-        Ok(Some(resolution)) => {
-            display_config.dimensions = Some(resolution);
-            if display_config.max_dimensions.is_some() {
-                display_config.max_dimensions = Some(resolution);
-            }
-            if display_config.min_dimensions.is_some() {
-                display_config.min_dimensions = Some(resolution);
+    // Overwrite dimensions with resolution specified in the legacy
+    // `resolution.txt` file, unless the user settings specify a resolution.
+    if user_settings.display.resolution.is_none() {
+        match resolution_parser::get_resolution() {
+            Ok(Some(resolution)) => {
+                user_settings::set_resolution(&mut display_config, resolution)
             }
+            Ok(None) => (),
+            Err(err) => panic!(err),
         }
-        Ok(None) => (),
-        Err(err) => panic!(err),
+    }
+
+    // Overwrite values with the ones from the user settings.
+    user_settings.display.apply_to(&mut display_config);
+
+    // Command line arguments take precedence over everything else.
+    let cli_args = cli::args();
+    if let Some(resolution) = cli_args.resolution {
+        user_settings::set_resolution(&mut display_config, resolution);
+    }
+    if cli_args.windowed {
+        display_config.fullscreen = false;
     }
 
     display_config
 }
+
+/// Returns the frame limit strategy and the max FPS,
+/// using the frame limit from the user settings, if there is one.
+fn get_frame_limit(
+    user_settings: &user_settings::UserSettings,
+) -> (FrameRateLimitStrategy, u32) {
+    match user_settings.display.frame_limit.unwrap_or(FPS) {
+        0 => (FrameRateLimitStrategy::Unlimited, FPS),
+        fps => (
+            FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(
+                SLEEP_AND_YIELD_MS,
+            )),
+            fps,
+        ),
+    }
+}
@@ -0,0 +1,161 @@
+//! Mod packs are custom campaigns, which players install by putting
+//! the pack's directory into the `mods` directory next to the executable.
+//! A pack's directory contains:
+//! - `pack.ron`, the pack's manifest (see `ModPackManifest`)
+//! - `levels/`, with the level files listed in the manifest
+//! - optionally `spritesheets/`, `textures/bg/` and `audio/`, with tilesets,
+//!   parallax backgrounds and songs; in the pack's campaign, these are used
+//!   instead of the resources with the same relative paths
+//!
+//! Packs are validated when they are loaded; packs with problems are listed,
+//! but can't be played.
+
+mod validation;
+
+use std::fs;
+
+use amethyst::utils::application_root_dir;
+
+use crate::resource_helpers::read_file;
+use crate::settings::SettingsLevelManagerCampaign;
+
+use validation::validate_campaign;
+
+pub const MODS_DIRNAME: &str = "mods";
+const MANIFEST_FILENAME: &str = "pack.ron";
+const LEVELS_DIRNAME: &str = "levels";
+
+/// A mod pack's `pack.ron`.
+#[derive(Clone, Deserialize)]
+pub struct ModPackManifest {
+    pub name:        String,
+    pub author:      String,
+    /// The level files in the pack's `levels` directory, in the order they are played.
+    pub level_names: Vec<String>,
+    /// The song of each level; either in the pack's `audio` directory,
+    /// or one of the game's songs.
+    pub song_names:  Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct ModPack {
+    /// The name of the pack's directory, which identifies the pack.
+    pub id:       String,
+    /// `None` if the manifest couldn't be loaded.
+    pub manifest: Option<ModPackManifest>,
+    /// `None` if the manifest couldn't be loaded.
+    pub campaign: Option<SettingsLevelManagerCampaign>,
+    /// Everything, which would break the pack's levels.
+    pub problems: Vec<String>,
+}
+
+impl ModPack {
+    /// Loads and validates the pack with the given directory name.
+    /// The pack's campaign uses the settings of the given campaign,
+    /// except for its levels, songs and savefile.
+    pub fn load(id: &str, template: &SettingsLevelManagerCampaign) -> Self {
+        let dir = format!("{}/{}", mods_dir(), id);
+        match load_manifest(&dir) {
+            Ok(manifest) => {
+                let mut campaign = template.clone();
+                campaign.levels_dir = LEVELS_DIRNAME.to_string();
+                campaign.level_names = manifest.level_names.clone();
+                campaign.song_names = manifest.song_names.clone();
+                campaign.savefile_path =
+                    format!("{}_mod_{}", template.savefile_path, id);
+                // Mod packs' assets aren't preloaded.
+                campaign.asset_manifest = String::new();
+                campaign.mod_dir = Some(dir);
+                let problems = validate_campaign(&campaign);
+                Self {
+                    id:       id.to_string(),
+                    manifest: Some(manifest),
+                    campaign: Some(campaign),
+                    problems: problems,
+                }
+            }
+            Err(err) => Self {
+                id:       id.to_string(),
+                manifest: None,
+                campaign: None,
+                problems: vec![err],
+            },
+        }
+    }
+
+    /// Returns `true` if the pack has no problems.
+    pub fn is_playable(&self) -> bool {
+        self.campaign.is_some() && self.problems.is_empty()
+    }
+
+    /// Returns the name from the pack's manifest,
+    /// or the directory name, if the manifest couldn't be loaded.
+    pub fn name(&self) -> &str {
+        self.manifest
+            .as_ref()
+            .map_or(self.id.as_str(), |manifest| manifest.name.as_str())
+    }
+}
+
+/// The installed mod packs, sorted by their directory names.
+#[derive(Default)]
+pub struct ModPacks {
+    packs: Vec<ModPack>,
+}
+
+impl ModPacks {
+    /// Loads and validates every pack in the `mods` directory.
+    /// There are no packs, if the directory doesn't exist.
+    pub fn scan(template: &SettingsLevelManagerCampaign) -> Self {
+        let mut ids: Vec<String> = fs::read_dir(mods_dir())
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| {
+                        entry.file_name().to_string_lossy().to_string()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        ids.sort();
+
+        let packs: Vec<ModPack> =
+            ids.iter().map(|id| ModPack::load(id, template)).collect();
+        for pack in packs.iter().filter(|pack| !pack.problems.is_empty()) {
+            warn!(
+                "Mod pack `{}` can't be played:\n  {}",
+                pack.id,
+                pack.problems.join("\n  ")
+            );
+        }
+
+        Self { packs: packs }
+    }
+
+    pub fn packs(&self) -> &Vec<ModPack> {
+        &self.packs
+    }
+
+    /// Returns the campaign of the pack with the given directory name,
+    /// if the pack is installed and playable.
+    pub fn campaign(&self, id: &str) -> Option<SettingsLevelManagerCampaign> {
+        self.packs
+            .iter()
+            .find(|pack| pack.id == id && pack.is_playable())
+            .and_then(|pack| pack.campaign.clone())
+    }
+}
+
+/// Returns the full path of the `mods` directory.
+pub fn mods_dir() -> String {
+    format!("{}/{}", application_root_dir(), MODS_DIRNAME)
+}
+
+fn load_manifest(dir: &str) -> Result<ModPackManifest, String> {
+    let path = format!("{}/{}", dir, MANIFEST_FILENAME);
+    let raw = read_file(&path)
+        .map_err(|err| format!("Couldn't read `{}`: {}", path, err))?;
+    ron::de::from_str(&raw)
+        .map_err(|err| format!("Couldn't parse `{}`: {}", path, err))
+}
@@ -0,0 +1,319 @@
+//! Checks a campaign's levels, songs and assets for anything,
+//! which would make the `LevelLoader` or the `LevelManager` panic,
+//! or which would make a level impossible to finish.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use json::JsonValue;
+
+use crate::asset_manifest::spritesheet_ron_path;
+use crate::components::prelude::ItemType;
+use crate::level_manager::{
+    enemy_spritesheet_filename,
+    object_assets,
+    tileset_spritesheet,
+    ObjectAsset,
+    TILE_COMPONENT_NAMES,
+};
+use crate::resource_helpers::{read_file, resource_exists};
+use crate::settings::SettingsLevelManagerCampaign;
+
+const REQUIRED_OBJECT_TYPES: [&str; 2] = ["Player", "Goal"];
+/// The object types, which the `LevelLoader` builds; it ignores any others.
+const LOADED_OBJECT_TYPES: [&str; 5] =
+    ["Player", "Parallax", "Enemy", "Goal", "Item"];
+const TURRET_FACINGS: [&str; 2] = ["Left", "Right"];
+const PARALLAX_SCALES: [&str; 2] = ["contain", "cover"];
+
+/// Returns the problems of the campaign's levels, songs and assets.
+/// The campaign can be played, if there are none.
+pub fn validate_campaign(
+    campaign: &SettingsLevelManagerCampaign,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if campaign.level_names.is_empty() {
+        problems.push("There are no levels".to_string());
+    }
+    if campaign.song_names.len() < campaign.level_names.len() {
+        problems.push(format!(
+            "There are {} levels, but only {} songs",
+            campaign.level_names.len(),
+            campaign.song_names.len()
+        ));
+    }
+
+    let mut assets = BTreeSet::new();
+    let mut spritesheets = BTreeSet::new();
+    for song_name in &campaign.song_names {
+        assets.insert(format!("audio/{}", song_name));
+    }
+    for level_name in &campaign.level_names {
+        let level_problems = load_level(campaign, level_name)
+            .map(|level| validate_level(&level, &mut assets, &mut spritesheets))
+            .unwrap_or_else(|err| vec![err]);
+        problems.extend(
+            level_problems
+                .into_iter()
+                .map(|problem| format!("{}: {}", level_name, problem)),
+        );
+    }
+    for path in assets {
+        if campaign.mod_resource(&path).is_none() && !resource_exists(&path) {
+            problems.push(format!("Asset `{}` doesn't exist", path));
+        }
+    }
+    // A spritesheet's image and its `.ron` file describe the same sprites,
+    // so a mod pack can't replace only one of them.
+    for path in spritesheets {
+        let ron_path = spritesheet_ron_path(&path);
+        match (
+            campaign.mod_resource(&path).is_some(),
+            campaign.mod_resource(&ron_path).is_some(),
+        ) {
+            (true, true) => (),
+            (false, false) => {
+                for path in &[&path, &ron_path] {
+                    if !resource_exists(path) {
+                        problems
+                            .push(format!("Asset `{}` doesn't exist", path));
+                    }
+                }
+            }
+            (true, false) | (false, true) => problems.push(format!(
+                "Spritesheet `{}` and `{}` must both be in the mod pack, \
+                 or both be the game's",
+                path, ron_path
+            )),
+        }
+    }
+
+    problems
+}
+
+/// Reads and parses the level file.
+/// A mod pack's levels must be in the pack's directory.
+fn load_level(
+    campaign: &SettingsLevelManagerCampaign,
+    level_name: &str,
+) -> Result<JsonValue, String> {
+    let path = format!("{}/{}", campaign.levels_dir, level_name);
+    let filepath = if campaign.mod_dir.is_some() {
+        campaign
+            .mod_resource(&path)
+            .ok_or_else(|| format!("Level file `{}` doesn't exist", path))?
+    } else {
+        campaign.resource(&path)
+    };
+    let raw = read_file(&filepath)
+        .map_err(|err| format!("Couldn't read level file: {}", err))?;
+    json::parse(&raw)
+        .map_err(|err| format!("Couldn't parse level file: {}", err))
+}
+
+/// Returns the level's problems, and adds the assets the level uses
+/// to the given sets; spritesheets are added by their image's path.
+fn validate_level(
+    level: &JsonValue,
+    assets: &mut BTreeSet<String>,
+    spritesheets: &mut BTreeSet<String>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let size = &level["level"]["size"];
+    if !size.is_null()
+        && (size["w"].as_f32().is_none() || size["h"].as_f32().is_none())
+    {
+        problems.push(
+            "The level size must have the numbers `w` and `h`".to_string(),
+        );
+    }
+
+    let mut object_types = BTreeSet::new();
+    for object in level["objects"].members() {
+        let object_type = match object["type"].as_str() {
+            Some(object_type) if LOADED_OBJECT_TYPES.contains(&object_type) => {
+                object_type
+            }
+            _ => continue,
+        };
+        // The `LevelLoader` skips objects without a numeric position and size.
+        if !has_numbers(&object["pos"], &["x", "y"])
+            || !has_numbers(&object["size"], &["w", "h"])
+        {
+            problems.push(format!(
+                "A `{}` object has no numeric position (`x` and `y`) \
+                 and size (`w` and `h`), so it isn't loaded",
+                object_type
+            ));
+            continue;
+        }
+        object_types.insert(object_type);
+        let properties = &object["properties"];
+        if let Err(problem) = validate_object(object_type, properties) {
+            problems.push(problem);
+        }
+        for asset in object_assets(object_type, properties) {
+            match asset {
+                ObjectAsset::Spritesheet(path) => {
+                    spritesheets.insert(path);
+                }
+                ObjectAsset::Texture(path) => {
+                    assets.insert(path);
+                }
+            }
+        }
+    }
+    for object_type in REQUIRED_OBJECT_TYPES.iter() {
+        if !object_types.contains(object_type) {
+            problems.push(format!("There is no `{}` object", object_type));
+        }
+    }
+
+    for tile in level["tiles"].members() {
+        // The `LevelLoader` skips tiles without a sprite id,
+        // a numeric position and a tileset.
+        let tileset = match (
+            tile["id"].as_usize(),
+            has_numbers(&tile["pos"], &["x", "y"]),
+            tile["ts"].as_str(),
+        ) {
+            (Some(_), true, Some(tileset)) => tileset,
+            _ => {
+                problems.push(
+                    "A tile has no sprite `id`, numeric position (`x` and \
+                     `y`) or tileset `ts`, so it isn't loaded"
+                        .to_string(),
+                );
+                continue;
+            }
+        };
+        spritesheets.insert(tileset_spritesheet(tileset));
+        if let Err(problem) = validate_tile(&tile["properties"]) {
+            problems.push(problem);
+        }
+    }
+
+    problems
+}
+
+/// Checks the tile properties, which the `LevelLoader` requires.
+fn validate_tile(properties: &JsonValue) -> Result<(), String> {
+    for component in properties["components"].members() {
+        let name = component
+            .as_str()
+            .ok_or("Tile components must be strings")?;
+        if !TILE_COMPONENT_NAMES.contains(&name) {
+            return Err(format!("Tile component `{}` doesn't exist", name));
+        }
+    }
+
+    match (
+        properties["animation_sprite_ids"].as_str(),
+        properties["animation_delays_ms"].as_str(),
+    ) {
+        (Some(sprite_ids), Some(delays_ms)) => {
+            if !is_list_of::<usize>(sprite_ids) || !is_list_of::<u64>(delays_ms)
+            {
+                return Err(format!(
+                    "Invalid tile animation `{}` with delays `{}`; both \
+                     must be comma separated whole numbers",
+                    sprite_ids, delays_ms
+                ));
+            }
+        }
+        (Some(_), None) | (None, Some(_)) => {
+            return Err("A tile animation needs both `animation_sprite_ids` \
+                        and `animation_delays_ms`"
+                .to_string());
+        }
+        (None, None) => (),
+    }
+    Ok(())
+}
+
+/// Checks the object properties, which the `LevelLoader` requires.
+fn validate_object(
+    object_type: &str,
+    properties: &JsonValue,
+) -> Result<(), String> {
+    match object_type {
+        "Enemy" => {
+            let enemy_type = properties["enemy_type"]
+                .as_str()
+                .ok_or("An `Enemy` object has no `enemy_type` property")?;
+            if enemy_spritesheet_filename(enemy_type).is_none() {
+                return Err(format!(
+                    "Enemy type `{}` doesn't exist",
+                    enemy_type
+                ));
+            }
+            let facing = &properties["facing"];
+            if enemy_type == "Turret"
+                && !facing.is_null()
+                && !facing
+                    .as_str()
+                    .map_or(false, |facing| TURRET_FACINGS.contains(&facing))
+            {
+                return Err(format!("Invalid turret facing `{}`", facing));
+            }
+        }
+        "Item" => {
+            let item_type = properties["item_type"]
+                .as_str()
+                .ok_or("An `Item` object has no `item_type` property")?;
+            if ItemType::from_name(item_type).is_none() {
+                return Err(format!("Item type `{}` doesn't exist", item_type));
+            }
+        }
+        "Parallax" => {
+            for (key, val) in properties.entries() {
+                let is_valid = match key {
+                    "image" => val.is_string(),
+                    "scale" => val.as_str().map_or(false, |scale| {
+                        PARALLAX_SCALES.contains(&scale)
+                    }),
+                    "speed_mult" | "offset" => {
+                        val.as_str().map_or(false, is_vector_string)
+                    }
+                    "speed_mult_x" | "speed_mult_y" | "offset_x"
+                    | "offset_y" => val.as_f32().is_some(),
+                    "repeat_x" | "repeat_y" => val.is_boolean(),
+                    _ => true,
+                };
+                if !is_valid {
+                    return Err(format!(
+                        "Invalid `Parallax` property `{}`: {}",
+                        key, val
+                    ));
+                }
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Returns `true` if the JSON object has a number for every key.
+fn has_numbers(object: &JsonValue, keys: &[&str]) -> bool {
+    keys.iter().all(|key| object[*key].as_f32().is_some())
+}
+
+/// Returns `true` if every comma separated field of the string parses as `T`,
+/// like the `LevelLoader` expects for tile animations.
+fn is_list_of<T: FromStr>(string: &str) -> bool {
+    string
+        .split(',')
+        .all(|field| field.trim().parse::<T>().is_ok())
+}
+
+/// Returns `true` if the string has two comma separated numbers,
+/// like the `LevelLoader` expects for vector properties.
+fn is_vector_string(string: &str) -> bool {
+    let fields: Vec<&str> = string.split(',').collect();
+    fields.len() == 2
+        && fields
+            .iter()
+            .all(|field| field.trim().parse::<f32>().is_ok())
+}
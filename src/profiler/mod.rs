@@ -0,0 +1,371 @@
+mod overlay;
+
+pub mod prelude {
+    pub use super::overlay::ProfilerOverlay;
+    pub use super::Profiled;
+    pub use super::SystemProfiler;
+    pub use super::SystemSummary;
+    pub use super::WithProfiled;
+}
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fs::create_dir_all;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use amethyst::ecs::{Resources, System};
+use amethyst::utils::application_root_dir;
+use deathframe::custom_game_data::prelude::CustomGameDataBuilder;
+
+use crate::resource_helpers::write_new_file;
+
+const PROFILE_VAR_NAME: &str = "STABMAN_PROFILE";
+const PROFILES_DIRNAME: &str = "profiles";
+/// Amount of recent runs per system, from which the p99 is calculated.
+const RECENT_RUNS: usize = 600;
+/// Maximum amount of runs recorded for the Chrome trace;
+/// runs beyond this amount are only aggregated.
+const MAX_TRACE_EVENTS: usize = 500_000;
+
+/// Measures the run time of every system wrapped in `Profiled`.
+/// Opt-in; only enabled when the environment variable `STABMAN_PROFILE` is set.
+/// Cloning returns a handle to the same measurements.
+#[derive(Clone)]
+pub struct SystemProfiler {
+    enabled: bool,
+    data:    Arc<Mutex<ProfilerData>>,
+}
+
+impl SystemProfiler {
+    pub fn from_env() -> Self {
+        let enabled = env::vars().any(|(key, val)| {
+            key == PROFILE_VAR_NAME && !val.is_empty() && val != "0"
+        });
+        Self {
+            enabled: enabled,
+            data:    Arc::new(Mutex::new(ProfilerData::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn record(&self, name: &'static str, start: Instant, duration: Duration) {
+        if let Ok(mut data) = self.data.lock() {
+            data.record(name, start, duration);
+        }
+    }
+
+    /// Returns the aggregated run times of all systems,
+    /// sorted by their average run time, slowest first.
+    pub fn summaries(&self) -> Vec<SystemSummary> {
+        let data = match self.data.lock() {
+            Ok(data) => data,
+            Err(_) => return Vec::new(),
+        };
+        let mut summaries = data
+            .systems
+            .iter()
+            .map(|(name, stats)| stats.summary(*name))
+            .collect::<Vec<SystemSummary>>();
+        summaries.sort_by(|a, b| b.avg.cmp(&a.avg));
+        summaries
+    }
+
+    /// Writes the summaries as CSV, and all recorded runs as a Chrome trace
+    /// (see `chrome://tracing`), to the `profiles` directory.
+    /// Does nothing if the profiler isn't enabled.
+    pub fn dump(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        let dir = format!("{}/{}", application_root_dir(), PROFILES_DIRNAME);
+        if let Err(err) = create_dir_all(&dir) {
+            error!("Couldn't create profiles directory `{}`: {}", dir, err);
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        // Profiles dumped within the same second get separate files.
+        let csv_name = format!("systems_{}", timestamp);
+        match write_new_file(&dir, &csv_name, "csv", self.to_csv()) {
+            Ok(path) => info!("Wrote system profile to {}", path),
+            Err(err) => error!("Couldn't write `{}`: {}", csv_name, err),
+        }
+
+        let trace_name = format!("trace_{}", timestamp);
+        match self.to_chrome_trace() {
+            Ok(trace) => match write_new_file(&dir, &trace_name, "json", trace)
+            {
+                Ok(path) => info!("Wrote system trace to {}", path),
+                Err(err) => {
+                    error!("Couldn't write `{}`: {}", trace_name, err)
+                }
+            },
+            Err(err) => error!("Couldn't serialize system trace: {}", err),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("system,runs,min_ms,avg_ms,max_ms,p99_ms\n");
+        for summary in self.summaries() {
+            csv.push_str(&format!(
+                "{},{},{:.4},{:.4},{:.4},{:.4}\n",
+                summary.name,
+                summary.runs,
+                as_ms(summary.min),
+                as_ms(summary.avg),
+                as_ms(summary.max),
+                as_ms(summary.p99),
+            ));
+        }
+        csv
+    }
+
+    fn to_chrome_trace(&self) -> Result<String, String> {
+        let data = self.data.lock().map_err(|err| err.to_string())?;
+        serde_json::to_string(&ChromeTrace {
+            trace_events: &data.trace,
+        })
+        .map_err(|err| err.to_string())
+    }
+}
+
+/// Aggregated run times of a single system.
+/// The `p99` is calculated from the most recent runs only.
+pub struct SystemSummary {
+    pub name: &'static str,
+    pub runs: u64,
+    pub min:  Duration,
+    pub avg:  Duration,
+    pub max:  Duration,
+    pub p99:  Duration,
+}
+
+struct ProfilerData {
+    start:   Instant,
+    systems: HashMap<&'static str, SystemStats>,
+    trace:   Vec<TraceEvent>,
+}
+
+impl ProfilerData {
+    fn new() -> Self {
+        Self {
+            start:   Instant::now(),
+            systems: HashMap::new(),
+            trace:   Vec::new(),
+        }
+    }
+
+    fn record(
+        &mut self,
+        name: &'static str,
+        start: Instant,
+        duration: Duration,
+    ) {
+        self.systems
+            .entry(name)
+            .or_insert_with(SystemStats::default)
+            .record(duration);
+
+        if self.trace.len() < MAX_TRACE_EVENTS {
+            self.trace.push(TraceEvent {
+                name: name,
+                ph:   "X",
+                ts:   as_us(start.duration_since(self.start)),
+                dur:  as_us(duration),
+                pid:  1,
+                tid:  current_thread_id(),
+            });
+        }
+    }
+}
+
+#[derive(Default)]
+struct SystemStats {
+    runs:   u64,
+    total:  Duration,
+    min:    Option<Duration>,
+    max:    Duration,
+    recent: VecDeque<Duration>,
+}
+
+impl SystemStats {
+    fn record(&mut self, duration: Duration) {
+        self.runs += 1;
+        self.total += duration;
+        self.min = Some(self.min.map_or(duration, |min| min.min(duration)));
+        self.max = self.max.max(duration);
+        if self.recent.len() >= RECENT_RUNS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(duration);
+    }
+
+    fn summary(&self, name: &'static str) -> SystemSummary {
+        let mut recent = self.recent.iter().cloned().collect::<Vec<Duration>>();
+        recent.sort();
+        let p99_index =
+            ((recent.len() as f32 * 0.99).ceil() as usize).saturating_sub(1);
+        SystemSummary {
+            name: name,
+            runs: self.runs,
+            min:  self.min.unwrap_or_default(),
+            avg:  if self.runs > 0 {
+                self.total / self.runs as u32
+            } else {
+                Duration::default()
+            },
+            max:  self.max,
+            p99:  recent.get(p99_index).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// A complete event ("ph": "X") in the Chrome trace event format.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    ph:   &'static str,
+    ts:   u64,
+    dur:  u64,
+    pid:  u32,
+    tid:  u64,
+}
+
+#[derive(Serialize)]
+struct ChromeTrace<'a> {
+    #[serde(rename = "traceEvents")]
+    trace_events: &'a [TraceEvent],
+}
+
+/// Wraps a system, and records its run time with the `SystemProfiler`.
+pub struct Profiled<S> {
+    name:     &'static str,
+    system:   S,
+    profiler: SystemProfiler,
+}
+
+impl<S> Profiled<S> {
+    pub fn new(
+        name: &'static str,
+        system: S,
+        profiler: SystemProfiler,
+    ) -> Self {
+        Self {
+            name:     name,
+            system:   system,
+            profiler: profiler,
+        }
+    }
+}
+
+impl<'a, S> System<'a> for Profiled<S>
+where
+    S: System<'a>,
+{
+    type SystemData = S::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        if !self.profiler.is_enabled() {
+            self.system.run(data);
+            return;
+        }
+
+        let start = Instant::now();
+        self.system.run(data);
+        self.profiler.record(self.name, start, start.elapsed());
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+    }
+}
+
+/// Adds systems wrapped in `Profiled` to the game data.
+pub trait WithProfiled<'a>: Sized {
+    fn with_profiled<S>(
+        self,
+        profiler: &SystemProfiler,
+        dispatcher_name: &str,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> amethyst::Result<Self>
+    where
+        S: for<'c> System<'c> + Send + 'a;
+
+    fn with_core_profiled<S>(
+        self,
+        profiler: &SystemProfiler,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> amethyst::Result<Self>
+    where
+        S: for<'c> System<'c> + Send + 'a;
+}
+
+impl<'a, 'b, C> WithProfiled<'a> for CustomGameDataBuilder<'a, 'b, C> {
+    fn with_profiled<S>(
+        self,
+        profiler: &SystemProfiler,
+        dispatcher_name: &str,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> amethyst::Result<Self>
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.with(
+            dispatcher_name,
+            Profiled::new(name, system, profiler.clone()),
+            name,
+            dependencies,
+        )
+    }
+
+    fn with_core_profiled<S>(
+        self,
+        profiler: &SystemProfiler,
+        system: S,
+        name: &'static str,
+        dependencies: &[&str],
+    ) -> amethyst::Result<Self>
+    where
+        S: for<'c> System<'c> + Send + 'a,
+    {
+        self.with_core(
+            Profiled::new(name, system, profiler.clone()),
+            name,
+            dependencies,
+        )
+    }
+}
+
+fn current_thread_id() -> u64 {
+    // `ThreadId` can't be converted to a number on stable, so parse its debug output.
+    format!("{:?}", thread::current().id())
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .unwrap_or(0)
+}
+
+fn as_us(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + u64::from(duration.subsec_micros())
+}
+
+pub fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0
+        + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use amethyst::ecs::{Entity, World};
+use amethyst::ui::{Anchor as AmethystAnchor, UiText};
+
+use super::{as_ms, SystemProfiler};
+use crate::states::helpers::{get_font, new_ui_transform};
+use crate::world_helpers::*;
+
+const LINE_ID_PREFIX: &str = "profiler_overlay_line";
+const PROFILER_OVERLAY_Z: f32 = 20.0;
+const PADDING: f32 = 8.0;
+
+/// Displays the slowest systems' run times from the `SystemProfiler` resource
+/// (development mode only). Owned by the `Ingame` state.
+pub struct ProfilerOverlay {
+    line_entities: Vec<Entity>,
+    last_refresh:  Option<Instant>,
+}
+
+impl ProfilerOverlay {
+    pub fn new(world: &mut World) -> Self {
+        let settings = world.settings().profiler_overlay;
+        let font = get_font(&settings.font_file, world);
+
+        let line_entities = (0 .. settings.lines)
+            .map(|i| {
+                let mut ui_text = UiText::new(
+                    font.clone(),
+                    String::new(),
+                    settings.font_color,
+                    settings.font_size,
+                );
+                ui_text.align = AmethystAnchor::MiddleLeft;
+                world
+                    .create_entity()
+                    .with(new_ui_transform(
+                        format!("{}_{}", LINE_ID_PREFIX, i),
+                        AmethystAnchor::BottomLeft,
+                        (
+                            settings.width * 0.5 + PADDING,
+                            (settings.lines - i) as f32 * settings.line_height
+                                - settings.line_height * 0.5
+                                + PADDING,
+                            PROFILER_OVERLAY_Z,
+                            settings.width,
+                            settings.line_height,
+                            0,
+                        ),
+                    ))
+                    .with(ui_text)
+                    .build()
+            })
+            .collect();
+
+        Self {
+            line_entities: line_entities,
+            last_refresh:  None,
+        }
+    }
+
+    /// Deletes the overlay's entities; call before dropping the overlay.
+    pub fn delete_ui(&mut self, world: &mut World) {
+        world
+            .delete_entities(&self.line_entities)
+            .expect("Should delete profiler overlay entities");
+        self.line_entities.clear();
+    }
+
+    /// Refreshes the displayed run times, in the configured interval.
+    pub fn update(&mut self, world: &mut World) {
+        let settings = world.settings().profiler_overlay;
+        if self.last_refresh.map_or(false, |last| {
+            last.elapsed() < Duration::from_millis(settings.refresh_ms)
+        }) {
+            return;
+        }
+        self.last_refresh = Some(Instant::now());
+
+        let profiler = world.read_resource::<SystemProfiler>().clone();
+        let mut lines = if profiler.is_enabled() {
+            vec![format!(
+                "{:<36} {:>8} {:>8} {:>8} {:>8}",
+                "SYSTEM (ms)", "min", "avg", "max", "p99"
+            )]
+        } else {
+            vec![
+                "System profiler is disabled;".to_string(),
+                "set the environment variable STABMAN_PROFILE to enable it"
+                    .to_string(),
+            ]
+        };
+        lines.extend(profiler.summaries().into_iter().map(|summary| {
+            format!(
+                "{:<36} {:>8.3} {:>8.3} {:>8.3} {:>8.3}",
+                summary.name,
+                as_ms(summary.min),
+                as_ms(summary.avg),
+                as_ms(summary.max),
+                as_ms(summary.p99),
+            )
+        }));
+
+        let mut ui_texts = world.write_storage::<UiText>();
+        for (i, entity) in self.line_entities.iter().enumerate() {
+            if let Some(ui_text) = ui_texts.get_mut(*entity) {
+                ui_text.text = lines.get(i).cloned().unwrap_or_default();
+            }
+        }
+    }
+}
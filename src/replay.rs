@@ -0,0 +1,115 @@
+//! Recordings of a level run's keyboard input, which `--record <file>` writes
+//! while playing, and which `--replay <file>` plays back in the headless `Simulation`.
+//! A run starts when a level has loaded, and ends when the level is left,
+//! for example when the player dies, beats the level or quits;
+//! the file always holds the most recent run.
+//! Controller input isn't recorded.
+
+use std::time::Duration;
+
+use amethyst::renderer::VirtualKeyCode;
+
+use crate::components::prelude::Player;
+use crate::resource_helpers::{read_file, write_file};
+use crate::states::helpers::CampaignType;
+
+/// The input of a single frame.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// The frame's delta time, in nanoseconds.
+    pub delta_nanos: u64,
+    /// The keyboard keys, which were held down during the frame.
+    pub keys:        Vec<VirtualKeyCode>,
+}
+
+impl ReplayFrame {
+    pub fn delta(&self) -> Duration {
+        Duration::from_nanos(self.delta_nanos)
+    }
+}
+
+/// A recorded level run.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub campaign: CampaignType,
+    pub level:    String,
+    /// The player at the start of the run, with the items and health
+    /// from the previous levels.
+    pub player:   Option<Player>,
+    pub frames:   Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = read_file(path).map_err(|err| {
+            format!("Couldn't read replay `{}`: {}", path, err)
+        })?;
+        ron::de::from_str(&raw)
+            .map_err(|err| format!("Couldn't parse replay `{}`: {}", path, err))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let serialized = ron::ser::to_string(self).map_err(|err| {
+            format!("Couldn't serialize replay `{}`: {}", path, err)
+        })?;
+        write_file(path, serialized)
+            .map_err(|err| format!("Couldn't write replay `{}`: {}", path, err))
+    }
+}
+
+/// Records the runs of `Ingame`, and writes each one to the file when it ends.
+pub struct ReplayRecorder {
+    path:   String,
+    replay: Option<Replay>,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: String) -> Self {
+        Self {
+            path:   path,
+            replay: None,
+        }
+    }
+
+    /// Starts recording a new run; a run, which is still being recorded, is ended first.
+    pub fn start(
+        &mut self,
+        campaign: CampaignType,
+        level: String,
+        player: Option<Player>,
+    ) {
+        self.finish();
+        self.replay = Some(Replay {
+            campaign: campaign,
+            level:    level,
+            player:   player,
+            frames:   Vec::new(),
+        });
+    }
+
+    /// Records a frame of the current run, if one is being recorded.
+    pub fn record_frame(&mut self, delta: Duration, keys: Vec<VirtualKeyCode>) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.frames.push(ReplayFrame {
+                delta_nanos: delta.as_secs() * 1_000_000_000
+                    + u64::from(delta.subsec_nanos()),
+                keys:        keys,
+            });
+        }
+    }
+
+    /// Ends the current run, if one is being recorded, and writes it to the file.
+    pub fn finish(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            match replay.save(&self.path) {
+                Ok(()) => info!(
+                    "Recorded {} frames of level {} to {}",
+                    replay.frames.len(),
+                    replay.level,
+                    self.path
+                ),
+                Err(err) => error!("{}", err),
+            }
+        }
+    }
+}
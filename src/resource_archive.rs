@@ -0,0 +1,481 @@
+//! A packed archive of the `resources` directory, for release builds.
+//! If `resources.pak` exists next to the executable, `read_file` and
+//! the asset loader (through the `ArchiveSource`) read resources from it,
+//! and fall back to the loose files in `resources/` for anything it doesn't contain.
+//! In development mode, loose files take precedence, so they can still be edited.
+//!
+//! The archive is created with the `--pack-resources` command line tool.
+//! Its layout (all integers are little endian):
+//! - magic bytes `STBPAK` and the format version (`u16`)
+//! - the amount of entries (`u32`)
+//! - the index: for every entry, its path length (`u16`), its path
+//!   (relative to the resources directory), its data offset (`u64`, relative to
+//!   the end of the index), its stored size and its original size (`u64`),
+//!   and its compression (`u8`: `0` stored, `1` deflate)
+//! - the data of all entries
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use amethyst::assets::{Directory, Error as AssetsError, Source};
+use amethyst::utils::application_root_dir;
+
+use crate::resource_helpers::resources_dir;
+
+pub const ARCHIVE_FILENAME: &str = "resources.pak";
+
+const MAGIC: &[u8; 6] = b"STBPAK";
+const VERSION: u16 = 1;
+const COMPRESSION_NONE: u8 = 0;
+const COMPRESSION_DEFLATE: u8 = 1;
+/// Entries are only stored compressed, if that saves at least this fraction of their size.
+const MIN_COMPRESSION_SAVINGS: f32 = 0.1;
+
+lazy_static! {
+    /// The archive next to the executable, if it exists.
+    /// Its index is read once, on first access from any thread
+    /// (so an unreadable archive's error is only logged once).
+    static ref ARCHIVE: Option<Arc<ResourceArchive>> = open_default_archive();
+}
+
+struct ArchiveEntry {
+    offset:        u64,
+    size:          u64,
+    original_size: u64,
+    compression:   u8,
+}
+
+pub struct ResourceArchive {
+    path:       String,
+    data_start: u64,
+    entries:    HashMap<String, ArchiveEntry>,
+}
+
+/// The amount of packed files, and their total size before and after packing.
+pub struct PackSummary {
+    pub files:         usize,
+    pub original_size: u64,
+    pub packed_size:   u64,
+}
+
+impl ResourceArchive {
+    /// Reads the archive's index.
+    pub fn open(path: &str) -> Result<Self, String> {
+        let err = |err: std::io::Error| {
+            format!("Couldn't read resource archive `{}`: {}", path, err)
+        };
+        let mut file = File::open(path).map_err(err)?;
+
+        let mut magic = [0; 6];
+        file.read_exact(&mut magic).map_err(err)?;
+        if &magic != MAGIC {
+            return Err(format!("`{}` isn't a resource archive", path));
+        }
+        let version = u16::from_le_bytes(read_bytes(&mut file).map_err(err)?);
+        if version != VERSION {
+            return Err(format!(
+                "Resource archive `{}` has version {}, expected {}",
+                path, version, VERSION
+            ));
+        }
+
+        let count = u32::from_le_bytes(read_bytes(&mut file).map_err(err)?);
+        let mut entries = HashMap::new();
+        for _ in 0 .. count {
+            let name_len =
+                u16::from_le_bytes(read_bytes(&mut file).map_err(err)?);
+            let mut name = vec![0; name_len as usize];
+            file.read_exact(&mut name).map_err(err)?;
+            let name = String::from_utf8(name).map_err(|_| {
+                format!("Resource archive `{}` has an invalid path", path)
+            })?;
+            let entry = ArchiveEntry {
+                offset:        u64::from_le_bytes(
+                    read_bytes(&mut file).map_err(err)?,
+                ),
+                size:          u64::from_le_bytes(
+                    read_bytes(&mut file).map_err(err)?,
+                ),
+                original_size: u64::from_le_bytes(
+                    read_bytes(&mut file).map_err(err)?,
+                ),
+                compression:   read_bytes::<[u8; 1]>(&mut file).map_err(err)?
+                    [0],
+            };
+            entries.insert(name, entry);
+        }
+        let data_start = file.seek(SeekFrom::Current(0)).map_err(err)?;
+
+        let data_size = file
+            .metadata()
+            .map_err(err)?
+            .len()
+            .saturating_sub(data_start);
+        if entries
+            .values()
+            .any(|entry| entry.offset.saturating_add(entry.size) > data_size)
+        {
+            return Err(format!(
+                "Resource archive `{}` is truncated or corrupted",
+                path
+            ));
+        }
+
+        Ok(Self {
+            path:       path.to_string(),
+            data_start: data_start,
+            entries:    entries,
+        })
+    }
+
+    /// Returns `true` if the archive contains the file
+    /// with the given path, relative to the resources directory.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Reads the file with the given path, relative to the resources directory.
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, String> {
+        let entry = self.entries.get(name).ok_or_else(|| {
+            format!("Resource archive doesn't contain `{}`", name)
+        })?;
+        let err = |err: std::io::Error| {
+            format!("Couldn't read `{}` from `{}`: {}", name, self.path, err)
+        };
+
+        let mut file = File::open(&self.path).map_err(err)?;
+        file.seek(SeekFrom::Start(self.data_start + entry.offset))
+            .map_err(err)?;
+        let mut data = vec![0; entry.size as usize];
+        file.read_exact(&mut data).map_err(err)?;
+
+        let data = match entry.compression {
+            COMPRESSION_NONE => data,
+            COMPRESSION_DEFLATE => {
+                inflate::inflate_bytes(&data).map_err(|err| {
+                    format!("Couldn't decompress `{}`: {}", name, err)
+                })?
+            }
+            compression => {
+                return Err(format!(
+                    "`{}` has an unknown compression: {}",
+                    name, compression
+                ))
+            }
+        };
+        if data.len() as u64 != entry.original_size {
+            return Err(format!("`{}` in `{}` is corrupted", name, self.path));
+        }
+        Ok(data)
+    }
+
+    /// Reads every file, to check that none of them are corrupted.
+    pub fn verify(&self) -> Result<(), String> {
+        self.entries
+            .keys()
+            .map(|name| self.read(name).map(|_| ()))
+            .collect()
+    }
+
+    /// Packs every file of the given directory into a new archive.
+    /// Files are compressed, if `compress` is `true` and it makes them smaller.
+    pub fn pack(
+        dir: &str,
+        output: &str,
+        compress: bool,
+    ) -> Result<PackSummary, String> {
+        let mut names = Vec::new();
+        collect_files(Path::new(dir), "", &mut names)?;
+        names.sort();
+
+        let mut index = Vec::new();
+        let mut data = Vec::new();
+        let mut original_size = 0;
+        for name in &names {
+            if name.len() > std::u16::MAX as usize {
+                return Err(format!(
+                    "Path `{}` is too long for a resource archive",
+                    name
+                ));
+            }
+            let mut content = Vec::new();
+            File::open(Path::new(dir).join(name))
+                .and_then(|mut file| file.read_to_end(&mut content))
+                .map_err(|err| format!("Couldn't read `{}`: {}", name, err))?;
+            original_size += content.len() as u64;
+
+            let compressed = if compress {
+                Some(deflate::deflate_bytes(&content)).filter(|compressed| {
+                    (compressed.len() as f32)
+                        < content.len() as f32 * (1.0 - MIN_COMPRESSION_SAVINGS)
+                })
+            } else {
+                None
+            };
+            let (stored, compression) = match compressed {
+                Some(compressed) => (compressed, COMPRESSION_DEFLATE),
+                None => (content.clone(), COMPRESSION_NONE),
+            };
+
+            index.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            index.extend_from_slice(name.as_bytes());
+            index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            index.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+            index.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            index.push(compression);
+            data.extend_from_slice(&stored);
+        }
+
+        let err = |err: std::io::Error| {
+            format!("Couldn't write resource archive `{}`: {}", output, err)
+        };
+        let mut file = File::create(output).map_err(err)?;
+        file.write_all(MAGIC).map_err(err)?;
+        file.write_all(&VERSION.to_le_bytes()).map_err(err)?;
+        file.write_all(&(names.len() as u32).to_le_bytes())
+            .map_err(err)?;
+        file.write_all(&index).map_err(err)?;
+        file.write_all(&data).map_err(err)?;
+
+        Ok(PackSummary {
+            files:         names.len(),
+            original_size: original_size,
+            packed_size:   data.len() as u64,
+        })
+    }
+}
+
+/// Reads a file from the archive, if the path is in the resources directory,
+/// and the archive contains it. Returns `None` if the loose file should be read;
+/// in development mode, existing loose files are always read.
+pub fn read_archived(path: &str) -> Option<Result<Vec<u8>, String>> {
+    let name = archive_name(path)?;
+    if crate::in_development_mode() && Path::new(path).is_file() {
+        return None;
+    }
+    ARCHIVE
+        .as_ref()
+        .filter(|archive| archive.contains(&name))
+        .map(|archive| archive.read(&name))
+}
+
+/// Returns `true` if the archive contains the file with the given path.
+pub fn is_archived(path: &str) -> bool {
+    archive_name(path).map_or(false, |name| {
+        ARCHIVE
+            .as_ref()
+            .map_or(false, |archive| archive.contains(&name))
+    })
+}
+
+/// The amethyst asset loader's source, which reads resources from the archive,
+/// and everything else from the filesystem.
+pub struct ArchiveSource {
+    directory: Directory,
+}
+
+impl Default for ArchiveSource {
+    fn default() -> Self {
+        Self {
+            directory: Directory::new(application_root_dir()),
+        }
+    }
+}
+
+impl Source for ArchiveSource {
+    fn modified(&self, path: &str) -> Result<u64, AssetsError> {
+        if is_archived(path) && !Path::new(path).is_file() {
+            // Archived files never change; hot reloading uses loose files.
+            Ok(0)
+        } else {
+            self.directory.modified(path)
+        }
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, AssetsError> {
+        match read_archived(path) {
+            Some(result) => result.map_err(AssetsError::from),
+            None => self.directory.load(path),
+        }
+    }
+}
+
+/// Returns the path relative to the resources directory,
+/// if the path is in the resources directory.
+fn archive_name(path: &str) -> Option<String> {
+    let prefix = format!("{}/", resources_dir());
+    if path.starts_with(&prefix) {
+        Some(path[prefix.len() ..].to_string())
+    } else {
+        None
+    }
+}
+
+fn open_default_archive() -> Option<Arc<ResourceArchive>> {
+    let path = format!("{}/{}", application_root_dir(), ARCHIVE_FILENAME);
+    if !Path::new(&path).is_file() {
+        return None;
+    }
+    match ResourceArchive::open(&path) {
+        Ok(archive) => Some(Arc::new(archive)),
+        Err(err) => {
+            error!("{}; using loose resource files", err);
+            None
+        }
+    }
+}
+
+/// Collects the paths of all files in the directory (recursively),
+/// relative to the top directory, with `/` as the separator.
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    names: &mut Vec<String>,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|err| format!("Couldn't read directory {:?}: {}", dir, err))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| err.to_string())?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, &format!("{}/", name), names)?;
+        } else {
+            names.push(name);
+        }
+    }
+    Ok(())
+}
+
+fn read_bytes<B>(file: &mut File) -> std::io::Result<B>
+where
+    B: Default + AsMut<[u8]>,
+{
+    let mut bytes = B::default();
+    file.read_exact(bytes.as_mut())?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory with a few resource files, one of them in a subdirectory,
+    /// which is deleted when dropped.
+    struct TestDir {
+        path: String,
+    }
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = format!(
+                "{}/stabman_archive_{}_{}",
+                std::env::temp_dir().display(),
+                name,
+                std::process::id()
+            );
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(format!("{}/resources/levels", path)).unwrap();
+            for (name, content) in test_files() {
+                fs::write(format!("{}/resources/{}", path, name), content)
+                    .unwrap();
+            }
+            Self { path: path }
+        }
+
+        fn resources(&self) -> String {
+            format!("{}/resources", self.path)
+        }
+
+        fn archive(&self) -> String {
+            format!("{}/{}", self.path, ARCHIVE_FILENAME)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+
+    fn test_files() -> Vec<(&'static str, Vec<u8>)> {
+        vec![
+            ("settings.ron", b"(value: 1)".to_vec()),
+            (
+                "levels/level.json",
+                "{ \"tiles\": [] }".repeat(64).into_bytes(),
+            ),
+            ("empty", Vec::new()),
+        ]
+    }
+
+    fn assert_round_trip(compress: bool) {
+        let dir = TestDir::new(if compress { "deflated" } else { "stored" });
+        let summary =
+            ResourceArchive::pack(&dir.resources(), &dir.archive(), compress)
+                .unwrap();
+        assert_eq!(summary.files, test_files().len());
+        assert_eq!(summary.packed_size < summary.original_size, compress);
+
+        let archive = ResourceArchive::open(&dir.archive()).unwrap();
+        for (name, content) in test_files() {
+            assert!(archive.contains(name));
+            assert_eq!(archive.read(name).unwrap(), content);
+        }
+        assert!(!archive.contains("missing"));
+        assert!(archive.read("missing").is_err());
+        assert!(archive.verify().is_ok());
+    }
+
+    #[test]
+    fn round_trip_stored() {
+        assert_round_trip(false);
+    }
+
+    #[test]
+    fn round_trip_deflated() {
+        assert_round_trip(true);
+    }
+
+    #[test]
+    fn corrupted_archive_errors() {
+        let dir = TestDir::new("corrupted");
+        ResourceArchive::pack(&dir.resources(), &dir.archive(), true).unwrap();
+        let bytes = fs::read(dir.archive()).unwrap();
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        fs::write(dir.archive(), &wrong_magic).unwrap();
+        assert!(ResourceArchive::open(&dir.archive()).is_err());
+
+        // The first entry's original size, after the header,
+        // its path length, path, offset and stored size.
+        let name = "empty";
+        let original_size_at = 6 + 2 + 4 + 2 + name.len() + 8 + 8;
+        let mut wrong_size = bytes.clone();
+        wrong_size[original_size_at] = 1;
+        fs::write(dir.archive(), &wrong_size).unwrap();
+        let archive = ResourceArchive::open(&dir.archive()).unwrap();
+        assert!(archive.read(name).is_err());
+        assert!(archive.verify().is_err());
+    }
+
+    #[test]
+    fn truncated_archive_errors() {
+        let dir = TestDir::new("truncated");
+        ResourceArchive::pack(&dir.resources(), &dir.archive(), false).unwrap();
+        let bytes = fs::read(dir.archive()).unwrap();
+
+        for len in &[0, 4, 20, bytes.len() - 1] {
+            fs::write(dir.archive(), &bytes[.. *len]).unwrap();
+            assert!(
+                ResourceArchive::open(&dir.archive()).is_err(),
+                "Archive truncated to {} bytes should fail to open",
+                len
+            );
+        }
+    }
+}
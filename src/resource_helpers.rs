@@ -1,11 +1,14 @@
 use std::fmt::Display;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::prelude::*;
 use std::path::Path;
 
+use amethyst::renderer::DisplayConfig;
 use amethyst::utils::application_root_dir;
 
+use crate::resource_archive::{is_archived, read_archived};
+
 const RESOURCES_DIRNAME: &str = "resources";
 
 pub fn resources_dir() -> String {
@@ -16,13 +19,34 @@ pub fn resource<T: ToString>(path: T) -> String {
     format!("{}/{}", resources_dir(), path.to_string())
 }
 
+/// Returns `true` if the file exists in the resources directory,
+/// either as a loose file or in the resource archive.
+pub fn resource_exists(path: &str) -> bool {
+    let path = resource(path);
+    Path::new(&path).is_file() || is_archived(&path)
+}
+
+/// Reads the file; files in the resources directory
+/// may be read from the resource archive.
 pub fn read_file<P>(path: P) -> Result<String, io::Error>
 where
     P: AsRef<Path> + Display,
 {
+    String::from_utf8(read_file_bytes(path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Reads the file's raw bytes, like `read_file`.
+pub fn read_file_bytes<P>(path: P) -> Result<Vec<u8>, io::Error>
+where
+    P: AsRef<Path> + Display,
+{
+    if let Some(result) = read_archived(&path.to_string()) {
+        return result.map_err(|err| io::Error::new(io::ErrorKind::Other, err));
+    }
     let mut file = File::open(&path)?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
     Ok(content)
 }
 
@@ -34,3 +58,46 @@ where
     let mut file = File::create(path)?;
     write!(&mut file, "{}", data.to_string())
 }
+
+/// Writes the data to a new file `DIR/NAME.EXTENSION`, and returns its path.
+/// Existing files are never overwritten: if the file already exists,
+/// a counter is appended to the name (`NAME_2.EXTENSION`, ...).
+pub fn write_new_file<S>(
+    dir: &str,
+    name: &str,
+    extension: &str,
+    data: S,
+) -> Result<String, io::Error>
+where
+    S: ToString,
+{
+    let mut path = format!("{}/{}.{}", dir, name, extension);
+    let mut counter = 1;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                write!(&mut file, "{}", data.to_string())?;
+                return Ok(path);
+            }
+            Err(err) => {
+                if err.kind() != io::ErrorKind::AlreadyExists {
+                    return Err(err);
+                }
+                counter += 1;
+                path = format!("{}/{}_{}.{}", dir, name, counter, extension);
+            }
+        }
+    }
+}
+
+/// Loads `display.ron`, which may be in the resource archive.
+pub fn load_display_config() -> DisplayConfig {
+    let path = resource("config/display.ron");
+    read_file(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|raw| ron::de::from_str(&raw).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            error!("Couldn't load display config `{}`: {}", path, err);
+            DisplayConfig::default()
+        })
+}
@@ -1,19 +1,36 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use deathframe::geo::Vector;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::resource_helpers::resource;
+
 pub mod prelude {
     pub use super::Settings;
+    pub use super::SettingsAnalytics;
     pub use super::SettingsCamera;
+    pub use super::SettingsDebugOverlay;
+    pub use super::SettingsDevConsole;
+    pub use super::SettingsEconomy;
+    pub use super::SettingsEntityInspector;
     pub use super::SettingsEntityLoader;
+    pub use super::SettingsFrameStep;
     pub use super::SettingsHarmful;
+    pub use super::SettingsHeatmap;
     pub use super::SettingsItem;
     pub use super::SettingsItems;
+    pub use super::SettingsLevelLoading;
     pub use super::SettingsLevelManager;
     pub use super::SettingsLevelManagerCampaign;
+    pub use super::SettingsLevelPreview;
     pub use super::SettingsLoadingText;
+    pub use super::SettingsLogging;
     pub use super::SettingsNoclip;
+    pub use super::SettingsOptionsMenu;
     pub use super::SettingsPlayer;
     pub use super::SettingsPlayerQuickTurnaround;
+    pub use super::SettingsProfilerOverlay;
 }
 
 #[derive(Clone, Deserialize)]
@@ -21,6 +38,7 @@ pub struct Settings {
     pub player:                 SettingsPlayer,
     pub camera:                 SettingsCamera,
     pub loading_text:           SettingsLoadingText,
+    pub level_loading:          SettingsLevelLoading,
     pub level_manager:          SettingsLevelManager,
     pub enemies:                SettingsEnemies,
     pub items:                  SettingsItems,
@@ -31,6 +49,16 @@ pub struct Settings {
     pub entity_loader:          SettingsEntityLoader,
     pub timers_print_to_stdout: bool,
     pub noclip:                 SettingsNoclip,
+    pub options_menu:           SettingsOptionsMenu,
+    pub dev_console:            SettingsDevConsole,
+    pub debug_overlay:          SettingsDebugOverlay,
+    pub frame_step:             SettingsFrameStep,
+    pub entity_inspector:       SettingsEntityInspector,
+    pub profiler_overlay:       SettingsProfilerOverlay,
+    pub logging:                SettingsLogging,
+    pub analytics:              SettingsAnalytics,
+    pub level_preview:          SettingsLevelPreview,
+    pub economy:                SettingsEconomy,
 }
 
 #[derive(Clone, Deserialize)]
@@ -72,9 +100,50 @@ pub enum SettingsPlayerQuickTurnaround {
 
 #[derive(Clone, Deserialize)]
 pub struct SettingsLoadingText {
-    pub text:      String,
-    pub font_file: String,
-    pub font_size: f32,
+    pub text:            String,
+    pub font_file:       String,
+    pub font_size:       f32,
+    pub preload_timeout: f32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsLevelLoading {
+    pub font_file:        String,
+    pub title_font_size:  f32,
+    pub font_size:        f32,
+    pub font_color:       [f32; 4],
+    pub line_height:      f32,
+    pub best_time_prefix: String,
+    pub parsing_text:     String,
+    pub progress_prefix:  String,
+    pub asset_timeout:    f32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsOptionsMenu {
+    pub title_font_size:     f32,
+    pub font_file:           String,
+    pub font_size:           f32,
+    pub font_color:          [f32; 4],
+    pub selected_font_color: [f32; 4],
+    pub row_height:          f32,
+    pub width:               f32,
+    pub slider_width:        usize,
+    pub resolutions:         Vec<(u32, u32)>,
+    pub frame_limits:        Vec<u32>,
+    pub controls_font_size:  f32,
+    pub controls_row_height: f32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsDevConsole {
+    pub font_file:        String,
+    pub font_size:        f32,
+    pub font_color:       [f32; 4],
+    pub input_font_color: [f32; 4],
+    pub line_height:      f32,
+    pub width:            f32,
+    pub output_lines:     usize,
 }
 
 #[derive(Clone, Deserialize)]
@@ -91,9 +160,35 @@ pub struct SettingsLevelManagerCampaign {
     pub song_names:               Vec<String>,
     pub tile_size:                Vector,
     pub savefile_path:            String,
+    pub asset_manifest:           String,
     pub health_increase_on_death: u32,
     pub level_timer_ui:           SettingsTimerUi,
     pub global_timer_ui:          SettingsTimerUi,
+    /// The directory of the mod pack, if this is a mod pack's campaign.
+    #[serde(skip)]
+    pub mod_dir:                  Option<String>,
+}
+
+impl SettingsLevelManagerCampaign {
+    /// Returns the full path of the file with the given path,
+    /// relative to the resources directory.
+    /// A mod pack's campaign uses the pack's own file, if the pack has it.
+    pub fn resource<T>(&self, path: T) -> String
+    where
+        T: ToString,
+    {
+        let path = path.to_string();
+        self.mod_resource(&path).unwrap_or_else(|| resource(path))
+    }
+
+    /// Returns the full path of the mod pack's file with the given path,
+    /// relative to the pack's directory, if the pack has it.
+    pub fn mod_resource(&self, path: &str) -> Option<String> {
+        self.mod_dir
+            .as_ref()
+            .map(|dir| format!("{}/{}", dir, path))
+            .filter(|path| Path::new(path).is_file())
+    }
 }
 
 #[derive(Clone, Deserialize)]
@@ -199,6 +294,106 @@ pub struct SettingsEntityLoader {
     pub load_distance:                  (f32, f32),
     pub enemy_load_distance_difference: (f32, f32),
     pub bullet_load_distance:           (f32, f32),
+    pub grid_cell_size:                 f32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsDebugOverlay {
+    pub z:                      f32,
+    pub line_width:             f32,
+    pub color_default:          [f32; 4],
+    pub color_solid:            [f32; 4],
+    pub color_solid_player:     [f32; 4],
+    pub color_solid_enemy:      [f32; 4],
+    pub color_solid_noclip:     [f32; 4],
+    pub color_harmful:          [f32; 4],
+    pub color_enemy:            [f32; 4],
+    pub color_player_attack:    [f32; 4],
+    pub color_bullet:           [f32; 4],
+    pub color_goal:             [f32; 4],
+    pub color_trigger_distance: [f32; 4],
+    pub color_load_distance:    [f32; 4],
+    pub color_camera_inner:     [f32; 4],
+    pub color_camera_deadzone:  [f32; 4],
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsFrameStep {
+    pub font_file:   String,
+    pub font_size:   f32,
+    pub font_color:  [f32; 4],
+    pub line_height: f32,
+    pub width:       f32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsEntityInspector {
+    pub font_file:           String,
+    pub font_size:           f32,
+    pub font_color:          [f32; 4],
+    pub selected_font_color: [f32; 4],
+    pub line_height:         f32,
+    pub width:               f32,
+    pub lines:               usize,
+    pub highlight_color:     [f32; 4],
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsProfilerOverlay {
+    pub font_file:   String,
+    pub font_size:   f32,
+    pub font_color:  [f32; 4],
+    pub line_height: f32,
+    pub width:       f32,
+    pub lines:       usize,
+    pub refresh_ms:  u64,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsLogging {
+    pub level:            String,
+    pub modules:          HashMap<String, String>,
+    pub log_dir:          String,
+    pub max_session_logs: usize,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsAnalytics {
+    pub enabled:      bool,
+    pub damage_file:  String,
+    pub heatmaps_dir: String,
+    pub heatmap:      SettingsHeatmap,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsHeatmap {
+    pub scale:              f32,
+    pub radius:             f32,
+    pub background_color:   [u8; 3],
+    pub tile_color:         [u8; 3],
+    pub solid_tile_color:   [u8; 3],
+    pub harmful_tile_color: [u8; 3],
+    pub player_color:       [u8; 3],
+    pub goal_color:         [u8; 3],
+    pub death_color:        [u8; 3],
+    pub death_marker_size:  u32,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsLevelPreview {
+    pub output_dir:       String,
+    pub scale:            f32,
+    pub background_color: [u8; 3],
+    pub draw_markers:     bool,
+    pub player_color:     [u8; 3],
+    pub goal_color:       [u8; 3],
+    pub enemy_color:      [u8; 3],
+    pub item_color:       [u8; 3],
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SettingsEconomy {
+    pub required_items: Vec<String>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -0,0 +1,249 @@
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+
+use amethyst::utils::application_root_dir;
+use ron::Value;
+
+use crate::resource_helpers::*;
+use crate::settings::Settings;
+
+const SETTINGS_OVERRIDES_FILENAME: &str = "settings_overrides.ron";
+const SET_ARG: &str = "--set";
+
+/// A single overridden settings value, and where it came from.
+struct SettingsOverride {
+    path:   Vec<String>,
+    value:  Value,
+    source: String,
+}
+
+impl SettingsOverride {
+    fn path_string(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+/// Loads the shipped `settings.ron`, and deep-merges the user's overrides
+/// on top of it, in this order:
+/// - the `settings_overrides.ron` file next to the executable
+///   (or the file given with the `--settings` command line argument);
+///   it has the same structure as `settings.ron`, but every field is optional.
+/// - command line arguments in the form `--set player.jump_strength=450`.
+///
+/// Finally, the other command line arguments, which change settings
+/// (like `--save-slot`), are applied.
+pub fn load_settings() -> Result<Settings, String> {
+    load_settings_with_hash().map(|(settings, _)| settings)
+}
+
+/// Like `load_settings`, but also returns a hash of the merged settings,
+/// which identifies the active settings in crash reports.
+pub fn load_settings_with_hash() -> Result<(Settings, u64), String> {
+    let settings_path = resource("config/settings.ron");
+    let base = read_ron_value(&settings_path)?;
+
+    let mut overrides = file_overrides()?;
+    overrides.append(&mut arg_overrides()?);
+
+    let mut merged = base.clone();
+    for settings_override in overrides.iter() {
+        if !path_exists(&base, &settings_override.path) {
+            return Err(format!(
+                "Unknown settings path `{}` (from {})",
+                settings_override.path_string(),
+                settings_override.source
+            ));
+        }
+        merge(&mut merged, value_at_path(settings_override));
+    }
+
+    let mut hasher = DefaultHasher::new();
+    merged.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let mut settings: Settings = merged.into_rust().map_err(|err| {
+        if let Err(base_err) = base.clone().into_rust::<Settings>() {
+            return format!(
+                "Couldn't parse settings `{}`: {}",
+                settings_path, base_err
+            );
+        }
+        // Find the override which breaks deserialization,
+        // by applying each override on its own.
+        overrides
+            .iter()
+            .find_map(|settings_override| {
+                let mut single = base.clone();
+                merge(&mut single, value_at_path(settings_override));
+                single.into_rust::<Settings>().err().map(|single_err| {
+                    format!(
+                        "Invalid value for settings path `{}` (from {}): {}",
+                        settings_override.path_string(),
+                        settings_override.source,
+                        single_err
+                    )
+                })
+            })
+            .unwrap_or_else(|| {
+                format!("Couldn't parse settings `{}`: {}", settings_path, err)
+            })
+    })?;
+
+    crate::cli::args().apply_to_settings(&mut settings);
+    Ok((settings, hash))
+}
+
+fn read_ron_value(path: &str) -> Result<Value, String> {
+    let raw = read_file(path)
+        .map_err(|err| format!("Couldn't read file `{}`: {}", path, err))?;
+    Value::from_str(&raw)
+        .map_err(|err| format!("Couldn't parse file `{}`: {}", path, err))
+}
+
+/// Returns every leaf value of the user's settings overrides file,
+/// or nothing if the file doesn't exist.
+/// An overrides file given with `--settings` must exist.
+fn file_overrides() -> Result<Vec<SettingsOverride>, String> {
+    let path = match crate::cli::args().settings.clone() {
+        Some(path) => path,
+        None => {
+            let path = format!(
+                "{}/{}",
+                application_root_dir(),
+                SETTINGS_OVERRIDES_FILENAME
+            );
+            if read_file(&path).is_err() {
+                return Ok(Vec::new());
+            }
+            path
+        }
+    };
+    let value = read_ron_value(&path)?;
+    let mut overrides = Vec::new();
+    collect_leaves(&value, &mut Vec::new(), &path, &mut overrides);
+    Ok(overrides)
+}
+
+fn collect_leaves(
+    value: &Value,
+    path: &mut Vec<String>,
+    source: &str,
+    overrides: &mut Vec<SettingsOverride>,
+) {
+    match value {
+        Value::Map(map) if !map.is_empty() => {
+            for (key, child) in map.iter() {
+                path.push(key_name(key));
+                collect_leaves(child, path, source, overrides);
+                path.pop();
+            }
+        }
+        // An empty file (or empty map) doesn't override anything.
+        _ if path.is_empty() => (),
+        _ => overrides.push(SettingsOverride {
+            path:   path.clone(),
+            value:  value.clone(),
+            source: format!("`{}`", source),
+        }),
+    }
+}
+
+/// Returns the overrides from `--set path=value` command line arguments.
+/// Values are parsed as RON; if that fails, they are used as strings.
+fn arg_overrides() -> Result<Vec<SettingsOverride>, String> {
+    let mut overrides = Vec::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let assignment = if arg == SET_ARG {
+            args.next().ok_or_else(|| {
+                format!("Missing `path=value` after `{}`", SET_ARG)
+            })?
+        } else if arg.starts_with(&format!("{}=", SET_ARG)) {
+            arg[SET_ARG.len() + 1 ..].to_string()
+        } else {
+            continue;
+        };
+
+        let mut split = assignment.splitn(2, '=');
+        let path = split.next().unwrap_or_default().trim();
+        let raw_value = split.next().ok_or_else(|| {
+            format!(
+                "Invalid `{} {}`, expected `{} path=value`",
+                SET_ARG, assignment, SET_ARG
+            )
+        })?;
+        if path.is_empty() {
+            return Err(format!(
+                "Invalid `{} {}`, the settings path is empty",
+                SET_ARG, assignment
+            ));
+        }
+
+        overrides.push(SettingsOverride {
+            path:   path.split('.').map(ToString::to_string).collect(),
+            value:  Value::from_str(raw_value)
+                .unwrap_or_else(|_| Value::String(raw_value.to_string())),
+            source: format!("`{} {}`", SET_ARG, assignment),
+        });
+    }
+
+    Ok(overrides)
+}
+
+/// Wraps the override's value in maps, for each segment of its path.
+fn value_at_path(settings_override: &SettingsOverride) -> Value {
+    settings_override.path.iter().rev().fold(
+        settings_override.value.clone(),
+        |value, key| {
+            Value::Map(
+                vec![(Value::String(key.clone()), value)]
+                    .into_iter()
+                    .collect(),
+            )
+        },
+    )
+}
+
+fn path_exists(value: &Value, path: &[String]) -> bool {
+    match path.split_first() {
+        None => true,
+        Some((key, rest)) => match value {
+            Value::Map(map) => map
+                .iter()
+                .find(|(map_key, _)| key_name(map_key) == *key)
+                .map(|(_, child)| path_exists(child, rest))
+                .unwrap_or(false),
+            _ => false,
+        },
+    }
+}
+
+/// Merges `overlay` into `base`. Maps are merged recursively,
+/// any other value in `overlay` replaces the value in `base`.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Map(base_map), Value::Map(overlay_map)) => {
+            for (key, value) in overlay_map.iter() {
+                match base_map.remove(key) {
+                    Some(mut existing) => {
+                        merge(&mut existing, value.clone());
+                        base_map.insert(key.clone(), existing);
+                    }
+                    None => {
+                        base_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
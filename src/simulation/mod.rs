@@ -0,0 +1,370 @@
+//! A headless simulation of the ingame world, for gameplay regression tests.
+//! Builds the ingame ECS world and dispatcher without a window, renderer or audio,
+//! loads a level file, and runs the game for a number of ticks with scripted inputs.
+//! The tests are only compiled for `cargo test`;
+//! the `--replay` tool plays recorded input back in the simulation.
+
+#[cfg(test)]
+mod scenarios;
+#[cfg(test)]
+mod smoke_test;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use amethyst::assets::AssetStorage;
+use amethyst::core::timing::Time;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::{Join, World};
+use amethyst::input::{Bindings, InputBundle};
+use amethyst::renderer::{
+    Camera as AmethystCamera,
+    Hidden,
+    ScreenDimensions,
+    SpriteRender,
+    SpriteSheet,
+    Texture,
+    TextureHandle,
+    Transparent,
+    VirtualKeyCode,
+};
+use amethyst::shrev::EventChannel;
+use amethyst::ui::{UiText, UiTransform};
+use amethyst::utils::application_root_dir;
+use amethyst::winit::{
+    DeviceId,
+    ElementState,
+    Event,
+    KeyboardInput,
+    ModifiersState,
+    WindowEvent,
+    WindowId,
+};
+use amethyst::{Application, DataInit, StateData};
+use deathframe::custom_game_data::prelude::*;
+
+use crate::components::prelude::*;
+use crate::crash_report::CrashReporter;
+#[cfg(test)]
+use crate::dev_console::prelude::run_command;
+use crate::level_manager::build_level;
+use crate::mod_packs::ModPacks;
+use crate::profiler::prelude::*;
+use crate::resource_helpers::*;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+use crate::states::helpers::*;
+use crate::states::Startup;
+use crate::systems::prelude::*;
+use crate::user_bindings::prelude::*;
+use crate::CustomData;
+
+/// An input, which is held down while the simulation runs.
+#[cfg(test)]
+#[derive(Clone, Copy)]
+pub enum SimInput {
+    /// An action from `bindings.ron`, for example `"player_jump"`.
+    Action(&'static str),
+    /// A side of an emulated axis from `bindings.ron`,
+    /// for example `("player_x_keyboard", AxisSide::Pos)`.
+    Axis(&'static str, AxisSide),
+}
+
+/// The ingame world and dispatcher, without a window, renderer or audio.
+/// Inputs are fed to amethyst's `InputHandler` as keyboard events,
+/// using the default keyboard bindings from `bindings.ron`.
+/// Noclip is always available, regardless of development mode.
+pub struct Simulation<'a, 'b> {
+    world:     World,
+    game_data: CustomGameData<'a, 'b, CustomData>,
+    #[cfg(test)]
+    bindings:  BindingsConfig,
+    held_keys: HashSet<VirtualKeyCode>,
+}
+
+impl<'a, 'b> Simulation<'a, 'b> {
+    pub fn new() -> Result<Self, String> {
+        // Only used for its world, which has the asset loader and time resources.
+        let mut world = Application::<CustomGameData<CustomData>>::build(
+            application_root_dir(),
+            Startup::default(),
+        )
+        .map_err(|err| err.to_string())?
+        .world;
+
+        let mut settings = load_settings()?;
+        // Don't record the simulated player's damage.
+        settings.analytics.enabled = false;
+        let bindings = BindingsConfig::load_default()?;
+        let display_config = load_display_config();
+        let dimensions = display_config.dimensions.unwrap_or((1280, 720));
+        let profiler = SystemProfiler::from_env();
+        // Replays may be of mod pack levels.
+        let mod_packs = ModPacks::scan(&settings.level_manager.normal);
+
+        world.add_resource(settings);
+        world.add_resource(ScreenDimensions::new(
+            dimensions.0,
+            dimensions.1,
+            1.0,
+        ));
+        world.add_resource(AssetStorage::<Texture>::new());
+        world.add_resource(AssetStorage::<SpriteSheet>::new());
+        world.add_resource(profiler.clone());
+        add_ingame_resources(&mut world);
+        world.add_resource(mod_packs);
+        register_render_components(&mut world);
+
+        let game_data = build_game_data(
+            CustomData {
+                display_config: display_config,
+                profiler:       profiler.clone(),
+                crash_reporter: CrashReporter::default(),
+            },
+            bindings.to_bindings()?,
+            &profiler,
+        )
+        .map_err(|err| err.to_string())?
+        .build(&mut world);
+
+        Ok(Self {
+            world:                 world,
+            game_data:             game_data,
+            #[cfg(test)]
+            bindings:              bindings,
+            held_keys:             HashSet::new(),
+        })
+    }
+
+    /// Deletes all entities, and builds the level from the given level file.
+    pub fn load_level<T>(
+        &mut self,
+        campaign: CampaignType,
+        level_filepath: T,
+    ) -> Result<(), String>
+    where
+        T: ToString,
+    {
+        let level_filepath = level_filepath.to_string();
+        let campaign_settings = campaign_settings(&self.world, &campaign);
+
+        self.world.write_resource::<LoadingLevel>().0 = true;
+        self.world.delete_all();
+        self.world.maintain();
+
+        build_level(
+            &mut StateData::new(&mut self.world, &mut self.game_data),
+            campaign_settings,
+            level_filepath.clone(),
+        )?;
+
+        self.world.write_resource::<CurrentCampaign>().0 = campaign;
+        self.world.write_resource::<CurrentLevelName>().0 =
+            level_filepath.rsplit('/').next().map(ToString::to_string);
+        self.world.write_resource::<LoadingLevel>().0 = false;
+        Ok(())
+    }
+
+    /// Runs a single tick (frame) with the given delta time,
+    /// while the given keys are held down. All other keys are released.
+    pub fn tick_keys(
+        &mut self,
+        keys: HashSet<VirtualKeyCode>,
+        delta: Duration,
+    ) -> Result<(), String> {
+        self.hold_keys(keys);
+        {
+            let mut time = self.world.write_resource::<Time>();
+            time.set_delta_time(delta);
+            time.increment_frame_number();
+        }
+        self.game_data
+            .update(&self.world, "ingame")
+            .map_err(|err| err.to_string())?;
+        self.world.maintain();
+        Ok(())
+    }
+
+    /// Replaces the player's `Player` component, for example
+    /// with the player from a previous level.
+    pub fn set_player(&mut self, new_player: Player) {
+        if let Some(player) =
+            (&mut self.world.write_storage::<Player>()).join().next()
+        {
+            *player = new_player;
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn player(&self) -> Option<Player> {
+        (&self.world.read_storage::<Player>())
+            .join()
+            .next()
+            .cloned()
+    }
+
+    pub fn player_position(&self) -> Option<(f32, f32)> {
+        (
+            &self.world.read_storage::<Player>(),
+            &self.world.read_storage::<Transform>(),
+        )
+            .join()
+            .next()
+            .map(|(_, transform)| {
+                let pos = transform.translation();
+                (pos.x, pos.y)
+            })
+    }
+
+    /// Returns `true` if the player has reached the level's goal.
+    pub fn has_completed_level(&self) -> bool {
+        (&self.world.read_storage::<Goal>())
+            .join()
+            .any(|goal| goal.next_level)
+    }
+
+    /// Presses the given keys, and releases all other keys.
+    fn hold_keys(&mut self, keys: HashSet<VirtualKeyCode>) {
+        let mut events = self.world.write_resource::<EventChannel<Event>>();
+        for key in self.held_keys.difference(&keys) {
+            events.single_write(key_event(*key, ElementState::Released));
+        }
+        for key in keys.difference(&self.held_keys) {
+            events.single_write(key_event(*key, ElementState::Pressed));
+        }
+        drop(events);
+
+        self.held_keys = keys;
+    }
+}
+
+/// Scripted inputs, for the gameplay tests.
+#[cfg(test)]
+impl<'a, 'b> Simulation<'a, 'b> {
+    /// Runs a single tick (frame), with a delta time of exactly one frame,
+    /// while the given inputs are held down. All other inputs are released.
+    pub fn tick(&mut self, inputs: &[SimInput]) -> Result<(), String> {
+        let keys = inputs
+            .iter()
+            .map(|input| self.key_for(input))
+            .collect::<Result<HashSet<VirtualKeyCode>, String>>()?;
+        self.tick_keys(keys, Duration::from_secs(1) / crate::FPS)
+    }
+
+    /// Runs the given amount of ticks, while the given inputs are held down.
+    pub fn run(
+        &mut self,
+        ticks: u64,
+        inputs: &[SimInput],
+    ) -> Result<(), String> {
+        for _ in 0 .. ticks {
+            self.tick(inputs)?;
+        }
+        Ok(())
+    }
+
+    /// Runs ticks, while the given inputs are held down, until the condition
+    /// is met. Returns an error, if it isn't met within `max_ticks` ticks.
+    pub fn run_until<F>(
+        &mut self,
+        max_ticks: u64,
+        inputs: &[SimInput],
+        condition: F,
+    ) -> Result<(), String>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        for _ in 0 .. max_ticks {
+            if condition(self) {
+                return Ok(());
+            }
+            self.tick(inputs)?;
+        }
+        if condition(self) {
+            Ok(())
+        } else {
+            Err(format!("Condition wasn't met within {} ticks", max_ticks))
+        }
+    }
+
+    /// Runs a developer console command, for example `give Dash` or `tp 0 0`.
+    pub fn command(&mut self, line: &str) -> Result<String, String> {
+        run_command(&mut self.world, line)
+    }
+
+    pub fn player_velocity(&self) -> Option<(f32, f32)> {
+        (
+            &self.world.read_storage::<Player>(),
+            &self.world.read_storage::<Velocity>(),
+        )
+            .join()
+            .next()
+            .map(|(_, velocity)| (velocity.x, velocity.y))
+    }
+
+    fn key_for(&self, input: &SimInput) -> Result<VirtualKeyCode, String> {
+        match input {
+            SimInput::Action(action) => {
+                self.bindings.key_for_action(action).ok_or_else(|| {
+                    format!("Action `{}` isn't bound to a key", action)
+                })
+            }
+            SimInput::Axis(axis, side) => self
+                .bindings
+                .key_for_axis(axis, *side)
+                .ok_or_else(|| format!("Axis `{}` isn't bound to a key", axis)),
+        }
+    }
+}
+
+/// Builds the `ingame` dispatcher, with the core systems, which handle input.
+fn build_game_data<'a, 'b>(
+    custom_data: CustomData,
+    bindings: Bindings<String, String>,
+    profiler: &SystemProfiler,
+) -> amethyst::Result<CustomGameDataBuilder<'a, 'b, CustomData>> {
+    let game_data = CustomGameData::<CustomData>::new()
+        .custom(custom_data)
+        .dispatcher("ingame")?
+        .with_core_bundle(
+            InputBundle::<String, String>::new().with_bindings(bindings),
+        )?
+        .with_core(InputManagerSystem, "input_manager_system", &[
+            "input_system",
+        ])?
+        .with_core(TimerSystem::default(), "timer_system", &[])?
+        .with("ingame", NoclipSystem::default(), "noclip_system", &[])?;
+    crate::with_ingame_systems(game_data, profiler)
+}
+
+/// Registers the components, which only the level loader and the
+/// rendering systems (which don't exist in the simulation) use.
+fn register_render_components(world: &mut World) {
+    world.register::<AmethystCamera>();
+    world.register::<Hidden>();
+    world.register::<SpriteRender>();
+    world.register::<TextureHandle>();
+    world.register::<Transparent>();
+    world.register::<ScaleOnce>();
+    world.register::<UiText>();
+    world.register::<UiTransform>();
+}
+
+fn key_event(key: VirtualKeyCode, state: ElementState) -> Event {
+    // The `InputHandler` doesn't care about window or device IDs.
+    Event::WindowEvent {
+        window_id: unsafe { WindowId::dummy() },
+        event:     WindowEvent::KeyboardInput {
+            device_id: unsafe { DeviceId::dummy() },
+            input:     KeyboardInput {
+                scancode:        key as u32,
+                state:           state,
+                virtual_keycode: Some(key),
+                modifiers:       ModifiersState::default(),
+            },
+        },
+    }
+}
@@ -0,0 +1,122 @@
+//! Gameplay regression tests, which run in the headless `Simulation`.
+//! All scenarios use the `simulation/flat.json` level: a flat floor with
+//! a wall on the left, spikes left of the player and the goal to the right.
+
+use super::{SimInput, Simulation};
+use crate::resource_helpers::resource;
+use crate::states::helpers::CampaignType;
+use crate::user_bindings::prelude::AxisSide;
+
+const LEVEL_FILE: &str = "levels/simulation/flat.json";
+/// Ticks to run after loading the level, so the player gains control.
+const SETTLE_TICKS: u64 = 30;
+/// Position above the spikes.
+const SPIKES_POS: (f32, f32) = (128.0, 120.0);
+
+const JUMP: SimInput = SimInput::Action("player_jump");
+const MOVE_RIGHT: SimInput = SimInput::Axis("player_x_keyboard", AxisSide::Pos);
+const DASH_RIGHT: SimInput = SimInput::Action("player_dash_right");
+const DASH_TRIGGER: SimInput = SimInput::Action("player_dash_trigger");
+
+/// The player jumps off the floor and lands on it again.
+#[test]
+fn jump() -> Result<(), String> {
+    let mut sim = start()?;
+    let (_, ground_y) = player_position(&sim)?;
+
+    sim.run_until(30, &[JUMP], |sim| {
+        position_y(sim)
+            .map(|y| y > ground_y + 16.0)
+            .unwrap_or(false)
+    })
+    .map_err(|err| format!("Player didn't jump: {}", err))?;
+
+    sim.run_until(180, &[], |sim| {
+        position_y(sim)
+            .map(|y| (y - ground_y).abs() < 1.0)
+            .unwrap_or(false)
+    })
+    .map_err(|err| format!("Player didn't land: {}", err))
+}
+
+/// The player dashes to the right in the air, after getting the dash item.
+#[test]
+fn dash() -> Result<(), String> {
+    let mut sim = start()?;
+    sim.command("give Dash")?;
+
+    // Dashing is only possible in the air.
+    sim.run(10, &[JUMP])?;
+    sim.tick(&[DASH_RIGHT])?;
+    sim.run_until(5, &[DASH_RIGHT, DASH_TRIGGER], |sim| {
+        sim.player()
+            .map(|player| player.items_data.dash.is_dashing)
+            .unwrap_or(false)
+    })
+    .map_err(|err| format!("Player didn't dash: {}", err))?;
+
+    let (velocity_x, _) = sim.player_velocity().ok_or_else(no_player)?;
+    if velocity_x > 0.0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Player should dash to the right, but has x velocity {}",
+            velocity_x
+        ))
+    }
+}
+
+/// The player takes damage when falling into spikes.
+#[test]
+fn damage() -> Result<(), String> {
+    let mut sim = start()?;
+    let health = sim.player().ok_or_else(no_player)?.health;
+
+    sim.command(&format!("tp {} {}", SPIKES_POS.0, SPIKES_POS.1))?;
+    sim.run_until(60, &[], |sim| {
+        sim.player()
+            .map(|player| player.health < health)
+            .unwrap_or(false)
+    })
+    .map_err(|err| format!("Player didn't take damage: {}", err))
+}
+
+/// The player walks to the right into the goal, and beats the level.
+#[test]
+fn level_complete() -> Result<(), String> {
+    let mut sim = start()?;
+    sim.run_until(600, &[MOVE_RIGHT], Simulation::has_completed_level)
+        .map_err(|err| format!("Player didn't reach the goal: {}", err))?;
+
+    let in_control = sim.player().ok_or_else(no_player)?.in_control;
+    if in_control {
+        Err("Player should lose control after beating the level".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Creates a simulation with the level loaded,
+/// and waits until the player stands on the floor.
+fn start<'a, 'b>() -> Result<Simulation<'a, 'b>, String> {
+    let mut sim = Simulation::new()?;
+    sim.load_level(CampaignType::Normal, resource(LEVEL_FILE))?;
+    sim.run(SETTLE_TICKS, &[])?;
+    if sim.player().ok_or_else(no_player)?.in_control {
+        Ok(sim)
+    } else {
+        Err("Player should be in control after loading the level".to_string())
+    }
+}
+
+fn player_position(sim: &Simulation) -> Result<(f32, f32), String> {
+    sim.player_position().ok_or_else(no_player)
+}
+
+fn position_y(sim: &Simulation) -> Option<f32> {
+    sim.player_position().map(|(_, y)| y)
+}
+
+fn no_player() -> String {
+    "There is no player".to_string()
+}
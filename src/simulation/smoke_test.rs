@@ -0,0 +1,170 @@
+//! Smoke tests, which play through every level of every campaign
+//! in the headless `Simulation`:
+//! idles for a while, checks that the player exists and that the `LoaderSystem`
+//! loads and unloads entities, then noclips into the goal to beat the level.
+
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+
+use amethyst::core::transform::Transform;
+use amethyst::ecs::{Entity, Join};
+
+use super::{SimInput, Simulation};
+use crate::components::prelude::*;
+use crate::resource_helpers::resource;
+use crate::settings::SettingsLevelManagerCampaign;
+use crate::settings_loader::load_settings;
+use crate::states::helpers::CampaignType;
+
+const IDLE_TICKS: u64 = 300;
+const GOAL_TICKS: u64 = 60;
+/// Ticks to wait for the camera (the loader) to catch up with the player.
+const LOADER_TICKS: u64 = 120;
+const NOCLIP_TOGGLE: SimInput = SimInput::Action("noclip_toggle");
+
+#[test]
+fn normal_campaign_levels() -> Result<(), String> {
+    let campaign_settings = load_settings()?.level_manager.normal;
+    smoke_test_campaign(CampaignType::Normal, campaign_settings)
+}
+
+#[test]
+fn bonus_a_campaign_levels() -> Result<(), String> {
+    let campaign_settings = load_settings()?.level_manager.bonus_a;
+    smoke_test_campaign(CampaignType::BonusA, campaign_settings)
+}
+
+#[test]
+fn bonus_b_campaign_levels() -> Result<(), String> {
+    let campaign_settings = load_settings()?.level_manager.bonus_b;
+    smoke_test_campaign(CampaignType::BonusB, campaign_settings)
+}
+
+/// Runs the smoke test for every level of the campaign,
+/// and returns an error listing every level, which failed.
+fn smoke_test_campaign(
+    campaign: CampaignType,
+    campaign_settings: SettingsLevelManagerCampaign,
+) -> Result<(), String> {
+    let mut failures = Vec::new();
+    for level_name in &campaign_settings.level_names {
+        let level_path =
+            format!("{}/{}", campaign_settings.levels_dir, level_name);
+        // Keep testing the other levels, if one of them panics.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            smoke_test_level(campaign.clone(), resource(&level_path))
+        }))
+        .unwrap_or_else(|_| Err("Panicked".to_string()));
+        if let Err(err) = result {
+            failures.push(format!("{}: {}", level_path, err));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} levels failed:\n  {}",
+            failures.len(),
+            campaign_settings.level_names.len(),
+            failures.join("\n  ")
+        ))
+    }
+}
+
+fn smoke_test_level(
+    campaign: CampaignType,
+    level_filepath: String,
+) -> Result<(), String> {
+    let mut sim = Simulation::new()?;
+    sim.load_level(campaign, level_filepath)?;
+    sim.run(IDLE_TICKS, &[])?;
+
+    let start_pos = sim
+        .player_position()
+        .ok_or_else(|| "There is no player".to_string())?;
+    let loaded_at_start = loaded_entities(&sim);
+    if loaded_at_start.is_empty() {
+        return Err("No entities were loaded".to_string());
+    }
+
+    // Noclip is toggled when the button is released.
+    sim.tick(&[NOCLIP_TOGGLE])?;
+    sim.tick(&[])?;
+    if !has_noclip(&sim) {
+        return Err("Couldn't enable noclip".to_string());
+    }
+
+    let goal_pos =
+        goal_position(&sim).ok_or_else(|| "There is no goal".to_string())?;
+    sim.command(&format!("tp {} {}", goal_pos.0, goal_pos.1))?;
+    sim.run_until(GOAL_TICKS, &[], Simulation::has_completed_level)
+        .map_err(|err| format!("Didn't reach the goal: {}", err))?;
+
+    // Only check for (un)loaded entities, if the goal
+    // is out of the camera's view from the player's start.
+    if let Some(camera_size) = camera_size(&sim) {
+        if (goal_pos.0 - start_pos.0).abs() > camera_size.0
+            || (goal_pos.1 - start_pos.1).abs() > camera_size.1
+        {
+            sim.run_until(LOADER_TICKS, &[], |sim| {
+                let loaded = loaded_entities(sim);
+                loaded.difference(&loaded_at_start).next().is_some()
+                    && loaded_at_start.difference(&loaded).next().is_some()
+            })
+            .map_err(|err| {
+                format!("Entities weren't loaded and unloaded: {}", err)
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn loaded_entities(sim: &Simulation) -> HashSet<Entity> {
+    let world = sim.world();
+    (
+        &*world.entities(),
+        &world.read_storage::<Loadable>(),
+        &world.read_storage::<Loaded>(),
+    )
+        .join()
+        .map(|(entity, _, _)| entity)
+        .collect()
+}
+
+fn has_noclip(sim: &Simulation) -> bool {
+    let world = sim.world();
+    (
+        &world.read_storage::<Player>(),
+        &world.read_storage::<Noclip>(),
+    )
+        .join()
+        .next()
+        .is_some()
+}
+
+fn goal_position(sim: &Simulation) -> Option<(f32, f32)> {
+    let world = sim.world();
+    (
+        &world.read_storage::<Goal>(),
+        &world.read_storage::<Transform>(),
+    )
+        .join()
+        .next()
+        .map(|(_, transform)| {
+            let pos = transform.translation();
+            (pos.x, pos.y)
+        })
+}
+
+fn camera_size(sim: &Simulation) -> Option<(f32, f32)> {
+    let world = sim.world();
+    (
+        &world.read_storage::<Camera>(),
+        &world.read_storage::<Size>(),
+    )
+        .join()
+        .next()
+        .map(|(_, size)| (size.w, size.h))
+}
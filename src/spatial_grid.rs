@@ -0,0 +1,150 @@
+//! A spatial hash of rectangles, for finding the items near an area,
+//! without testing every item.
+//! The `SpatialGridSystem` maintains the `EntityGrid` resource from every entity's
+//! `Transform` and `Size`; the `LoaderSystem`, `HarmfulSystem`
+//! and `GridCollisionSystem` query it.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use amethyst::ecs::Entity;
+use deathframe::geo::Rect;
+
+/// Cell size of a new grid; changed with `set_cell_size`.
+const DEFAULT_CELL_SIZE: f32 = 256.0;
+
+/// The cells covered by a rect: the min and max column and row, inclusive.
+#[derive(Clone, Copy, PartialEq)]
+struct CellRange {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    cells:     HashMap<(i32, i32), Vec<T>>,
+    items:     HashMap<T, CellRange>,
+}
+
+/// The spatial grid of all entities with a `Transform`, as a resource.
+pub type EntityGrid = SpatialGrid<Entity>;
+
+impl<T> SpatialGrid<T>
+where
+    T: Copy + Eq + Hash,
+{
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1.0),
+            cells:     HashMap::new(),
+            items:     HashMap::new(),
+        }
+    }
+
+    /// Changes the cell size. Removes all items, and returns `true`, if it changed.
+    pub fn set_cell_size(&mut self, cell_size: f32) -> bool {
+        let cell_size = cell_size.max(1.0);
+        if (cell_size - self.cell_size).abs() > std::f32::EPSILON {
+            self.cell_size = cell_size;
+            self.clear();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.items.clear();
+    }
+
+    /// Inserts the item with the given rect, or moves it there
+    /// if it was already inserted. Does nothing if it didn't change cells.
+    pub fn update(&mut self, item: T, rect: &Rect) {
+        let range = self.cell_range(rect);
+        match self.items.get(&item).cloned() {
+            Some(old_range) if old_range == range => return,
+            Some(old_range) => self.remove_from_cells(item, old_range),
+            None => (),
+        }
+        for cell in cells_in(range) {
+            self.cells.entry(cell).or_insert_with(Vec::new).push(item);
+        }
+        self.items.insert(item, range);
+    }
+
+    pub fn remove(&mut self, item: T) {
+        if let Some(range) = self.items.remove(&item) {
+            self.remove_from_cells(item, range);
+        }
+    }
+
+    /// Returns the items in the cells the rect covers, each only once.
+    /// These may not actually overlap the rect; callers do the exact checks.
+    pub fn query(&self, rect: &Rect) -> Vec<T> {
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        for cell in cells_in(self.cell_range(rect)) {
+            if let Some(items) = self.cells.get(&cell) {
+                for item in items {
+                    if seen.insert(*item) {
+                        found.push(*item);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn remove_from_cells(&mut self, item: T, range: CellRange) {
+        for cell in cells_in(range) {
+            let is_empty = if let Some(items) = self.cells.get_mut(&cell) {
+                items.retain(|other| *other != item);
+                items.is_empty()
+            } else {
+                false
+            };
+            if is_empty {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn cell_range(&self, rect: &Rect) -> CellRange {
+        CellRange {
+            min: (
+                (rect.left / self.cell_size).floor() as i32,
+                (rect.bottom / self.cell_size).floor() as i32,
+            ),
+            max: (
+                (rect.right / self.cell_size).floor() as i32,
+                (rect.top / self.cell_size).floor() as i32,
+            ),
+        }
+    }
+}
+
+impl<T> Default for SpatialGrid<T>
+where
+    T: Copy + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+/// Returns the rect of the given center position and size.
+pub fn rect_around(pos: (f32, f32), size: (f32, f32)) -> Rect {
+    Rect {
+        top:    pos.1 + size.1 * 0.5,
+        bottom: pos.1 - size.1 * 0.5,
+        left:   pos.0 - size.0 * 0.5,
+        right:  pos.0 + size.0 * 0.5,
+    }
+}
+
+fn cells_in(range: CellRange) -> impl Iterator<Item = (i32, i32)> {
+    (range.min.1 ..= range.max.1).flat_map(move |row| {
+        (range.min.0 ..= range.max.0).map(move |col| (col, row))
+    })
+}
@@ -13,7 +13,6 @@ impl BonusSelectMenu {
         &self,
         data: &StateData<CustomGameData<CustomData>>,
     ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
-        let settings = data.world.settings();
         let input_manager = data.world.input_manager();
 
         // Back to main menu
@@ -21,7 +20,7 @@ impl BonusSelectMenu {
             Some(Trans::Pop)
         // Start bonus_a
         } else if input_manager.is_up("accept") {
-            Some(self.trans_for_campaign(CampaignType::BonusA, &settings))
+            Some(self.trans_for_campaign(CampaignType::BonusA, &data.world))
         } else {
             None
         }
@@ -34,11 +33,11 @@ impl BonusSelectMenu {
     fn trans_for_campaign<'a, 'b>(
         &self,
         campaign: CampaignType,
-        settings: &Settings,
+        world: &World,
     ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
         // Only start ContinueOrNewGameMenu if a savefile already exists,
         // otherwise start the game directly.
-        if savefile_exists_for(&campaign, settings) {
+        if savefile_exists_for(world, &campaign) {
             Trans::Switch(Box::new(ContinueOrNewGameMenu::new(campaign)))
         } else {
             Trans::Switch(Box::new(
@@ -112,7 +111,6 @@ impl Menu for BonusSelectMenu {
         data: &mut StateData<CustomGameData<CustomData>>,
         event_name: String,
     ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
-        let settings = data.world.settings();
         let mut start_with_campaign = None;
 
         match event_name.as_ref() {
@@ -127,7 +125,7 @@ impl Menu for BonusSelectMenu {
         };
 
         if let Some(campaign) = start_with_campaign {
-            Some(self.trans_for_campaign(campaign, &settings))
+            Some(self.trans_for_campaign(campaign, &data.world))
         } else {
             None
         }
@@ -153,17 +151,3 @@ impl Menu for BonusSelectMenu {
         &mut self.ui_reader_id
     }
 }
-
-fn savefile_exists_for(campaign: &CampaignType, settings: &Settings) -> bool {
-    use amethyst::utils::application_root_dir;
-    use std::path::Path;
-
-    let savefile_relative = match campaign {
-        CampaignType::Normal => &settings.level_manager.normal.savefile_path,
-        CampaignType::BonusA => &settings.level_manager.bonus_a.savefile_path,
-        CampaignType::BonusB => &settings.level_manager.bonus_b.savefile_path,
-    };
-    let savefile_path =
-        format!("{}/{}", application_root_dir(), savefile_relative);
-    Path::new(&savefile_path).exists()
-}
@@ -0,0 +1,302 @@
+use amethyst::input::{Button, InputHandler};
+
+use super::state_prelude::*;
+use crate::user_bindings::button_name;
+
+const TITLE: &str = "Controls";
+const FOOTER: &str = "Press accept on an action, then press the new key or \
+                      controller button.";
+const FOOTER_CAPTURING: &str =
+    "Press a key or controller button ... (Escape to cancel)";
+const CANCEL_KEY: VirtualKeyCode = VirtualKeyCode::Escape;
+
+/// The rows after the rebindables' rows.
+#[derive(Clone, Copy, PartialEq)]
+enum ExtraRow {
+    ResetToDefaults,
+    Back,
+}
+
+const EXTRA_ROWS: [ExtraRow; 2] = [ExtraRow::ResetToDefaults, ExtraRow::Back];
+
+/// Lists all rebindable actions with their keyboard and controller buttons,
+/// and lets the player bind a new button to the selected action.
+/// Changes are applied immediately and are saved to the user bindings file.
+/// The bindings are loaded in `on_start`; if they can't be loaded,
+/// the menu shows the error and no buttons, and nothing can be rebound.
+pub struct ControlsMenu {
+    menu:          TextMenu,
+    config:        Option<BindingsConfig>,
+    user_bindings: UserBindings,
+    /// `Some` while waiting for the player to press a button for
+    /// the rebindable at the given index.
+    capturing:     Option<usize>,
+    /// Buttons which were already held down when capturing started;
+    /// these are ignored until they are released.
+    held_buttons:  Vec<Button>,
+    /// After capturing a button, input is ignored until it was released,
+    /// so the captured button doesn't trigger any menu actions.
+    wait_release:  Option<Button>,
+}
+
+impl Default for ControlsMenu {
+    fn default() -> Self {
+        Self {
+            menu:          TextMenu::default(),
+            config:        None,
+            user_bindings: UserBindings::default(),
+            capturing:     None,
+            held_buttons:  Vec::new(),
+            wait_release:  None,
+        }
+    }
+}
+
+impl ControlsMenu {
+    fn handle_keys<'a, 'b>(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
+        let (navigated, accepted, declined) = {
+            let input_manager = data.world.input_manager();
+            (
+                self.menu.handle_navigation(&input_manager),
+                input_manager.is_up("accept"),
+                input_manager.is_up("decline"),
+            )
+        };
+
+        if declined {
+            return Some(Trans::Pop);
+        }
+
+        if navigated {
+            self.update_menu(data);
+        }
+
+        if accepted {
+            let selected = self.menu.selected();
+            match selected.checked_sub(REBINDABLES.len()) {
+                None if self.config.is_none() => (),
+                None => {
+                    self.held_buttons = buttons_down(data);
+                    self.capturing = Some(selected);
+                    self.menu.update_footer(&mut data.world, FOOTER_CAPTURING);
+                    self.update_menu(data);
+                }
+                Some(extra) => match EXTRA_ROWS[extra] {
+                    ExtraRow::ResetToDefaults => {
+                        self.user_bindings = UserBindings::default();
+                        self.apply_bindings(data);
+                        self.menu.update_footer(
+                            &mut data.world,
+                            "Reset all controls to their defaults.",
+                        );
+                    }
+                    ExtraRow::Back => return Some(Trans::Pop),
+                },
+            }
+        }
+
+        None
+    }
+
+    /// Binds the first newly pressed button to the rebindable
+    /// which is being captured.
+    fn capture(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+        index: usize,
+    ) {
+        let down = buttons_down(data);
+        self.held_buttons.retain(|button| down.contains(button));
+        let pressed = match down.into_iter().find(|button| {
+            !self.held_buttons.contains(button)
+                && ButtonKind::of(button).is_some()
+        }) {
+            Some(button) => button,
+            None => return,
+        };
+
+        self.capturing = None;
+        self.wait_release = Some(pressed.clone());
+
+        let rebindable = &REBINDABLES[index];
+        let footer = if pressed == Button::Key(CANCEL_KEY) {
+            FOOTER.to_string()
+        } else if let Some(other) = self
+            .config
+            .as_ref()
+            .and_then(|config| config.conflict_for(rebindable, &pressed))
+        {
+            format!(
+                "{} is already bound to {}.",
+                button_name(&pressed),
+                other.label
+            )
+        } else {
+            self.user_bindings.set(rebindable, pressed.clone());
+            self.apply_bindings(data);
+            format!("Bound {} to {}.", rebindable.label, button_name(&pressed))
+        };
+        self.menu.update_footer(&mut data.world, footer);
+        self.update_menu(data);
+    }
+
+    /// Rebuilds the bindings from the defaults and the user's overrides,
+    /// and hands them to the `InputHandler`.
+    fn apply_bindings(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        match BindingsConfig::load_with_user_bindings(&self.user_bindings)
+            .and_then(|config| {
+                config.to_bindings().map(|bindings| (config, bindings))
+            }) {
+            Ok((config, bindings)) => {
+                self.config = Some(config);
+                data.world
+                    .write_resource::<InputHandler<String, String>>()
+                    .bindings = bindings;
+            }
+            Err(err) => error!("Couldn't apply bindings: {}", err),
+        }
+        self.update_menu(data);
+    }
+
+    fn update_menu(&self, data: &mut StateData<CustomGameData<CustomData>>) {
+        let rows = self.row_texts();
+        self.menu.update(&mut data.world, &rows);
+    }
+
+    fn row_texts(&self) -> Vec<String> {
+        REBINDABLES
+            .iter()
+            .enumerate()
+            .map(|(i, rebindable)| {
+                if self.capturing == Some(i) {
+                    format!("{:<20}...", rebindable.label)
+                } else {
+                    format!(
+                        "{:<20}{:<24}{}",
+                        rebindable.label,
+                        self.buttons_text(rebindable, ButtonKind::Keyboard),
+                        self.buttons_text(rebindable, ButtonKind::Controller)
+                    )
+                }
+            })
+            .chain(EXTRA_ROWS.iter().map(|row| match row {
+                ExtraRow::ResetToDefaults => "Reset to Defaults".to_string(),
+                ExtraRow::Back => "Back".to_string(),
+            }))
+            .collect()
+    }
+
+    fn buttons_text(
+        &self,
+        rebindable: &Rebindable,
+        kind: ButtonKind,
+    ) -> String {
+        let buttons = self
+            .config
+            .as_ref()
+            .map(|config| config.buttons_for(rebindable, kind))
+            .unwrap_or_default();
+        if buttons.is_empty() {
+            "-".to_string()
+        } else {
+            buttons
+                .iter()
+                .map(button_name)
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    }
+
+    fn save_user_bindings(&self) {
+        if let Err(err) = self.user_bindings.save() {
+            error!("Couldn't save user bindings: {}", err);
+        }
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for ControlsMenu
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        let settings = data.world.settings().options_menu;
+        self.user_bindings = UserBindings::load();
+        let footer = match BindingsConfig::load_with_user_bindings(
+            &self.user_bindings,
+        ) {
+            Ok(config) => {
+                self.config = Some(config);
+                FOOTER.to_string()
+            }
+            Err(err) => {
+                error!("Couldn't load bindings: {}", err);
+                format!("Couldn't load the controls: {}", err)
+            }
+        };
+        let rows = self.row_texts();
+        self.menu.set_footer(footer);
+        self.menu.set_row_size(
+            settings.controls_font_size,
+            settings.controls_row_height,
+        );
+        self.menu.create(&mut data.world, TITLE, &rows);
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.menu.delete(&mut data.world);
+        self.save_user_bindings();
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        match &event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    Trans::Quit
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        mut data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        data.data.update(&data.world, "controls_menu").unwrap();
+
+        if let Some(button) = self.wait_release.clone() {
+            if !buttons_down(&data).contains(&button) {
+                self.wait_release = None;
+            }
+            return Trans::None;
+        }
+
+        if let Some(index) = self.capturing {
+            self.capture(&mut data, index);
+            return Trans::None;
+        }
+
+        if let Some(trans) = self.handle_keys(&mut data) {
+            return trans;
+        }
+        Trans::None
+    }
+}
+
+fn buttons_down(data: &StateData<CustomGameData<CustomData>>) -> Vec<Button> {
+    data.world
+        .read_resource::<InputHandler<String, String>>()
+        .buttons_that_are_down()
+        .collect()
+}
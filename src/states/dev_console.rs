@@ -0,0 +1,230 @@
+use amethyst::winit::{ElementState, Event, KeyboardInput, WindowEvent};
+
+use super::state_prelude::*;
+
+const OUTPUT_ID_PREFIX: &str = "dev_console_output";
+const INPUT_ID: &str = "dev_console_input";
+const PROMPT: &str = "> ";
+const CURSOR: &str = "_";
+/// The key which opens the console; its character is ignored in the input.
+const TOGGLE_CHAR: char = '`';
+const DEV_CONSOLE_Z: f32 = 20.0;
+const PADDING: f32 = 8.0;
+
+/// The developer console (development mode only).
+/// Type a command and press `Return` to run it; see the `help` command.
+/// Commands are registered in the `DevCommands` resource.
+#[derive(Default)]
+pub struct DevConsole {
+    input:           String,
+    /// Index into the command history, while browsing it with `Up`/`Down`.
+    history_index:   Option<usize>,
+    input_entity:    Option<Entity>,
+    output_entities: Vec<Entity>,
+}
+
+impl DevConsole {
+    fn create_ui(&mut self, world: &mut World) {
+        let settings = world.settings().dev_console;
+        let font = get_font(&settings.font_file, world);
+
+        let mut create_line = |id: String, i: usize, color: [f32; 4]| {
+            let mut ui_text = UiText::new(
+                font.clone(),
+                String::new(),
+                color,
+                settings.font_size,
+            );
+            ui_text.align = AmethystAnchor::MiddleLeft;
+            world
+                .create_entity()
+                .with(new_ui_transform(
+                    id,
+                    AmethystAnchor::TopLeft,
+                    (
+                        settings.width * 0.5 + PADDING,
+                        -(i as f32 + 0.5) * settings.line_height - PADDING,
+                        DEV_CONSOLE_Z,
+                        settings.width,
+                        settings.line_height,
+                        0,
+                    ),
+                ))
+                .with(ui_text)
+                .build()
+        };
+
+        self.output_entities = (0 .. settings.output_lines)
+            .map(|i| {
+                create_line(
+                    format!("{}_{}", OUTPUT_ID_PREFIX, i),
+                    i,
+                    settings.font_color,
+                )
+            })
+            .collect();
+        self.input_entity = Some(create_line(
+            INPUT_ID.to_string(),
+            settings.output_lines,
+            settings.input_font_color,
+        ));
+
+        self.update_ui(world);
+    }
+
+    fn delete_ui(&mut self, world: &mut World) {
+        if let Some(entity) = self.input_entity.take() {
+            world
+                .delete_entity(entity)
+                .expect("Should delete dev console input entity");
+        }
+        world
+            .delete_entities(&self.output_entities)
+            .expect("Should delete dev console output entities");
+        self.output_entities.clear();
+    }
+
+    fn update_ui(&self, world: &mut World) {
+        let log = world.read_resource::<DevConsoleLog>();
+        let mut ui_texts = world.write_storage::<UiText>();
+
+        // Show the last lines of output, aligned to the bottom.
+        let skip = self.output_entities.len().saturating_sub(log.output.len());
+        let visible = log
+            .output
+            .iter()
+            .skip(log.output.len().saturating_sub(self.output_entities.len()));
+        let texts = (0 .. skip).map(|_| String::new()).chain(visible.cloned());
+        for (entity, text) in self.output_entities.iter().zip(texts) {
+            if let Some(ui_text) = ui_texts.get_mut(*entity) {
+                ui_text.text = text;
+            }
+        }
+
+        if let Some(ui_text) = self
+            .input_entity
+            .and_then(|entity| ui_texts.get_mut(entity))
+        {
+            ui_text.text = format!("{}{}{}", PROMPT, self.input, CURSOR);
+        }
+    }
+
+    /// Runs the current input as a command, and prints its output.
+    fn submit(&mut self, world: &mut World) {
+        let line = self.input.trim().to_string();
+        self.input.clear();
+        self.history_index = None;
+        if line.is_empty() {
+            return;
+        }
+
+        let result = run_command(world, &line);
+
+        let mut log = world.write_resource::<DevConsoleLog>();
+        log.output.push(format!("{}{}", PROMPT, line));
+        match result {
+            Ok(output) => {
+                log.output.extend(output.lines().map(ToString::to_string))
+            }
+            Err(err) => log.output.push(format!("ERROR: {}", err)),
+        }
+        log.history.push(line);
+    }
+
+    /// Replaces the input with an older (`Up`) or newer (`Down`) command
+    /// from the history.
+    fn browse_history(&mut self, world: &World, older: bool) {
+        let log = world.read_resource::<DevConsoleLog>();
+        let len = log.history.len();
+        if len == 0 {
+            return;
+        }
+        self.history_index = match (self.history_index, older) {
+            (None, true) => Some(len - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < len => Some(index + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_index
+            .map(|index| log.history[index].clone())
+            .unwrap_or_default();
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for DevConsole
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.create_ui(&mut data.world);
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.delete_ui(&mut data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::ReceivedCharacter(c),
+                    ..
+                } => {
+                    if !c.is_control() && *c != TOGGLE_CHAR {
+                        self.input.push(*c);
+                    }
+                }
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: ElementState::Pressed,
+                                    virtual_keycode: Some(key),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => match key {
+                    VirtualKeyCode::Escape => return Trans::Pop,
+                    VirtualKeyCode::Return => self.submit(data.world),
+                    VirtualKeyCode::Back => {
+                        self.input.pop();
+                    }
+                    VirtualKeyCode::Up => self.browse_history(data.world, true),
+                    VirtualKeyCode::Down => {
+                        self.browse_history(data.world, false)
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+
+            self.update_ui(data.world);
+        }
+
+        Trans::None
+    }
+
+    fn update(
+        &mut self,
+        data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        data.data.update(&data.world, "dev_console").unwrap();
+
+        if data.world.input_manager().is_up("dev_console") {
+            return Trans::Pop;
+        }
+        Trans::None
+    }
+}
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Join, ReadStorage};
+
+use super::state_prelude::*;
+use crate::solid_tag::SolidTag;
+use crate::systems::SidesTouching;
+
+const HUD_ID_PREFIX: &str = "frame_step_hud";
+const HUD_LINES: usize = 6;
+const FRAME_STEP_Z: f32 = 20.0;
+const PADDING: f32 = 8.0;
+
+/// The frame stepping debugger (development mode only).
+/// Freezes the game, and runs the `ingame` dispatcher exactly once
+/// (with a fixed delta time of one frame) for every `frame_step_advance` press.
+/// Displays the player's state in a HUD.
+#[derive(Default)]
+pub struct FrameStep {
+    hud_entities:   Vec<Entity>,
+    step_requested: bool,
+    steps:          u64,
+}
+
+impl FrameStep {
+    fn create_hud(&mut self, world: &mut World) {
+        let settings = world.settings().frame_step;
+        let font = get_font(&settings.font_file, world);
+
+        self.hud_entities = (0 .. HUD_LINES)
+            .map(|i| {
+                let mut ui_text = UiText::new(
+                    font.clone(),
+                    String::new(),
+                    settings.font_color,
+                    settings.font_size,
+                );
+                ui_text.align = AmethystAnchor::MiddleLeft;
+                world
+                    .create_entity()
+                    .with(new_ui_transform(
+                        format!("{}_{}", HUD_ID_PREFIX, i),
+                        AmethystAnchor::TopLeft,
+                        (
+                            settings.width * 0.5 + PADDING,
+                            -(i as f32 + 0.5) * settings.line_height - PADDING,
+                            FRAME_STEP_Z,
+                            settings.width,
+                            settings.line_height,
+                            0,
+                        ),
+                    ))
+                    .with(ui_text)
+                    .build()
+            })
+            .collect();
+
+        self.update_hud(world);
+    }
+
+    fn delete_hud(&mut self, world: &mut World) {
+        world
+            .delete_entities(&self.hud_entities)
+            .expect("Should delete frame step HUD entities");
+        self.hud_entities.clear();
+    }
+
+    fn update_hud(&self, world: &mut World) {
+        let mut lines = vec![format!("FRAME STEP - step {}", self.steps)];
+        lines.extend(player_state_lines(world));
+
+        let mut ui_texts = world.write_storage::<UiText>();
+        for (i, entity) in self.hud_entities.iter().enumerate() {
+            if let Some(ui_text) = ui_texts.get_mut(*entity) {
+                ui_text.text = lines.get(i).cloned().unwrap_or_default();
+            }
+        }
+    }
+
+    /// Runs the `ingame` dispatcher once, with a delta time of exactly one frame.
+    fn step(&mut self, data: &StateData<CustomGameData<CustomData>>) {
+        data.world
+            .write_resource::<Time>()
+            .set_delta_time(Duration::from_secs(1) / crate::FPS);
+        data.data.update(&data.world, "ingame").unwrap();
+        self.steps += 1;
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for FrameStep
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        pause_game_timers(&mut data.world);
+        self.create_hud(&mut data.world);
+        info!("frame step: ENABLED");
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.delete_hud(&mut data.world);
+        resume_game_timers(&mut data.world);
+        info!("frame step: DISABLED");
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        match &event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    Trans::Quit
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        mut data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        // The core dispatcher runs with either dispatcher, so the input
+        // is checked after updating, and the step is run in the next frame.
+        if self.step_requested {
+            self.step_requested = false;
+            self.step(&data);
+            self.update_hud(&mut data.world);
+        } else {
+            data.data.update(&data.world, "frame_step").unwrap();
+        }
+
+        let input_manager = data.world.input_manager();
+        if input_manager.is_down("frame_step_toggle") {
+            return Trans::Pop;
+        }
+        self.step_requested = input_manager.is_down("frame_step_advance");
+
+        Trans::None
+    }
+}
+
+fn player_state_lines(world: &mut World) -> Vec<String> {
+    world.exec(
+        |(
+            entities,
+            players,
+            velocities,
+            collisions,
+            solids,
+            animations_containers,
+        ): (
+            Entities,
+            ReadStorage<Player>,
+            ReadStorage<Velocity>,
+            ReadStorage<Collision>,
+            ReadStorage<Solid<SolidTag>>,
+            ReadStorage<AnimationsContainer>,
+        )| {
+            let (entity, player) = match (&entities, &players).join().next() {
+                Some(player) => player,
+                None => return vec!["There is no player".to_string()],
+            };
+            let items_data = &player.items_data;
+            let mut lines = Vec::new();
+
+            if let Some(velocity) = velocities.get(entity) {
+                lines.push(format!(
+                    "velocity: x {:.2} y {:.2}",
+                    velocity.x, velocity.y
+                ));
+            }
+
+            if let (Some(collision), Some(solid)) =
+                (collisions.get(entity), solids.get(entity))
+            {
+                let sides_touching = SidesTouching::new(
+                    &entities,
+                    collision,
+                    solid,
+                    &collisions,
+                    &solids,
+                );
+                lines.push(format!(
+                    "touching: top {} bottom {} left {} right {}",
+                    sides_touching.is_touching_top,
+                    sides_touching.is_touching_bottom,
+                    sides_touching.is_touching_left,
+                    sides_touching.is_touching_right,
+                ));
+            }
+
+            lines.push(format!(
+                "extra jumps: used {}/{} wall jump {}",
+                items_data.extra_jump.used_extra_jumps,
+                items_data.extra_jump.extra_jumps,
+                items_data.wall_jump.can_wall_jump,
+            ));
+            lines.push(format!(
+                "dashes: used {}/{} dashing {}",
+                items_data.dash.used_dashes,
+                items_data.dash.dashes,
+                items_data.dash.is_dashing,
+            ));
+
+            let animation = animations_containers
+                .get(entity)
+                .and_then(|container| container.current.as_ref())
+                .map(|(name, _)| name.as_str())
+                .unwrap_or("none");
+            lines.push(format!("animation: {}", animation));
+
+            lines
+        },
+    )
+}
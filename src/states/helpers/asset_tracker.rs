@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use amethyst::assets::{AssetStorage, Progress, ProgressCounter, Tracker};
+use amethyst::audio::{Source, SourceHandle};
+use amethyst::ecs::World;
+use amethyst::renderer::{
+    SpriteSheet,
+    SpriteSheetHandle,
+    Texture,
+    TextureHandle,
+};
+
+/// An asset, which may still be loading.
+pub enum TrackedAsset {
+    /// Finished once its texture has loaded, too.
+    SpriteSheet(SpriteSheetHandle),
+    Texture(TextureHandle),
+    Song(SourceHandle),
+}
+
+/// Tracks the loading of assets with a `ProgressCounter`.
+/// The assets are loaded through deathframe's handles
+/// (`SpriteSheetHandles`, `TextureHandles` and `AudioHandles`),
+/// which don't take a `ProgressCounter`;
+/// so each asset's tracker is completed once it appears in its `AssetStorage`.
+pub struct AssetTracker {
+    progress:    ProgressCounter,
+    pending:     Vec<(TrackedAsset, Box<dyn Tracker>)>,
+    tracked_ids: HashSet<(u8, u32)>,
+}
+
+impl AssetTracker {
+    pub fn new() -> Self {
+        Self {
+            progress:    ProgressCounter::new(),
+            pending:     Vec::new(),
+            tracked_ids: HashSet::new(),
+        }
+    }
+
+    /// Tracks the asset, unless its handle is already tracked.
+    pub fn track(&mut self, asset: TrackedAsset) {
+        let id = match &asset {
+            TrackedAsset::SpriteSheet(handle) => (0, handle.id()),
+            TrackedAsset::Texture(handle) => (1, handle.id()),
+            TrackedAsset::Song(handle) => (2, handle.id()),
+        };
+        if self.tracked_ids.insert(id) {
+            (&mut self.progress).add_assets(1);
+            let tracker: Box<dyn Tracker> =
+                Box::new((&mut self.progress).create_tracker());
+            self.pending.push((asset, tracker));
+        }
+    }
+
+    /// Completes the trackers of all assets, which have finished loading.
+    pub fn update(&mut self, world: &World) {
+        let sprite_sheets = world.read_resource::<AssetStorage<SpriteSheet>>();
+        let textures = world.read_resource::<AssetStorage<Texture>>();
+        let sources = world.read_resource::<AssetStorage<Source>>();
+
+        let (loaded, pending): (Vec<_>, Vec<_>) =
+            self.pending.drain(..).partition(|(asset, _)| match asset {
+                TrackedAsset::SpriteSheet(handle) => {
+                    sprite_sheets.get(handle).map_or(false, |sheet| {
+                        textures.get(&sheet.texture).is_some()
+                    })
+                }
+                TrackedAsset::Texture(handle) => textures.get(handle).is_some(),
+                TrackedAsset::Song(handle) => sources.get(handle).is_some(),
+            });
+        self.pending = pending;
+        for (_, tracker) in loaded {
+            tracker.success();
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress.is_complete()
+    }
+
+    /// Returns the amount of loaded and total tracked assets.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.progress.num_finished(), self.progress.num_assets())
+    }
+}
+
+impl Default for AssetTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
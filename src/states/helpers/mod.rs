@@ -1,18 +1,40 @@
+mod asset_tracker;
 mod menu;
 mod resources;
 mod stats;
+mod text_menu;
 
+pub use asset_tracker::*;
 pub use menu::*;
 pub use resources::*;
 pub use stats::*;
+pub use text_menu::*;
 
+use std::path::Path;
+
+use amethyst::ecs::{Join, World, WriteStorage};
 use amethyst::ui::{Anchor as AmethystAnchor, UiTransform};
+use amethyst::utils::application_root_dir;
+use deathframe::handlers::{SpriteSheetHandles, TextureHandles};
+use deathframe::input_manager::InputManager;
+
+use crate::analytics::PlayerDamageEvents;
+use crate::bullet_creator::prelude::BulletCreator;
+use crate::components::prelude::EnemyAi;
+use crate::dev_console::prelude::{DevCommands, DevConsoleLog};
+use crate::mod_packs::ModPacks;
+use crate::resource_helpers::resource;
+use crate::settings::SettingsLevelManagerCampaign;
+use crate::spatial_grid::EntityGrid;
+use crate::world_helpers::WorldHelpers;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CampaignType {
     Normal,
     BonusA,
     BonusB,
+    /// An installed mod pack, identified by its directory's name.
+    Mod(String),
 }
 
 impl Default for CampaignType {
@@ -21,6 +43,34 @@ impl Default for CampaignType {
     }
 }
 
+/// Returns the `LevelManager` settings of the given campaign.
+/// A mod pack must be playable (see `ModPacks`).
+pub fn campaign_settings(
+    world: &World,
+    campaign: &CampaignType,
+) -> SettingsLevelManagerCampaign {
+    let settings = world.settings();
+    match campaign {
+        CampaignType::Normal => settings.level_manager.normal,
+        CampaignType::BonusA => settings.level_manager.bonus_a,
+        CampaignType::BonusB => settings.level_manager.bonus_b,
+        CampaignType::Mod(id) => world
+            .read_resource::<ModPacks>()
+            .campaign(id)
+            .expect(&format!("Mod pack `{}` should be playable", id)),
+    }
+}
+
+/// Returns `true` if the savefile of the given campaign exists.
+pub fn savefile_exists_for(world: &World, campaign: &CampaignType) -> bool {
+    let savefile_path = format!(
+        "{}/{}",
+        application_root_dir(),
+        campaign_settings(world, campaign).savefile_path
+    );
+    Path::new(&savefile_path).exists()
+}
+
 /// `UiTransform::new` wrapper
 pub fn new_ui_transform<T: ToString>(
     name: T,
@@ -38,3 +88,77 @@ pub fn new_ui_transform<T: ToString>(
         pos.5, // tab-order (?)
     )
 }
+
+/// Pauses the level and global timers, and all turret timers.
+pub fn pause_game_timers(world: &mut World) {
+    {
+        let mut timers = world.write_resource::<Timers>();
+        if timers.level.state.is_running() {
+            timers.level.pause().unwrap();
+        }
+        timers.global.as_mut().map(|timer| {
+            if timer.state.is_running() {
+                timer.pause().unwrap()
+            }
+        });
+    }
+
+    world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
+        (&mut enemy_ais).join().for_each(|enemy_ai| {
+            if let EnemyAi::Turret(ai_data) = enemy_ai {
+                ai_data.shot_timer.pause().unwrap();
+            }
+        });
+    });
+}
+
+/// Resumes the timers paused by `pause_game_timers`.
+pub fn resume_game_timers(world: &mut World) {
+    {
+        let mut timers = world.write_resource::<Timers>();
+        if timers.level.state.is_paused() {
+            timers.level.resume().unwrap();
+        }
+        timers.global.as_mut().map(|timer| {
+            if timer.state.is_paused() {
+                timer.resume().unwrap()
+            }
+        });
+    }
+
+    world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
+        (&mut enemy_ais).join().for_each(|enemy_ai| {
+            if let EnemyAi::Turret(ai_data) = enemy_ai {
+                ai_data.shot_timer.resume().unwrap();
+            }
+        });
+    });
+}
+
+/// Adds the resources used by the ingame systems and the `LevelManager`,
+/// and loads the spritesheets, which are used by all levels.
+/// The `Settings` resource must already exist.
+pub fn add_ingame_resources(world: &mut World) {
+    let mut sprite_sheet_handles = SpriteSheetHandles::default();
+    sprite_sheet_handles
+        .load(resource("spritesheets/player_hearts.png"), world);
+    sprite_sheet_handles
+        .load(resource("spritesheets/player_bullets.png"), world);
+    world.add_resource(sprite_sheet_handles);
+    world.add_resource(TextureHandles::default());
+    world.add_resource(InputManager::default());
+    world.add_resource(BulletCreator::default());
+    world.add_resource(UpdateHealthDisplay::default());
+    world.add_resource(ToMainMenu::default());
+    world.add_resource(Timers::default());
+    world.add_resource(Stats::default());
+    world.add_resource(CurrentCampaign::default());
+    world.add_resource(CurrentLevelName::default());
+    world.add_resource(LoadingLevel::default());
+    world.add_resource(LoadLevelRequest::default());
+    world.add_resource(DevCommands::with_builtin());
+    world.add_resource(DevConsoleLog::default());
+    world.add_resource(PlayerDamageEvents::default());
+    world.add_resource(EntityGrid::default());
+    world.add_resource(ModPacks::default());
+}
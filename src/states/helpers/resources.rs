@@ -1,5 +1,7 @@
 use climer::Timer;
 
+use super::CampaignType;
+
 #[derive(Default)]
 pub struct ToMainMenu(pub bool);
 
@@ -12,8 +14,17 @@ pub struct Timers {
     pub global: Option<Timer>,
 }
 
+/// The campaign of the level, which is being played.
+#[derive(Default)]
+pub struct CurrentCampaign(pub CampaignType);
+
 #[derive(Default)]
 pub struct CurrentLevelName(pub Option<String>);
 
 #[derive(Default)]
 pub struct LoadingLevel(pub bool);
+
+/// Set by the developer console's `level` command;
+/// the `LevelManager` loads the level with this name.
+#[derive(Default)]
+pub struct LoadLevelRequest(pub Option<String>);
@@ -0,0 +1,247 @@
+use amethyst::ui::FontHandle;
+
+use super::super::state_prelude::*;
+
+const TITLE_ID: &str = "text_menu_title";
+const FOOTER_ID: &str = "text_menu_footer";
+const ROW_ID_PREFIX: &str = "text_menu_row";
+const SELECTED_PREFIX: &str = "> ";
+const UNSELECTED_PREFIX: &str = "  ";
+const TEXT_MENU_Z: f32 = 10.0;
+
+/// A vertical list of text rows, of which one is selected.
+/// Used for menus which need to be navigated with the keyboard or a controller,
+/// and whose contents change at runtime (like the options menu).
+#[derive(Default)]
+pub struct TextMenu {
+    title_entity:  Option<Entity>,
+    footer_entity: Option<Entity>,
+    row_entities:  Vec<Entity>,
+    selected:      usize,
+    footer:        Option<String>,
+    /// Overrides the settings' `font_size` and `row_height`.
+    row_size:      Option<(f32, f32)>,
+}
+
+impl TextMenu {
+    /// Sets a text, which is displayed below the rows.
+    /// Must be called before `create`.
+    pub fn set_footer<T>(&mut self, footer: T)
+    where
+        T: ToString,
+    {
+        self.footer = Some(footer.to_string());
+    }
+
+    /// Changes the footer's text, after the menu was created
+    /// with a footer.
+    pub fn update_footer<T>(&mut self, world: &mut World, footer: T)
+    where
+        T: ToString,
+    {
+        self.footer = Some(footer.to_string());
+        if let Some(entity) = self.footer_entity {
+            if let Some(ui_text) =
+                world.write_storage::<UiText>().get_mut(entity)
+            {
+                ui_text.text = footer.to_string();
+            }
+        }
+    }
+
+    /// Uses the given font size and row height for the rows,
+    /// instead of the ones from the settings.
+    /// Must be called before `create`.
+    pub fn set_row_size(&mut self, font_size: f32, row_height: f32) {
+        self.row_size = Some((font_size, row_height));
+    }
+
+    /// Creates the UI entities for the given title and rows.
+    pub fn create<T>(&mut self, world: &mut World, title: T, rows: &[String])
+    where
+        T: ToString,
+    {
+        self.delete(world);
+
+        let settings = world.settings().options_menu;
+        let font = get_font(&settings.font_file, world);
+        let (font_size, row_height) = self
+            .row_size
+            .unwrap_or((settings.font_size, settings.row_height));
+        let rows_len = rows.len() as f32;
+        let top = rows_len * 0.5 * row_height + 1.5 * settings.row_height;
+
+        let mut title_text = UiText::new(
+            font.clone(),
+            title.to_string(),
+            settings.selected_font_color,
+            settings.title_font_size,
+        );
+        title_text.align = AmethystAnchor::Middle;
+        self.title_entity = Some(
+            world
+                .create_entity()
+                .with(new_ui_transform(
+                    TITLE_ID,
+                    AmethystAnchor::Middle,
+                    (
+                        0.0,
+                        top,
+                        TEXT_MENU_Z,
+                        settings.width,
+                        settings.title_font_size * 1.5,
+                        0,
+                    ),
+                ))
+                .with(title_text)
+                .build(),
+        );
+
+        if let Some(footer) = self.footer.as_ref() {
+            let y = -(rows_len * 0.5 * row_height + 0.5 * settings.row_height);
+            let mut footer_text = UiText::new(
+                font.clone(),
+                footer.clone(),
+                settings.font_color,
+                settings.font_size * 0.75,
+            );
+            footer_text.align = AmethystAnchor::Middle;
+            self.footer_entity = Some(
+                world
+                    .create_entity()
+                    .with(new_ui_transform(
+                        FOOTER_ID,
+                        AmethystAnchor::Middle,
+                        (
+                            0.0,
+                            y,
+                            TEXT_MENU_Z,
+                            settings.width,
+                            settings.row_height,
+                            0,
+                        ),
+                    ))
+                    .with(footer_text)
+                    .build(),
+            );
+        }
+
+        for (i, _) in rows.iter().enumerate() {
+            let y = (rows_len * 0.5 - i as f32) * row_height;
+            let mut ui_text = UiText::new(
+                font.clone(),
+                String::new(),
+                settings.font_color,
+                font_size,
+            );
+            ui_text.align = AmethystAnchor::MiddleLeft;
+            let entity = world
+                .create_entity()
+                .with(new_ui_transform(
+                    format!("{}_{}", ROW_ID_PREFIX, i),
+                    AmethystAnchor::Middle,
+                    (
+                        0.0,
+                        y,
+                        TEXT_MENU_Z,
+                        settings.width,
+                        row_height,
+                        i as i32 + 1,
+                    ),
+                ))
+                .with(ui_text)
+                .build();
+            self.row_entities.push(entity);
+        }
+
+        self.selected = self.selected.min(rows.len().saturating_sub(1));
+        self.update(world, rows);
+    }
+
+    /// Updates the rows' texts, and highlights the selected row.
+    /// The amount of rows must be the same as when the menu was created.
+    pub fn update(&self, world: &mut World, rows: &[String]) {
+        let settings = world.settings().options_menu;
+        let mut ui_texts = world.write_storage::<UiText>();
+
+        for (i, (entity, row)) in
+            self.row_entities.iter().zip(rows.iter()).enumerate()
+        {
+            if let Some(ui_text) = ui_texts.get_mut(*entity) {
+                if i == self.selected {
+                    ui_text.text = format!("{}{}", SELECTED_PREFIX, row);
+                    ui_text.color = settings.selected_font_color;
+                } else {
+                    ui_text.text = format!("{}{}", UNSELECTED_PREFIX, row);
+                    ui_text.color = settings.font_color;
+                }
+            }
+        }
+    }
+
+    /// Deletes all UI entities of this menu.
+    pub fn delete(&mut self, world: &mut World) {
+        if let Some(entity) = self.title_entity.take() {
+            world
+                .delete_entity(entity)
+                .expect("Should delete text menu title entity");
+        }
+        if let Some(entity) = self.footer_entity.take() {
+            world
+                .delete_entity(entity)
+                .expect("Should delete text menu footer entity");
+        }
+        world
+            .delete_entities(&self.row_entities)
+            .expect("Should delete text menu row entities");
+        self.row_entities.clear();
+    }
+
+    /// Moves the selection with the `menu_up` and `menu_down` actions.
+    /// Returns `true` if the selection changed.
+    pub fn handle_navigation(&mut self, input_manager: &InputManager) -> bool {
+        let len = self.row_entities.len();
+        if len == 0 {
+            return false;
+        }
+
+        if input_manager.is_down("menu_up") {
+            self.selected = (self.selected + len - 1) % len;
+            true
+        } else if input_manager.is_down("menu_down") {
+            self.selected = (self.selected + 1) % len;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
+
+/// Returns the value as a text slider, for example `[#####-----] 50%`.
+pub fn text_slider(value: f32, width: usize) -> String {
+    let value = value.max(0.0).min(1.0);
+    let filled = (value * width as f32).round() as usize;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        (value * 100.0).round() as u32
+    )
+}
+
+pub fn get_font<T>(font: T, world: &World) -> FontHandle
+where
+    T: ToString,
+{
+    world.read_resource::<AssetLoader>().load(
+        resource(font),
+        TtfFormat,
+        Default::default(),
+        (),
+        &world.read_resource(),
+    )
+}
@@ -1,10 +1,32 @@
+use amethyst::core::timing::Time;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::Join;
+use amethyst::input::InputHandler;
+
 use super::state_prelude::*;
+use crate::crash_report::{CrashReporter, PlayerSnapshot};
+use crate::entity_inspector::EntityInspector;
+use crate::profiler::prelude::*;
+use crate::replay::ReplayRecorder;
+
+/// The player's part of the crash report's game snapshot
+/// is updated every this many frames.
+const PLAYER_SNAPSHOT_INTERVAL: u32 = 30;
 
 pub struct Ingame {
-    campaign:      CampaignType,
-    level_manager: Option<LevelManager>,
-    to_main_menu:  bool,
-    new_game:      bool,
+    campaign:         CampaignType,
+    level_manager:    Option<LevelManager>,
+    to_main_menu:     bool,
+    /// `true` while the `LevelLoading` state is pushed.
+    loading_level:    bool,
+    new_game:         bool,
+    start_level:      Option<String>,
+    entity_inspector: Option<EntityInspector>,
+    profiler_overlay: Option<ProfilerOverlay>,
+    /// Frames until the player's crash snapshot is updated again.
+    snapshot_frames:  u32,
+    /// Records the level runs, if `--record` is given.
+    replay_recorder:  Option<ReplayRecorder>,
 }
 
 impl Ingame {
@@ -21,11 +43,169 @@ impl Ingame {
         if input_manager.is_down("pause") {
             let paused_state = Box::new(Paused::default());
             Some(Trans::Push(paused_state))
+        } else if crate::in_development_mode()
+            && input_manager.is_up("dev_console")
+        {
+            Some(Trans::Push(Box::new(DevConsole::default())))
+        } else if crate::in_development_mode()
+            && input_manager.is_down("frame_step_toggle")
+        {
+            Some(Trans::Push(Box::new(FrameStep::default())))
         } else {
             None
         }
     }
 
+    /// Opens or closes the entity inspector (development mode only),
+    /// and updates it while open.
+    fn update_entity_inspector(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        if crate::in_development_mode()
+            && data
+                .world
+                .input_manager()
+                .is_down("entity_inspector_toggle")
+        {
+            if self.entity_inspector.is_some() {
+                self.close_entity_inspector(data);
+            } else {
+                self.entity_inspector =
+                    Some(EntityInspector::new(&mut data.world));
+            }
+        }
+
+        if let Some(entity_inspector) = self.entity_inspector.as_mut() {
+            entity_inspector.update(&mut data.world);
+        }
+    }
+
+    fn close_entity_inspector(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        if let Some(mut entity_inspector) = self.entity_inspector.take() {
+            entity_inspector.delete_ui(&mut data.world);
+        }
+    }
+
+    /// Opens or closes the system profiler overlay (development mode only),
+    /// and updates it while open.
+    fn update_profiler_overlay(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        if crate::in_development_mode()
+            && data
+                .world
+                .input_manager()
+                .is_down("profiler_overlay_toggle")
+        {
+            if self.profiler_overlay.is_some() {
+                self.close_profiler_overlay(data);
+            } else {
+                self.profiler_overlay =
+                    Some(ProfilerOverlay::new(&mut data.world));
+            }
+        }
+
+        if let Some(profiler_overlay) = self.profiler_overlay.as_mut() {
+            profiler_overlay.update(&mut data.world);
+        }
+    }
+
+    fn close_profiler_overlay(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        if let Some(mut profiler_overlay) = self.profiler_overlay.take() {
+            profiler_overlay.delete_ui(&mut data.world);
+        }
+    }
+
+    /// Sets the campaign and level of the game state,
+    /// which is included in crash reports.
+    fn set_crash_snapshot_level(
+        &mut self,
+        data: &StateData<CustomGameData<CustomData>>,
+    ) {
+        let level_name =
+            data.world.read_resource::<CurrentLevelName>().0.clone();
+        data.world
+            .read_resource::<CrashReporter>()
+            .set_snapshot_level(format!("{:?}", self.campaign), level_name);
+        self.snapshot_frames = 0;
+    }
+
+    /// Updates the player's part of the game state, which is included in
+    /// crash reports, every `PLAYER_SNAPSHOT_INTERVAL` frames.
+    fn update_crash_snapshot_player(
+        &mut self,
+        data: &StateData<CustomGameData<CustomData>>,
+    ) {
+        if self.snapshot_frames > 0 {
+            self.snapshot_frames -= 1;
+            return;
+        }
+        self.snapshot_frames = PLAYER_SNAPSHOT_INTERVAL;
+
+        let player = (
+            &data.world.read_storage::<Player>(),
+            &data.world.read_storage::<Transform>(),
+        )
+            .join()
+            .next()
+            .map(|(player, transform)| {
+                let pos = transform.translation();
+                PlayerSnapshot {
+                    position:   (pos.x, pos.y),
+                    health:     player.health,
+                    max_health: player.max_health,
+                    items:      player.items_data.clone(),
+                }
+            });
+        data.world
+            .read_resource::<CrashReporter>()
+            .set_snapshot_player(player);
+    }
+
+    /// Starts recording a new level run, after the level has loaded.
+    fn start_replay_recording(
+        &mut self,
+        data: &StateData<CustomGameData<CustomData>>,
+    ) {
+        if let Some(recorder) = self.replay_recorder.as_mut() {
+            let level_name = data
+                .world
+                .read_resource::<CurrentLevelName>()
+                .0
+                .clone()
+                .unwrap_or_default();
+            let player = (&data.world.read_storage::<Player>())
+                .join()
+                .next()
+                .cloned();
+            recorder.start(self.campaign.clone(), level_name, player);
+        }
+    }
+
+    /// Records the frame's delta time and the held down keys.
+    fn record_replay_frame(
+        &mut self,
+        data: &StateData<CustomGameData<CustomData>>,
+    ) {
+        if let Some(recorder) = self.replay_recorder.as_mut() {
+            let delta = data.world.read_resource::<Time>().delta_time();
+            let keys = data
+                .world
+                .read_resource::<InputHandler<String, String>>()
+                .keys_that_are_down()
+                .collect();
+            recorder.record_frame(delta, keys);
+        }
+    }
+
     fn level_manager(&self) -> &LevelManager {
         self.level_manager.as_ref().expect("LevelManager is None")
     }
@@ -37,44 +217,72 @@ impl Ingame {
 
 impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Ingame {
     fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        data.world.write_resource::<CurrentCampaign>().0 =
+            self.campaign.clone();
+
         // Initialize the LevelManager
-        let settings = data.world.settings();
-        let level_manager_settings = match self.campaign {
-            CampaignType::Normal => settings.level_manager.normal,
-            CampaignType::BonusA => settings.level_manager.bonus_a,
-            CampaignType::BonusB => settings.level_manager.bonus_b,
-        };
+        let level_manager_settings =
+            campaign_settings(&data.world, &self.campaign);
         self.level_manager = Some(LevelManager::new(
             &mut data,
             level_manager_settings,
             self.new_game,
         ));
+        if let Some(level_name) = self.start_level.take() {
+            if let Err(err) =
+                self.level_manager_mut().set_current_level(&level_name)
+            {
+                error!("{}", err);
+            }
+        }
 
         self.level_manager_mut().on_start(&mut data);
     }
 
     fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        data.world.read_resource::<CrashReporter>().clear_snapshot();
+        if let Some(recorder) = self.replay_recorder.as_mut() {
+            recorder.finish();
+        }
+        self.close_entity_inspector(&mut data);
+        self.close_profiler_overlay(&mut data);
         self.level_manager().on_stop(&mut data);
     }
 
     fn on_pause(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.close_entity_inspector(&mut data);
+        self.close_profiler_overlay(&mut data);
         self.level_manager().on_pause(&mut data);
     }
 
     fn on_resume(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
         self.level_manager().on_resume(&mut data);
 
-        // Return to main menu, if `Paused` state set the resource to do so
+        // Return to main menu, if `Paused` state set the resource to do so,
+        // or if `LevelLoading` state couldn't load the level
         self.to_main_menu = data.world.read_resource::<ToMainMenu>().0;
+
+        if self.loading_level {
+            self.loading_level = false;
+            if !self.to_main_menu {
+                self.level_manager_mut().finish_loading_level(&mut data);
+                self.set_crash_snapshot_level(&data);
+                self.start_replay_recording(&data);
+            }
+        }
     }
 
     fn handle_event(
         &mut self,
-        _data: StateData<CustomGameData<CustomData>>,
+        data: StateData<CustomGameData<CustomData>>,
         event: StateEvent,
     ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
         match &event {
             StateEvent::Window(event) => {
+                if let Some(entity_inspector) = self.entity_inspector.as_mut() {
+                    entity_inspector.handle_event(data.world, event);
+                }
+
                 if is_close_requested(&event) {
                     Trans::Pop
                 } else {
@@ -95,8 +303,19 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Ingame {
         }
 
         data.data.update(&data.world, "ingame").unwrap();
+        self.record_replay_frame(&data);
 
         self.level_manager_mut().update(&mut data);
+        if let Some(level_load) = self.level_manager_mut().take_level_load() {
+            if let Some(recorder) = self.replay_recorder.as_mut() {
+                recorder.finish();
+            }
+            self.loading_level = true;
+            return Trans::Push(Box::new(LevelLoading::new(level_load)));
+        }
+        self.update_entity_inspector(&mut data);
+        self.update_profiler_overlay(&mut data);
+        self.update_crash_snapshot_player(&data);
         if self.level_manager().has_won_game {
             // Switch to WinGameMenu
             return Trans::Switch(Box::new(WinGameMenu::new(
@@ -114,8 +333,9 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Ingame {
 
 #[derive(Default)]
 pub struct IngameBuilder {
-    campaign: CampaignType,
-    new_game: bool,
+    campaign:    CampaignType,
+    new_game:    bool,
+    start_level: Option<String>,
 }
 
 impl IngameBuilder {
@@ -129,12 +349,27 @@ impl IngameBuilder {
         self
     }
 
+    /// Start at the level with the given name, instead of the saved level.
+    pub fn level(mut self, level_name: Option<String>) -> Self {
+        self.start_level = level_name;
+        self
+    }
+
     pub fn build(self) -> Ingame {
         Ingame {
-            campaign:      self.campaign,
-            level_manager: None,
-            to_main_menu:  false,
-            new_game:      self.new_game,
+            campaign:         self.campaign,
+            level_manager:    None,
+            to_main_menu:     false,
+            loading_level:    false,
+            new_game:         self.new_game,
+            start_level:      self.start_level,
+            entity_inspector: None,
+            profiler_overlay: None,
+            snapshot_frames:  0,
+            replay_recorder:  crate::cli::args()
+                .record
+                .clone()
+                .map(ReplayRecorder::new),
         }
     }
 }
@@ -0,0 +1,262 @@
+use std::time::{Duration, Instant};
+
+use amethyst::ecs::{Join, ReadStorage};
+
+use super::state_prelude::*;
+use crate::level_manager::{
+    build_parsed_level,
+    LevelLoad,
+    LevelParser,
+    ParseStatus,
+};
+
+const UI_Z: f32 = 10.0;
+const UI_IDS: [&str; 3] = [
+    "level_loading_title",
+    "level_loading_best_time",
+    "level_loading_progress",
+];
+
+/// The loading screen, which is pushed by `Ingame` to load a level.
+/// Displays the level's name, its best time and the loading progress.
+/// The level file is parsed on a background thread; once the entities are built,
+/// the state waits until all of the level's sprite sheets and its song are loaded.
+/// If the level can't be parsed, the error is logged and the game returns to the main menu.
+pub struct LevelLoading {
+    level_load:  LevelLoad,
+    parser:      Option<LevelParser>,
+    assets:      Option<AssetTracker>,
+    started_at:  Instant,
+    ui_entities: Vec<Entity>,
+}
+
+impl LevelLoading {
+    pub fn new(level_load: LevelLoad) -> Self {
+        Self {
+            level_load:  level_load,
+            parser:      None,
+            assets:      None,
+            started_at:  Instant::now(),
+            ui_entities: Vec::new(),
+        }
+    }
+
+    fn create_ui(&mut self, data: &mut StateData<CustomGameData<CustomData>>) {
+        let settings = data.world.settings().level_loading;
+        let screen_width = data
+            .data
+            .custom
+            .as_ref()
+            .expect("CustomData should exist")
+            .display_config
+            .dimensions
+            .unwrap_or((1200, 800))
+            .0 as f32;
+        let font = get_font(&settings.font_file, &data.world);
+
+        let title = self.level_load.level_name.trim_end_matches(".json");
+        let best_time = self
+            .level_load
+            .best_time
+            .map(|time| format!("{}{}", settings.best_time_prefix, time))
+            .unwrap_or_default();
+        let lines = [
+            (title.to_string(), settings.title_font_size),
+            (best_time, settings.font_size),
+            (settings.parsing_text.clone(), settings.font_size),
+        ];
+
+        self.ui_entities = lines
+            .iter()
+            .zip(UI_IDS.iter())
+            .enumerate()
+            .map(|(i, ((text, font_size), id))| {
+                data.world
+                    .create_entity()
+                    .with(new_ui_transform(
+                        id,
+                        AmethystAnchor::Middle,
+                        (
+                            0.0,
+                            (1.0 - i as f32) * settings.line_height,
+                            UI_Z,
+                            screen_width,
+                            settings.line_height,
+                            0,
+                        ),
+                    ))
+                    .with(UiText::new(
+                        font.clone(),
+                        text.clone(),
+                        settings.font_color,
+                        *font_size,
+                    ))
+                    .build()
+            })
+            .collect();
+    }
+
+    fn delete_ui(&mut self, data: &mut StateData<CustomGameData<CustomData>>) {
+        data.world
+            .delete_entities(&self.ui_entities)
+            .expect("Should delete level loading UI entities");
+        self.ui_entities.clear();
+    }
+
+    fn update_progress_text(&self, world: &mut World) {
+        let text = match self.assets.as_ref() {
+            None => world.settings().level_loading.parsing_text,
+            Some(assets) => {
+                let (loaded, total) = assets.counts();
+                format!(
+                    "{}{} / {}",
+                    world.settings().level_loading.progress_prefix,
+                    loaded,
+                    total
+                )
+            }
+        };
+        if let Some(entity) = self.ui_entities.last() {
+            if let Some(ui_text) =
+                world.write_storage::<UiText>().get_mut(*entity)
+            {
+                ui_text.text = text;
+            }
+        }
+    }
+
+    /// Polls the background thread, and builds the level once it is parsed.
+    /// Returns an error, if the level couldn't be parsed.
+    fn update_parser(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) -> Result<(), String> {
+        let status = self
+            .parser
+            .as_ref()
+            .expect("Level parser should exist")
+            .poll();
+        match status {
+            ParseStatus::Parsing => (),
+            ParseStatus::Parsed(level_loader) => {
+                self.parser = None;
+                build_parsed_level(data, level_loader);
+                self.assets = Some(track_level_assets(
+                    &mut data.world,
+                    &self.level_load.song_filepath,
+                ));
+            }
+            ParseStatus::Failed(err) => {
+                self.parser = None;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn has_timed_out(&self, world: &World) -> bool {
+        let timeout = world.settings().level_loading.asset_timeout.max(0.0);
+        self.started_at.elapsed()
+            > Duration::from_millis((timeout * 1000.0) as u64)
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for LevelLoading
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.create_ui(&mut data);
+        self.started_at = Instant::now();
+        self.parser = Some(self.level_load.start_parsing());
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.delete_ui(&mut data);
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        match &event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    Trans::Quit
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        mut data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        data.data.update(&data.world, "level_loading").unwrap();
+
+        if self.parser.is_some() {
+            if let Err(err) = self.update_parser(&mut data) {
+                error!(
+                    "Couldn't load level {}, returning to the main menu: {}",
+                    self.level_load.level_name, err
+                );
+                data.world.write_resource::<ToMainMenu>().0 = true;
+                return Trans::Pop;
+            }
+        }
+
+        let has_timed_out = self.has_timed_out(&data.world);
+        if let Some(assets) = self.assets.as_mut() {
+            assets.update(&data.world);
+            if assets.is_complete() {
+                info!(
+                    "Loaded level {} in {} ms",
+                    self.level_load.level_name,
+                    millis(self.started_at.elapsed())
+                );
+                return Trans::Pop;
+            }
+            if has_timed_out {
+                let (loaded, total) = assets.counts();
+                warn!(
+                    "Level {} is playing, before all assets have loaded: {} \
+                     of {} loaded",
+                    self.level_load.level_name, loaded, total
+                );
+                return Trans::Pop;
+            }
+        }
+
+        self.update_progress_text(&mut data.world);
+
+        Trans::None
+    }
+}
+
+/// Tracks the sprite sheets of all built entities, and starts loading the song.
+fn track_level_assets(world: &mut World, song_filepath: &str) -> AssetTracker {
+    let sprite_sheets: Vec<SpriteSheetHandle> =
+        world.exec(|sprite_renders: ReadStorage<SpriteRender>| {
+            (&sprite_renders)
+                .join()
+                .map(|sprite_render| sprite_render.sprite_sheet.clone())
+                .collect()
+        });
+    let song = world
+        .write_resource::<AudioHandles>()
+        .get_or_load(song_filepath.to_string(), world);
+
+    let mut tracker = AssetTracker::new();
+    for handle in sprite_sheets {
+        tracker.track(TrackedAsset::SpriteSheet(handle));
+    }
+    tracker.track(TrackedAsset::Song(song));
+    tracker
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
@@ -6,14 +6,23 @@ const UI_RON_PATH: &str = "ui/main_menu.ron";
 pub struct MainMenu {
     ui_entities:  Vec<Entity>,
     ui_reader_id: Option<ReaderId<UiEvent>>,
+    start_ingame: Option<Ingame>,
 }
 
 impl MainMenu {
+    /// Pushes the given `Ingame` state right away, so the game starts
+    /// without going through the main menu, but returns to it afterwards.
+    pub fn starting_ingame(ingame: Ingame) -> Self {
+        Self {
+            start_ingame: Some(ingame),
+            ..Self::default()
+        }
+    }
+
     fn handle_keys<'a, 'b>(
         &self,
         data: &StateData<CustomGameData<CustomData>>,
     ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
-        let settings = data.world.settings();
         let input_manager = data.world.input_manager();
 
         // Quit game
@@ -21,7 +30,10 @@ impl MainMenu {
             Some(Trans::Quit)
         // Start game
         } else if input_manager.is_up("accept") {
-            Some(self.trans_for_campaign(CampaignType::default(), &settings))
+            Some(self.trans_for_campaign(CampaignType::default(), &data.world))
+        // Open options menu
+        } else if input_manager.is_up("options") {
+            Some(Trans::Push(Box::new(OptionsMenu::default())))
         } else {
             None
         }
@@ -34,11 +46,11 @@ impl MainMenu {
     fn trans_for_campaign<'a, 'b>(
         &self,
         campaign: CampaignType,
-        settings: &Settings,
+        world: &World,
     ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
         // Only start ContinueOrNewGameMenu if a savefile already exists,
         // otherwise start the game directly.
-        if savefile_exists_for(&campaign, settings) {
+        if savefile_exists_for(world, &campaign) {
             Trans::Push(Box::new(ContinueOrNewGameMenu::new(campaign)))
         } else {
             Trans::Push(Box::new(
@@ -93,6 +105,9 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
         data: StateData<CustomGameData<CustomData>>,
     ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
         data.data.update(&data.world, "main_menu").unwrap();
+        if let Some(ingame) = self.start_ingame.take() {
+            return Trans::Push(Box::new(ingame));
+        }
         if let Some(trans) = self.handle_keys(&data) {
             return trans;
         }
@@ -121,7 +136,6 @@ impl Menu for MainMenu {
             Trans(Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>),
         }
 
-        let settings = data.world.settings();
         let start_with_campaign = match event_name.as_ref() {
             "start_bonus_select_menu" => {
                 let state = Box::new(BonusSelectMenu::default());
@@ -136,13 +150,21 @@ impl Menu for MainMenu {
             "start_button_bonus_b" => {
                 Some(CampainOrTrans::Campaign(CampaignType::BonusB))
             }
+            "options_button" => {
+                let state = Box::new(OptionsMenu::default());
+                Some(CampainOrTrans::Trans(Trans::Push(state)))
+            }
+            "mods_button" => {
+                let state = Box::new(ModSelectMenu::default());
+                Some(CampainOrTrans::Trans(Trans::Push(state)))
+            }
             "quit_button" => Some(CampainOrTrans::Trans(Trans::Quit)),
             _ => None,
         };
 
         match start_with_campaign {
             Some(CampainOrTrans::Campaign(campaign)) => {
-                Some(self.trans_for_campaign(campaign, &settings))
+                Some(self.trans_for_campaign(campaign, &data.world))
             }
             Some(CampainOrTrans::Trans(trans)) => Some(trans),
             None => None,
@@ -169,17 +191,3 @@ impl Menu for MainMenu {
         &mut self.ui_reader_id
     }
 }
-
-fn savefile_exists_for(campaign: &CampaignType, settings: &Settings) -> bool {
-    use amethyst::utils::application_root_dir;
-    use std::path::Path;
-
-    let savefile_relative = match campaign {
-        CampaignType::Normal => &settings.level_manager.normal.savefile_path,
-        CampaignType::BonusA => &settings.level_manager.bonus_a.savefile_path,
-        CampaignType::BonusB => &settings.level_manager.bonus_b.savefile_path,
-    };
-    let savefile_path =
-        format!("{}/{}", application_root_dir(), savefile_relative);
-    Path::new(&savefile_path).exists()
-}
@@ -1,7 +1,13 @@
 mod bonus_select_menu;
 mod continue_or_new_game_menu;
+mod controls_menu;
+mod dev_console;
+mod frame_step;
 mod ingame;
+mod level_loading;
 mod main_menu;
+mod mod_select_menu;
+mod options_menu;
 mod paused;
 mod startup;
 mod win_game_menu;
@@ -9,8 +15,14 @@ mod win_game_menu;
 pub mod prelude {
     pub use super::bonus_select_menu::BonusSelectMenu;
     pub use super::continue_or_new_game_menu::ContinueOrNewGameMenu;
+    pub use super::controls_menu::ControlsMenu;
+    pub use super::dev_console::DevConsole;
+    pub use super::frame_step::FrameStep;
     pub use super::ingame::Ingame;
+    pub use super::level_loading::LevelLoading;
     pub use super::main_menu::MainMenu;
+    pub use super::mod_select_menu::ModSelectMenu;
+    pub use super::options_menu::OptionsMenu;
     pub use super::paused::Paused;
     pub use super::startup::Startup;
     pub use super::win_game_menu::WinGameMenu;
@@ -58,9 +70,12 @@ pub mod state_prelude {
     pub use super::prelude::*;
     pub use crate::bullet_creator::prelude::*;
     pub use crate::components::prelude::*;
+    pub use crate::dev_console::prelude::*;
     pub use crate::level_manager::prelude::*;
     pub use crate::resource_helpers::*;
     pub use crate::settings::prelude::*;
+    pub use crate::user_bindings::prelude::*;
+    pub use crate::user_settings::prelude::*;
     pub use crate::world_helpers::*;
     pub use crate::CustomData;
 }
@@ -0,0 +1,179 @@
+use super::state_prelude::*;
+use crate::mod_packs::{ModPacks, MODS_DIRNAME};
+
+const TITLE: &str = "Mods";
+const BACK_ROW: &str = "Back";
+
+/// Lists the installed mod packs, and starts the selected pack's campaign.
+/// The `mods` directory is scanned again whenever the menu is opened,
+/// so packs can be added without restarting the game.
+#[derive(Default)]
+pub struct ModSelectMenu {
+    menu: TextMenu,
+}
+
+impl ModSelectMenu {
+    fn handle_keys<'a, 'b>(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
+        let (navigated, accepted, declined) = {
+            let input_manager = data.world.input_manager();
+            (
+                self.menu.handle_navigation(&input_manager),
+                input_manager.is_up("accept"),
+                input_manager.is_up("decline"),
+            )
+        };
+
+        if declined {
+            return Some(Trans::Pop);
+        }
+
+        let selected_pack = data
+            .world
+            .read_resource::<ModPacks>()
+            .packs()
+            .get(self.menu.selected())
+            .cloned();
+
+        if accepted {
+            match selected_pack.as_ref() {
+                Some(pack) if pack.is_playable() => {
+                    return Some(trans_for_campaign(
+                        CampaignType::Mod(pack.id.clone()),
+                        &data.world,
+                    ));
+                }
+                Some(_) => (),
+                None => return Some(Trans::Pop),
+            }
+        }
+
+        if navigated {
+            let rows = row_texts(&data.world);
+            let footer = footer_text(&data.world, self.menu.selected());
+            self.menu.update(&mut data.world, &rows);
+            self.menu.update_footer(&mut data.world, footer);
+        }
+
+        None
+    }
+
+    fn create_menu(&mut self, world: &mut World) {
+        let mod_packs = ModPacks::scan(&world.settings().level_manager.normal);
+        world.add_resource(mod_packs);
+
+        let rows = row_texts(world);
+        self.menu.set_footer("");
+        self.menu.create(world, TITLE, &rows);
+        // Creating the menu keeps the selection within the rows,
+        // whose amount may have changed.
+        let footer = footer_text(world, self.menu.selected());
+        self.menu.update_footer(world, footer);
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for ModSelectMenu
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.create_menu(&mut data.world);
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.menu.delete(&mut data.world);
+    }
+
+    fn on_pause(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.menu.delete(&mut data.world);
+    }
+
+    fn on_resume(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.create_menu(&mut data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        match &event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    Trans::Quit
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        mut data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        data.data.update(&data.world, "mod_select_menu").unwrap();
+        if let Some(trans) = self.handle_keys(&mut data) {
+            return trans;
+        }
+        Trans::None
+    }
+}
+
+/// Returns the `Trans::Switch` with the appropriate state, given the campaign.
+/// Starts the `ContinueOrNewGameMenu` state if the campaign's savefile exists,
+/// and the `Ingame` state otherwise.
+fn trans_for_campaign<'a, 'b>(
+    campaign: CampaignType,
+    world: &World,
+) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+    if savefile_exists_for(world, &campaign) {
+        Trans::Switch(Box::new(ContinueOrNewGameMenu::new(campaign)))
+    } else {
+        Trans::Switch(Box::new(
+            Ingame::builder().campaign(campaign).new_game(false).build(),
+        ))
+    }
+}
+
+/// One row per installed pack, and the `Back` row.
+fn row_texts(world: &World) -> Vec<String> {
+    world
+        .read_resource::<ModPacks>()
+        .packs()
+        .iter()
+        .map(|pack| match pack.manifest.as_ref() {
+            Some(manifest) if pack.is_playable() => {
+                format!("{}  by {}", manifest.name, manifest.author)
+            }
+            _ => format!("{}  (broken)", pack.name()),
+        })
+        .chain(Some(BACK_ROW.to_string()))
+        .collect()
+}
+
+/// Describes the selected pack; or where to install packs, if there are none.
+fn footer_text(world: &World, selected: usize) -> String {
+    let mod_packs = world.read_resource::<ModPacks>();
+    if mod_packs.packs().is_empty() {
+        return format!(
+            "No mod packs installed. Put level packs into the `{}` directory.",
+            MODS_DIRNAME
+        );
+    }
+    match mod_packs.packs().get(selected) {
+        Some(pack) => match pack.campaign.as_ref() {
+            Some(campaign) if pack.is_playable() => {
+                format!("{} levels", campaign.level_names.len())
+            }
+            _ => format!(
+                "Can't be played: {} problem(s), the first is: {}",
+                pack.problems.len(),
+                pack.problems.first().map_or("", String::as_str)
+            ),
+        },
+        None => String::new(),
+    }
+}
@@ -0,0 +1,319 @@
+use amethyst::audio::AudioSink;
+
+use super::state_prelude::*;
+use crate::user_settings::step_volume;
+
+const TITLE: &str = "Options";
+const FOOTER: &str = "Display changes take effect after restarting the game.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum OptionsRow {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Mute,
+    Resolution,
+    WindowMode,
+    Vsync,
+    FrameLimit,
+    Controls,
+    Back,
+}
+
+const ROWS: [OptionsRow; 10] = [
+    OptionsRow::MasterVolume,
+    OptionsRow::MusicVolume,
+    OptionsRow::SfxVolume,
+    OptionsRow::Mute,
+    OptionsRow::Resolution,
+    OptionsRow::WindowMode,
+    OptionsRow::Vsync,
+    OptionsRow::FrameLimit,
+    OptionsRow::Controls,
+    OptionsRow::Back,
+];
+
+#[derive(Default)]
+pub struct OptionsMenu {
+    menu:         TextMenu,
+    /// If the music is currently playing at the _paused_ volume;
+    /// used when applying volume changes live.
+    music_paused: bool,
+}
+
+impl OptionsMenu {
+    /// Creates a new `OptionsMenu`, which is opened from the `Paused` state.
+    pub fn from_paused() -> Self {
+        Self {
+            music_paused: true,
+            ..Default::default()
+        }
+    }
+
+    fn handle_keys<'a, 'b>(
+        &mut self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
+        let (navigated, change, accepted, declined) = {
+            let input_manager = data.world.input_manager();
+            let navigated = self.menu.handle_navigation(&input_manager);
+            let change = if input_manager.is_down("menu_left") {
+                -1
+            } else if input_manager.is_down("menu_right") {
+                1
+            } else {
+                0
+            };
+            (
+                navigated,
+                change,
+                input_manager.is_up("accept"),
+                input_manager.is_up("decline"),
+            )
+        };
+
+        if declined {
+            return Some(Trans::Pop);
+        }
+
+        let row = ROWS[self.menu.selected()];
+        let mut changed = false;
+
+        if change != 0 {
+            changed = self.change_row(data, row, change);
+        } else if accepted {
+            match row {
+                OptionsRow::Mute
+                | OptionsRow::WindowMode
+                | OptionsRow::Vsync => changed = self.change_row(data, row, 1),
+                OptionsRow::Controls => {
+                    return Some(Trans::Push(Box::new(ControlsMenu::default())))
+                }
+                OptionsRow::Back => return Some(Trans::Pop),
+                _ => (),
+            }
+        }
+
+        if changed {
+            self.apply_music_volume(data);
+        }
+        if navigated || changed {
+            let rows = self.row_texts(data);
+            self.menu.update(&mut data.world, &rows);
+        }
+
+        None
+    }
+
+    /// Changes the value of the given row by the given amount of steps.
+    /// Returns `true` if anything was changed.
+    fn change_row(
+        &self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+        row: OptionsRow,
+        steps: i32,
+    ) -> bool {
+        let settings = data.world.settings().options_menu;
+        let display_config = current_display_config(data);
+        let mut user_settings = data.world.write_resource::<UserSettings>();
+        let UserSettings { audio, display } = &mut *user_settings;
+        match row {
+            OptionsRow::MasterVolume => {
+                audio.master_volume = step_volume(audio.master_volume, steps)
+            }
+            OptionsRow::MusicVolume => {
+                audio.music_volume = step_volume(audio.music_volume, steps)
+            }
+            OptionsRow::SfxVolume => {
+                audio.sfx_volume = step_volume(audio.sfx_volume, steps)
+            }
+            OptionsRow::Mute => audio.muted = !audio.muted,
+            OptionsRow::Resolution => {
+                let current = display
+                    .resolution
+                    .or(display_config.dimensions)
+                    .unwrap_or_default();
+                display.resolution =
+                    Some(cycle(&settings.resolutions, &current, steps));
+            }
+            OptionsRow::WindowMode => {
+                let fullscreen =
+                    display.fullscreen.unwrap_or(display_config.fullscreen);
+                display.fullscreen = Some(!fullscreen);
+            }
+            OptionsRow::Vsync => {
+                let vsync = display.vsync.unwrap_or(display_config.vsync);
+                display.vsync = Some(!vsync);
+            }
+            OptionsRow::FrameLimit => {
+                let current = display.frame_limit.unwrap_or(crate::FPS);
+                display.frame_limit =
+                    Some(cycle(&settings.frame_limits, &current, steps));
+            }
+            OptionsRow::Controls | OptionsRow::Back => return false,
+        }
+        true
+    }
+
+    fn apply_music_volume(
+        &self,
+        data: &mut StateData<CustomGameData<CustomData>>,
+    ) {
+        let volume = if self.music_paused {
+            data.world.music_volume_paused()
+        } else {
+            data.world.music_volume()
+        };
+        data.world.write_resource::<AudioSink>().set_volume(volume);
+    }
+
+    fn row_texts(
+        &self,
+        data: &StateData<CustomGameData<CustomData>>,
+    ) -> Vec<String> {
+        let slider_width = data.world.settings().options_menu.slider_width;
+        let UserSettings { audio, display } = data.world.user_settings();
+        let display_config = current_display_config(data);
+
+        ROWS.iter()
+            .map(|row| match row {
+                OptionsRow::MasterVolume => format!(
+                    "Master Volume  {}",
+                    text_slider(audio.master_volume, slider_width)
+                ),
+                OptionsRow::MusicVolume => format!(
+                    "Music Volume   {}",
+                    text_slider(audio.music_volume, slider_width)
+                ),
+                OptionsRow::SfxVolume => format!(
+                    "SFX Volume     {}",
+                    text_slider(audio.sfx_volume, slider_width)
+                ),
+                OptionsRow::Mute => format!(
+                    "Mute           {}",
+                    if audio.muted { "On" } else { "Off" }
+                ),
+                OptionsRow::Resolution => {
+                    match display.resolution.or(display_config.dimensions) {
+                        Some((w, h)) => format!("Resolution     {} x {}", w, h),
+                        None => "Resolution     Default".to_string(),
+                    }
+                }
+                OptionsRow::WindowMode => format!(
+                    "Window Mode    {}",
+                    if display.fullscreen.unwrap_or(display_config.fullscreen) {
+                        "Fullscreen"
+                    } else {
+                        "Windowed"
+                    }
+                ),
+                OptionsRow::Vsync => format!(
+                    "VSync          {}",
+                    if display.vsync.unwrap_or(display_config.vsync) {
+                        "On"
+                    } else {
+                        "Off"
+                    }
+                ),
+                OptionsRow::FrameLimit => {
+                    match display.frame_limit.unwrap_or(crate::FPS) {
+                        0 => "Frame Limit    Unlimited".to_string(),
+                        fps => format!("Frame Limit    {} FPS", fps),
+                    }
+                }
+                OptionsRow::Controls => "Controls".to_string(),
+                OptionsRow::Back => "Back".to_string(),
+            })
+            .collect()
+    }
+
+    fn save_user_settings(&self, data: &StateData<CustomGameData<CustomData>>) {
+        if let Err(err) = data.world.read_resource::<UserSettings>().save() {
+            error!("Couldn't save user settings: {}", err);
+        }
+    }
+}
+
+impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent>
+    for OptionsMenu
+{
+    fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        let rows = self.row_texts(&data);
+        self.menu.set_footer(FOOTER);
+        self.menu.create(&mut data.world, TITLE, &rows);
+    }
+
+    fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.menu.delete(&mut data.world);
+        self.save_user_settings(&data);
+    }
+
+    fn on_pause(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.menu.delete(&mut data.world);
+    }
+
+    fn on_resume(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        let rows = self.row_texts(&data);
+        self.menu.create(&mut data.world, TITLE, &rows);
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<CustomGameData<CustomData>>,
+        event: StateEvent,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        match &event {
+            StateEvent::Window(event) => {
+                if is_close_requested(&event) {
+                    Trans::Quit
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        mut data: StateData<CustomGameData<CustomData>>,
+    ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
+        data.data.update(&data.world, "options_menu").unwrap();
+        if let Some(trans) = self.handle_keys(&mut data) {
+            return trans;
+        }
+        Trans::None
+    }
+}
+
+/// Returns the `DisplayConfig` the game was started with.
+fn current_display_config(
+    data: &StateData<CustomGameData<CustomData>>,
+) -> DisplayConfig {
+    data.data
+        .custom
+        .as_ref()
+        .expect("CustomData should exist")
+        .display_config
+        .clone()
+}
+
+/// Returns the value which is the given amount of steps away from
+/// the current value in the given list of values, wrapping around.
+/// If the current value is not in the list, returns the first value.
+fn cycle<T>(values: &[T], current: &T, steps: i32) -> T
+where
+    T: Clone + PartialEq,
+{
+    if values.is_empty() {
+        return current.clone();
+    }
+    match values.iter().position(|value| value == current) {
+        Some(index) => {
+            let len = values.len() as i32;
+            let index = ((index as i32 + steps) % len + len) % len;
+            values[index as usize].clone()
+        }
+        None => values[0].clone(),
+    }
+}
@@ -1,5 +1,4 @@
 use super::state_prelude::*;
-use amethyst::ecs::{Join, WriteStorage};
 
 const UI_RON_PATH: &str = "ui/paused.ron";
 
@@ -27,6 +26,9 @@ impl Paused {
             || input_manager.is_up("accept")
         {
             Some(Trans::Pop)
+        // Open options menu
+        } else if input_manager.is_up("options") {
+            Some(Trans::Push(Box::new(OptionsMenu::from_paused())))
         } else {
             None
         }
@@ -37,27 +39,7 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Paused {
     fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
         self.create_ui(&mut data);
 
-        // Pause timers
-        {
-            let mut timers = data.world.write_resource::<Timers>();
-            if timers.level.state.is_running() {
-                timers.level.pause().unwrap();
-            }
-            timers.global.as_mut().map(|timer| {
-                if timer.state.is_running() {
-                    timer.pause().unwrap()
-                }
-            });
-        }
-
-        // Pause all turret timers
-        data.world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
-            (&mut enemy_ais).join().for_each(|enemy_ai| {
-                if let EnemyAi::Turret(ai_data) = enemy_ai {
-                    ai_data.shot_timer.pause().unwrap();
-                }
-            });
-        });
+        pause_game_timers(&mut data.world);
     }
 
     fn on_stop(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
@@ -68,27 +50,15 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Paused {
             data.world.write_resource::<ToMainMenu>().0 = true;
         }
 
-        // Resume timers
-        {
-            let mut timers = data.world.write_resource::<Timers>();
-            if timers.level.state.is_paused() {
-                timers.level.resume().unwrap();
-            }
-            timers.global.as_mut().map(|timer| {
-                if timer.state.is_paused() {
-                    timer.resume().unwrap()
-                }
-            });
-        }
+        resume_game_timers(&mut data.world);
+    }
 
-        // Resume all turret timers
-        data.world.exec(|mut enemy_ais: WriteStorage<EnemyAi>| {
-            (&mut enemy_ais).join().for_each(|enemy_ai| {
-                if let EnemyAi::Turret(ai_data) = enemy_ai {
-                    ai_data.shot_timer.resume().unwrap();
-                }
-            });
-        });
+    fn on_resume(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.create_ui(&mut data);
+    }
+
+    fn on_pause(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        self.delete_ui(&mut data);
     }
 
     fn handle_event(
@@ -140,6 +110,9 @@ impl Menu for Paused {
     ) -> Option<Trans<CustomGameData<'a, 'b, CustomData>, StateEvent>> {
         match event_name.as_ref() {
             "pause_button" => Some(Trans::Pop),
+            "options_button" => {
+                Some(Trans::Push(Box::new(OptionsMenu::from_paused())))
+            }
             "quit_button" => {
                 self.to_main_menu = true;
                 Some(Trans::Pop)
@@ -1,15 +1,86 @@
+use std::time::{Duration, Instant};
+
 use amethyst::audio::AudioSink;
 
 use super::state_prelude::*;
+use crate::asset_manifest::{spritesheet_ron_path, AssetManifest};
+use crate::mod_packs::ModPacks;
+use crate::resource_archive::ArchiveSource;
+use crate::settings_loader::load_settings_with_hash;
 
 pub struct Startup {
     loading_text_entity: Option<Entity>,
+    asset_tracker:       AssetTracker,
+    started_at:          Instant,
 }
 
 impl Startup {
-    // TODO
-    fn is_finished_loading(&self) -> bool {
-        true
+    /// Finished when all preloaded assets have loaded,
+    /// or when waiting for them has timed out.
+    fn is_finished_loading(&mut self, world: &World) -> bool {
+        self.asset_tracker.update(world);
+        if self.asset_tracker.is_complete() {
+            return true;
+        }
+        let timeout = world.settings().loading_text.preload_timeout.max(0.0);
+        let has_timed_out = self.started_at.elapsed()
+            > Duration::from_millis((timeout * 1000.0) as u64);
+        if has_timed_out {
+            let (loaded, total) = self.asset_tracker.counts();
+            warn!(
+                "Preloading assets timed out: {} of {} loaded",
+                loaded, total
+            );
+        }
+        has_timed_out
+    }
+
+    /// Starts loading the assets of every campaign's asset manifest,
+    /// so they don't have to be loaded when they are first used.
+    fn preload_assets(&mut self, world: &mut World) {
+        let settings = world.settings().level_manager;
+        let mut manifest = AssetManifest::default();
+        for campaign_settings in
+            &[settings.normal, settings.bonus_a, settings.bonus_b]
+        {
+            match AssetManifest::load(&campaign_settings.asset_manifest) {
+                Ok(campaign_manifest) => manifest.merge(campaign_manifest),
+                Err(err) => {
+                    warn!("{}; its assets are loaded once they are used", err)
+                }
+            }
+        }
+
+        // Missing files would never finish loading.
+        for file in manifest.missing_files() {
+            warn!("Asset `{}` doesn't exist, not preloading it", file);
+        }
+
+        for path in &manifest.spritesheets {
+            if resource_exists(path)
+                && resource_exists(&spritesheet_ron_path(path))
+            {
+                let handle = world
+                    .write_resource::<SpriteSheetHandles>()
+                    .get_or_load(resource(path), world);
+                self.asset_tracker.track(TrackedAsset::SpriteSheet(handle));
+            }
+        }
+        for path in manifest.textures.iter().filter(|p| resource_exists(p)) {
+            let handle = world
+                .write_resource::<TextureHandles>()
+                .get_or_load(resource(path), world);
+            self.asset_tracker.track(TrackedAsset::Texture(handle));
+        }
+        for path in manifest.songs.iter().filter(|p| resource_exists(p)) {
+            let handle = world
+                .write_resource::<AudioHandles>()
+                .get_or_load(resource(path), world);
+            self.asset_tracker.track(TrackedAsset::Song(handle));
+        }
+
+        let (_, total) = self.asset_tracker.counts();
+        info!("Preloading {} assets", total);
     }
 
     fn initialize_loading_text(
@@ -63,38 +134,55 @@ impl Startup {
 
 impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Startup {
     fn on_start(&mut self, mut data: StateData<CustomGameData<CustomData>>) {
+        // Load assets from the resource archive, if it exists.
+        data.world
+            .write_resource::<AssetLoader>()
+            .add_source("", ArchiveSource::default());
+
         // Resources
-        data.world.add_resource(load_settings());
-        let settings = data.world.settings();
-        let mut sprite_sheet_handles = SpriteSheetHandles::default();
-        sprite_sheet_handles
-            .load(resource("spritesheets/player_hearts.png"), &mut data.world);
-        sprite_sheet_handles
-            .load(resource("spritesheets/player_bullets.png"), &mut data.world);
+        let crash_reporter = data
+            .data
+            .custom
+            .as_ref()
+            .expect("CustomData should exist")
+            .crash_reporter
+            .clone();
+        let (settings, settings_hash) = load_settings_with_hash()
+            .unwrap_or_else(|err| panic!("Couldn't load settings: {}", err));
+        crash_reporter.set_settings_hash(settings_hash);
+        data.world.add_resource(settings);
+        data.world.add_resource(crash_reporter);
+        data.world.add_resource(UserSettings::load());
+        add_ingame_resources(&mut data.world);
+        let mod_packs =
+            ModPacks::scan(&data.world.settings().level_manager.normal);
+        data.world.add_resource(mod_packs);
         {
+            let music_volume = data.world.music_volume();
             let mut sink = data.world.write_resource::<AudioSink>();
-            sink.set_volume(settings.music_volume);
+            sink.set_volume(music_volume);
         }
-        let audio_handles = AudioHandles::default();
-        data.world.add_resource(sprite_sheet_handles);
-        data.world.add_resource(audio_handles);
-        data.world.add_resource(TextureHandles::default());
-        data.world.add_resource(InputManager::default());
-        data.world.add_resource(BulletCreator::default());
-        data.world.add_resource(UpdateHealthDisplay::default());
-        data.world.add_resource(ToMainMenu::default());
-        data.world.add_resource(Timers::default());
-        data.world.add_resource(Stats::default());
-        data.world.add_resource(CurrentLevelName::default());
-        data.world.add_resource(LoadingLevel::default());
-
-        // TODO
-        // data.world
-        //     .add_resource(DebugLines::new().with_capacity(200));
-        // data.world
-        //     .add_resource(DebugLinesParams { line_width: 1.0 });
+        data.world.add_resource(AudioHandles::default());
+        data.world.add_resource(
+            data.data
+                .custom
+                .as_ref()
+                .expect("CustomData should exist")
+                .profiler
+                .clone(),
+        );
+
+        // Used by the collision debug overlay (development mode)
+        let debug_line_width = data.world.settings().debug_overlay.line_width;
+        data.world
+            .add_resource(DebugLines::new().with_capacity(200));
+        data.world.add_resource(DebugLinesParams {
+            line_width: debug_line_width,
+        });
 
         self.initialize_loading_text(&mut data);
+        self.started_at = Instant::now();
+        self.preload_assets(&mut data.world);
     }
 
     fn update(
@@ -103,8 +191,26 @@ impl<'a, 'b> State<CustomGameData<'a, 'b, CustomData>, StateEvent> for Startup {
     ) -> Trans<CustomGameData<'a, 'b, CustomData>, StateEvent> {
         data.data.update(&data.world, "startup").unwrap();
 
-        if self.is_finished_loading() {
-            let ingame = Box::new(MainMenu::default());
+        if self.is_finished_loading(&data.world) {
+            let cli = crate::cli::args();
+            let ingame = Box::new(
+                if cli.starts_ingame()
+                    && is_playable(
+                        &data.world,
+                        &cli.campaign.clone().unwrap_or_default(),
+                    )
+                {
+                    MainMenu::starting_ingame(
+                        Ingame::builder()
+                            .campaign(cli.campaign.clone().unwrap_or_default())
+                            .new_game(cli.new_game)
+                            .level(cli.level.clone())
+                            .build(),
+                    )
+                } else {
+                    MainMenu::default()
+                },
+            );
             // Remove loading text
             if let Some(entity) = self.loading_text_entity {
                 data.world
@@ -122,15 +228,27 @@ impl Default for Startup {
     fn default() -> Self {
         Self {
             loading_text_entity: None,
+            asset_tracker:       AssetTracker::new(),
+            started_at:          Instant::now(),
         }
     }
 }
 
-fn load_settings() -> Settings {
-    let settings_raw = read_file(resource("config/settings.ron"))
-        .expect("Couldn't read settings.ron file");
-    ron::Value::from_str(&settings_raw)
-        .unwrap()
-        .into_rust()
-        .unwrap()
+/// Returns `false` if the campaign is a mod pack, which can't be played.
+fn is_playable(world: &World, campaign: &CampaignType) -> bool {
+    match campaign {
+        CampaignType::Mod(id) => {
+            let is_playable =
+                world.read_resource::<ModPacks>().campaign(id).is_some();
+            if !is_playable {
+                error!(
+                    "Mod pack `{}` isn't installed or can't be played; \
+                     starting the main menu instead",
+                    id
+                );
+            }
+            is_playable
+        }
+        _ => true,
+    }
 }
@@ -53,12 +53,8 @@ impl WinGameMenu {
         let mut stats_texts = HashMap::<String, String>::new();
 
         let level_manager = {
-            let settings = data.world.settings();
-            let level_manager_settings = match self.campaign {
-                CampaignType::Normal => settings.level_manager.normal,
-                CampaignType::BonusA => settings.level_manager.bonus_a,
-                CampaignType::BonusB => settings.level_manager.bonus_b,
-            };
+            let level_manager_settings =
+                campaign_settings(&data.world, &self.campaign);
             LevelManager::new(&mut data, level_manager_settings, false)
         };
 
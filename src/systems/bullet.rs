@@ -11,6 +11,8 @@ impl<'a> System<'a> for BulletSystem {
         ReadStorage<'a, Collision>,
         ReadStorage<'a, Solid<SolidTag>>,
         ReadStorage<'a, Invincible>,
+        ReadStorage<'a, Transform>,
+        Write<'a, PlayerDamageEvents>,
         WriteStorage<'a, Bullet>,
         WriteStorage<'a, Player>,
         WriteStorage<'a, Enemy>,
@@ -25,6 +27,8 @@ impl<'a> System<'a> for BulletSystem {
             collisions,
             solids,
             invincibles,
+            transforms,
+            mut damage_events,
             mut bullets,
             mut players,
             mut enemies,
@@ -39,10 +43,21 @@ impl<'a> System<'a> for BulletSystem {
         {
             // Collides with player?
             if bullet.owner != BulletOwner::Player {
-                if let Some((player_entity, player, player_velocity, _)) =
-                    (&entities, &mut players, &mut velocities, !&invincibles)
-                        .join()
-                        .next()
+                if let Some((
+                    player_entity,
+                    player,
+                    player_transform,
+                    player_velocity,
+                    _,
+                )) = (
+                    &entities,
+                    &mut players,
+                    &transforms,
+                    &mut velocities,
+                    !&invincibles,
+                )
+                    .join()
+                    .next()
                 {
                     let player_id = player_entity.id();
                     if let Some(collision::Data {
@@ -52,7 +67,14 @@ impl<'a> System<'a> for BulletSystem {
                     {
                         // Bullet is colliding with player;
                         // deal damage to player and delete bullet entity.
+                        let health_before = player.health;
                         player.take_damage(bullet.damage);
+                        damage_events.push(
+                            DamageCause::Bullet,
+                            health_before,
+                            player,
+                            player_transform,
+                        );
                         // Knockback
                         let knockback_opt =
                             match (&bullet.knockback, &bullet.facing) {
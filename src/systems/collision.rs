@@ -0,0 +1,172 @@
+use deathframe::geo::Rect;
+
+use super::system_prelude::*;
+
+/// Entities within this distance around a checking entity are candidates.
+const QUERY_PADDING: f32 = 2.0;
+/// Rects, which are less than this far apart, touch.
+/// Solid entities may stop moving slightly before they would touch.
+const TOUCH_PADDING: f32 = 1.0;
+/// Rects, which overlap by at most this much, only touch.
+const OVERLAP_EPSILON: f32 = 0.01;
+
+/// Sets the `Collision` of every entity with `CheckCollision`,
+/// instead of `deathframe`'s `CollisionSystem`, which checks every entity
+/// against every other entity. Only the entities near each checking entity,
+/// found with the `EntityGrid`, are checked.
+/// Entities, which touch each other, collide on the side they touch;
+/// entities, which overlap, collide on the `Inner` side.
+/// Unloaded entities don't collide.
+pub struct GridCollisionSystem;
+
+impl<'a> System<'a> for GridCollisionSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Size>,
+        ReadStorage<'a, CheckCollision>,
+        ReadStorage<'a, Loadable>,
+        ReadStorage<'a, Loaded>,
+        Read<'a, EntityGrid>,
+        WriteStorage<'a, Collision>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            transforms,
+            sizes,
+            check_collisions,
+            loadables,
+            loadeds,
+            grid,
+            mut collisions,
+        ): Self::SystemData,
+    ) {
+        let is_loaded =
+            |entity: Entity| match (loadables.get(entity), loadeds.get(entity))
+            {
+                (None, None) | (Some(_), Some(_)) => true,
+                _ => false,
+            };
+        let size_of = |entity: Entity| {
+            sizes
+                .get(entity)
+                .map(|size| (size.w, size.h))
+                .unwrap_or((0.0, 0.0))
+        };
+        // The entities, which can be collided with.
+        let colliders = collisions.mask().clone();
+
+        for (entity, transform, collision, _) in
+            (&entities, &transforms, &mut collisions, &check_collisions).join()
+        {
+            if !is_loaded(entity) {
+                continue;
+            }
+
+            let pos = transform.translation();
+            let size = size_of(entity);
+            let rect = rect_around((pos.x, pos.y), size);
+            let nearby = grid.query(&rect_around(
+                (pos.x, pos.y),
+                (size.0 + QUERY_PADDING, size.1 + QUERY_PADDING),
+            ));
+
+            for other in nearby {
+                if other == entity
+                    || !colliders.contains(other.id())
+                    || !is_loaded(other)
+                {
+                    continue;
+                }
+                if let Some(other_transform) = transforms.get(other) {
+                    let other_pos = other_transform.translation();
+                    let other_rect =
+                        rect_around((other_pos.x, other_pos.y), size_of(other));
+                    if let Some(side) = collision_side(&rect, &other_rect) {
+                        collision.set_collision_with(other.id(), side);
+                    }
+                }
+            }
+
+            collision.update();
+        }
+    }
+}
+
+/// Returns the side of `rect`, which `other` touches,
+/// `Side::Inner` if they overlap, or `None` if they don't collide.
+/// Rects, which only touch at their corners, don't collide.
+fn collision_side(rect: &Rect, other: &Rect) -> Option<Side> {
+    let overlap = (
+        rect.right.min(other.right) - rect.left.max(other.left),
+        rect.top.min(other.top) - rect.bottom.max(other.bottom),
+    );
+    let overlapping = |overlap: f32| overlap > OVERLAP_EPSILON;
+    let touching =
+        |overlap: f32| overlap > -TOUCH_PADDING && overlap <= OVERLAP_EPSILON;
+    match (overlapping(overlap.0), overlapping(overlap.1)) {
+        (true, true) => Some(Side::Inner),
+        (true, false) if touching(overlap.1) => {
+            if other.top + other.bottom > rect.top + rect.bottom {
+                Some(Side::Top)
+            } else {
+                Some(Side::Bottom)
+            }
+        }
+        (false, true) if touching(overlap.0) => {
+            if other.left + other.right > rect.left + rect.right {
+                Some(Side::Right)
+            } else {
+                Some(Side::Left)
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side_of(other_pos: (f32, f32)) -> Option<Side> {
+        collision_side(
+            &rect_around((0.0, 0.0), (32.0, 32.0)),
+            &rect_around(other_pos, (32.0, 32.0)),
+        )
+    }
+
+    #[test]
+    fn touching_sides() {
+        match (
+            side_of((0.0, 32.0)),
+            side_of((0.0, -32.5)),
+            side_of((-32.0, 8.0)),
+            side_of((32.0, -8.0)),
+        ) {
+            (
+                Some(Side::Top),
+                Some(Side::Bottom),
+                Some(Side::Left),
+                Some(Side::Right),
+            ) => (),
+            _ => panic!("Touching rects should collide on the touched side"),
+        }
+    }
+
+    #[test]
+    fn overlapping_is_inner() {
+        match (side_of((8.0, 8.0)), side_of((0.0, 0.0))) {
+            (Some(Side::Inner), Some(Side::Inner)) => (),
+            _ => panic!("Overlapping rects should collide on the inner side"),
+        }
+    }
+
+    #[test]
+    fn apart_or_corners_dont_collide() {
+        assert!(side_of((0.0, 34.0)).is_none());
+        assert!(side_of((32.0, 32.0)).is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use super::system_prelude::*;
+use crate::analytics::{append_records, DamageRecord};
+
+/// Writes the damage the player took this frame to the analytics file.
+pub struct DamageAnalyticsSystem;
+
+impl<'a> System<'a> for DamageAnalyticsSystem {
+    type SystemData = (
+        ReadExpect<'a, Settings>,
+        Read<'a, CurrentCampaign>,
+        Read<'a, CurrentLevelName>,
+        Write<'a, PlayerDamageEvents>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            settings,
+            current_campaign,
+            current_level_name,
+            mut damage_events,
+        ): Self::SystemData,
+    ) {
+        if damage_events.0.is_empty() {
+            return;
+        }
+        let damages = damage_events.0.drain(..).collect::<Vec<_>>();
+
+        if let (true, Some(level)) =
+            (settings.analytics.enabled, current_level_name.0.as_ref())
+        {
+            let records = damages
+                .into_iter()
+                .map(|damage| {
+                    DamageRecord::new(
+                        current_campaign.0.clone(),
+                        level.clone(),
+                        damage,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if let Err(err) = append_records(&settings.analytics, &records) {
+                error!("Couldn't record damage analytics: {}", err);
+            }
+        }
+    }
+}
@@ -20,7 +20,7 @@ impl<'a> System<'a> for DebugSystem {
         {
             let fps_frame = fps_counter.frame_fps();
             let fps_avg = fps_counter.sampled_fps();
-            println!("this_frame: {:.02} average: {:.02}", fps_frame, fps_avg,);
+            info!("this_frame: {:.02} average: {:.02}", fps_frame, fps_avg,);
             self.last_fps_print = now;
         }
     }
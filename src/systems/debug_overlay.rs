@@ -0,0 +1,183 @@
+use super::system_prelude::*;
+
+const ACTION_TOGGLE: &str = "debug_overlay_toggle";
+
+/// Draws the collision rectangles of all (loaded) entities, enemies' trigger
+/// distances, loaders' load distances and the camera's inner rectangle and deadzone,
+/// using `DebugLines`. Toggled with the `debug_overlay_toggle` binding.
+/// Only used in development mode.
+#[derive(Default)]
+pub struct DebugOverlaySystem {
+    enabled: bool,
+}
+
+impl<'a> System<'a> for DebugOverlaySystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Settings>,
+        Read<'a, InputManager>,
+        WriteExpect<'a, DebugLines>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Size>,
+        ReadStorage<'a, InnerSize>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Loader>,
+        ReadStorage<'a, Loadable>,
+        ReadStorage<'a, Loaded>,
+        ReadStorage<'a, Solid<SolidTag>>,
+        ReadStorage<'a, Harmful>,
+        ReadStorage<'a, Enemy>,
+        ReadStorage<'a, PlayerAttack>,
+        ReadStorage<'a, Bullet>,
+        ReadStorage<'a, Goal>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            settings,
+            input_manager,
+            mut debug_lines,
+            transforms,
+            sizes,
+            inner_sizes,
+            cameras,
+            loaders,
+            loadables,
+            loadeds,
+            solids,
+            harmfuls,
+            enemies,
+            player_attacks,
+            bullets,
+            goals,
+        ): Self::SystemData,
+    ) {
+        if input_manager.is_up(ACTION_TOGGLE) {
+            self.enabled = !self.enabled;
+            info!(
+                "debug overlay: {}",
+                if self.enabled { "ENABLED" } else { "DISABLED" }
+            );
+        }
+        if !self.enabled {
+            return;
+        }
+
+        let overlay = &settings.debug_overlay;
+        let mut draw = |center: (f32, f32), half_size: (f32, f32), color| {
+            draw_debug_rect(
+                &mut debug_lines,
+                overlay.z,
+                center,
+                half_size,
+                color,
+            );
+        };
+
+        // Collision rectangles
+        for (entity, transform, size, _) in
+            (&entities, &transforms, &sizes, !&cameras).join()
+        {
+            if loadables.contains(entity) && !loadeds.contains(entity) {
+                continue;
+            }
+
+            let color = if player_attacks.contains(entity) {
+                overlay.color_player_attack
+            } else if bullets.contains(entity) {
+                overlay.color_bullet
+            } else if enemies.contains(entity) {
+                overlay.color_enemy
+            } else if goals.contains(entity) {
+                overlay.color_goal
+            } else if harmfuls.contains(entity) {
+                overlay.color_harmful
+            } else if let Some(solid) = solids.get(entity) {
+                match solid.tag {
+                    SolidTag::Default => overlay.color_solid,
+                    SolidTag::Player => overlay.color_solid_player,
+                    SolidTag::Enemy => overlay.color_solid_enemy,
+                    SolidTag::Noclip => overlay.color_solid_noclip,
+                }
+            } else {
+                overlay.color_default
+            };
+
+            let pos = transform.translation();
+            draw((pos.x, pos.y), (size.w * 0.5, size.h * 0.5), color);
+
+            // Enemy trigger distances
+            if let Some(enemy) = enemies.get(entity) {
+                draw(
+                    (pos.x, pos.y),
+                    (
+                        enemy.trigger_distance.0.abs(),
+                        enemy.trigger_distance.1.abs(),
+                    ),
+                    overlay.color_trigger_distance,
+                );
+            }
+        }
+
+        // Load distances, for an entity without a size;
+        // see the `LoaderSystem` for how they are calculated.
+        for (camera_opt, loader, transform, size_opt) in
+            (cameras.maybe(), &loaders, &transforms, sizes.maybe()).join()
+        {
+            let pos = center_of(transform, camera_opt.and(size_opt));
+            let padding = loader.padding.unwrap_or(Vector::new(0.0, 0.0));
+            let distance = match (loader.distance, size_opt) {
+                (Some(distance), _) => distance,
+                (None, Some(size)) => Vector::new(size.w * 0.5, size.h * 0.5),
+                (None, None) => continue,
+            };
+            let enemy_distance =
+                (distance.0 + padding.0, distance.1 + padding.1);
+            let difference =
+                settings.entity_loader.enemy_load_distance_difference;
+            draw(pos, enemy_distance, overlay.color_load_distance);
+            draw(
+                pos,
+                (
+                    enemy_distance.0 + difference.0,
+                    enemy_distance.1 + difference.1,
+                ),
+                overlay.color_load_distance,
+            );
+        }
+
+        // Camera inner rectangle and deadzone
+        for (_, transform, size, inner_size_opt) in
+            (&cameras, &transforms, &sizes, inner_sizes.maybe()).join()
+        {
+            let pos = center_of(transform, Some(size));
+            if let Some(inner_size) = inner_size_opt {
+                draw(
+                    pos,
+                    (inner_size.0.w * 0.5, inner_size.0.h * 0.5),
+                    overlay.color_camera_inner,
+                );
+            }
+            draw(
+                pos,
+                (settings.camera.deadzone.0, settings.camera.deadzone.1),
+                overlay.color_camera_deadzone,
+            );
+        }
+    }
+}
+
+/// Returns the center position of the entity.
+/// The camera's position's origin is bottom-left, so its size needs to be passed.
+fn center_of(
+    transform: &Transform,
+    camera_size_opt: Option<&Size>,
+) -> (f32, f32) {
+    let pos = transform.translation();
+    match camera_size_opt {
+        None => (pos.x, pos.y),
+        Some(size) => (pos.x + size.w * 0.5, pos.y + size.h * 0.5),
+    }
+}
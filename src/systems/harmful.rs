@@ -2,6 +2,9 @@ use super::system_prelude::*;
 use crate::settings::Settings;
 use deathframe::geo::Vector;
 
+/// Harmful entities within this distance around a harmable entity are checked.
+const QUERY_PADDING: f32 = 2.0;
+
 pub struct HarmfulSystem;
 
 impl<'a> System<'a> for HarmfulSystem {
@@ -9,12 +12,15 @@ impl<'a> System<'a> for HarmfulSystem {
         ReadExpect<'a, Settings>,
         Entities<'a>,
         ReadStorage<'a, Transform>,
+        ReadStorage<'a, Size>,
         ReadStorage<'a, Harmful>,
         ReadStorage<'a, Harmable>,
         ReadStorage<'a, Collision>,
         ReadStorage<'a, Loadable>,
         ReadStorage<'a, Loaded>,
         ReadStorage<'a, Invincible>,
+        Write<'a, PlayerDamageEvents>,
+        Read<'a, EntityGrid>,
         WriteStorage<'a, Player>,
         WriteStorage<'a, Enemy>,
         WriteStorage<'a, Velocity>,
@@ -26,12 +32,15 @@ impl<'a> System<'a> for HarmfulSystem {
             settings,
             entities,
             transforms,
+            sizes,
             harmfuls,
             harmables,
             collisions,
             loadables,
             loadeds,
             invincibles,
+            mut damage_events,
+            grid,
             mut players,
             mut enemies,
             mut velocities,
@@ -43,6 +52,8 @@ impl<'a> System<'a> for HarmfulSystem {
         for (
             entity_harmable,
             collision_harmable,
+            transform_harmable,
+            size_harmable_opt,
             _,
             loadable_opt,
             loaded_opt,
@@ -50,6 +61,8 @@ impl<'a> System<'a> for HarmfulSystem {
         ) in (
             &entities,
             &collisions,
+            &transforms,
+            sizes.maybe(),
             &harmables,
             loadables.maybe(),
             loadeds.maybe(),
@@ -62,8 +75,25 @@ impl<'a> System<'a> for HarmfulSystem {
             {
                 let harmable_id = entity_harmable.id();
 
+                // Only harmful entities touching the harmable can collide with it.
+                let pos = transform_harmable.translation();
+                let size = size_harmable_opt
+                    .map(|size| (size.w, size.h))
+                    .unwrap_or((0.0, 0.0));
+                let nearby = grid.query(&rect_around(
+                    (pos.x, pos.y),
+                    (size.0 + QUERY_PADDING, size.1 + QUERY_PADDING),
+                ));
+
                 for (entity_harmful, harmful, transform_harmful) in
-                    (&entities, &harmfuls, &transforms).join()
+                    nearby.into_iter().filter_map(|entity| {
+                        match (harmfuls.get(entity), transforms.get(entity)) {
+                            (Some(harmful), Some(transform)) => {
+                                Some((entity, harmful, transform))
+                            }
+                            _ => None,
+                        }
+                    })
                 {
                     let harmful_id = entity_harmful.id();
 
@@ -87,6 +117,7 @@ impl<'a> System<'a> for HarmfulSystem {
                                 &mut enemies,
                                 &mut velocities,
                             ),
+                            &mut damage_events,
                             transform_harmful.into(),
                             harmable_id,
                             harmful.damage,
@@ -107,6 +138,7 @@ fn deal_damage_to(
         &mut WriteStorage<Enemy>,
         &mut WriteStorage<Velocity>,
     ),
+    damage_events: &mut PlayerDamageEvents,
     harmful_pos: Vector,
     target_id: Index,
     damage: u32,
@@ -146,7 +178,14 @@ fn deal_damage_to(
                 (player, transform, velocity)
             })
     {
+        let health_before = player.health;
         player.take_damage(damage);
+        damage_events.push(
+            DamageCause::HarmfulTile,
+            health_before,
+            player,
+            transform,
+        );
         if let Some(velocity) = velocity_opt {
             apply_knockback(
                 harmful_pos,
@@ -1,6 +1,8 @@
 use std::ops::Deref;
 
+use amethyst::core::nalgebra::Point3;
 use amethyst::ecs::storage::{MaskedStorage, Storage};
+use amethyst::renderer::Rgba;
 use deathframe::components::solid::SolidTag as _;
 
 use super::system_prelude::*;
@@ -220,3 +222,31 @@ pub fn create_heart<'a, 'b>(
 
     entity
 }
+
+/// Draws the outline of the rectangle with the given center and half size.
+pub fn draw_debug_rect(
+    debug_lines: &mut DebugLines,
+    z: f32,
+    center: (f32, f32),
+    half_size: (f32, f32),
+    color: [f32; 4],
+) {
+    let color = Rgba::from(color);
+    let left = center.0 - half_size.0;
+    let right = center.0 + half_size.0;
+    let bottom = center.1 - half_size.1;
+    let top = center.1 + half_size.1;
+    let corners = [
+        Point3::new(left, bottom, z),
+        Point3::new(right, bottom, z),
+        Point3::new(right, top, z),
+        Point3::new(left, top, z),
+    ];
+    for i in 0 .. corners.len() {
+        debug_lines.draw_line(
+            corners[i],
+            corners[(i + 1) % corners.len()],
+            color,
+        );
+    }
+}
@@ -1,9 +1,12 @@
+use std::collections::HashSet;
+
 use super::system_prelude::*;
 
 #[derive(Default)]
 pub struct LoaderSystem;
 
 /// Loads loadable entities when they are within the camera.
+/// Nearby entities are found with the `EntityGrid`.
 impl<'a> System<'a> for LoaderSystem {
     type SystemData = (
         ReadExpect<'a, Settings>,
@@ -17,6 +20,7 @@ impl<'a> System<'a> for LoaderSystem {
         ReadStorage<'a, Enemy>,
         ReadStorage<'a, Loadable>,
         WriteStorage<'a, Loaded>,
+        Read<'a, EntityGrid>,
     );
 
     fn run(
@@ -33,6 +37,7 @@ impl<'a> System<'a> for LoaderSystem {
             enemies,
             loadables,
             mut loadeds,
+            grid,
         ): Self::SystemData,
     ) {
         // Don't do anything if level is loading.
@@ -60,16 +65,58 @@ impl<'a> System<'a> for LoaderSystem {
                 }
             };
 
-            for (entity, transform, size_opt, _, loaded_opt, enemy_opt) in (
-                &entities,
-                &transforms,
-                sizes.maybe(),
-                &loadables,
-                loadeds.maybe(),
-                enemies.maybe(),
-            )
-                .join()
-            {
+            // Only entities near the loader can be loaded, so only those are
+            // queried from the grid; loaded entities are also checked, so they can be unloaded.
+            let query_distance = {
+                let loader_padding = loader
+                    .padding
+                    .as_ref()
+                    .cloned()
+                    .unwrap_or(Vector::new(0.0, 0.0));
+                let difference =
+                    settings.entity_loader.enemy_load_distance_difference;
+                let base = match (loader.distance.as_ref(), loader_size_opt) {
+                    (Some(distance), _) => (distance.0, distance.1),
+                    (None, Some(loader_size)) => {
+                        (loader_size.w * 0.5, loader_size.h * 0.5)
+                    }
+                    (None, None) => (0.0, 0.0),
+                };
+                (
+                    base.0 + loader_padding.0 + difference.0.max(0.0),
+                    base.1 + loader_padding.1 + difference.1.max(0.0),
+                )
+            };
+            let mut candidates = grid.query(&rect_around(
+                loader_pos,
+                (query_distance.0 * 2.0, query_distance.1 * 2.0),
+            ));
+            let queried: HashSet<Entity> = candidates.iter().cloned().collect();
+            candidates.extend(
+                (&entities, &loadeds)
+                    .join()
+                    .map(|(entity, _)| entity)
+                    .filter(|entity| !queried.contains(entity)),
+            );
+
+            for entity in candidates {
+                let (transform, size_opt, loaded_opt, enemy_opt) = match (
+                    transforms.get(entity),
+                    sizes.get(entity),
+                    loadables.get(entity),
+                    loadeds.get(entity),
+                    enemies.get(entity),
+                ) {
+                    (
+                        Some(transform),
+                        size_opt,
+                        Some(_),
+                        loaded_opt,
+                        enemy_opt,
+                    ) => (transform, size_opt, loaded_opt, enemy_opt),
+                    _ => continue,
+                };
+
                 let size =
                     size_opt.map(|s| s.into()).unwrap_or(Vector::new(0.0, 0.0));
                 let loader_padding_default = Vector::new(0.0, 0.0);
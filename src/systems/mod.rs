@@ -1,6 +1,9 @@
 mod bullet;
 mod bullet_creator;
+mod collision;
+mod damage_analytics;
 mod debug;
+mod debug_overlay;
 mod enemy_ai;
 mod goal;
 mod harmful;
@@ -12,6 +15,7 @@ mod player_attack;
 mod player_controls;
 mod player_dash;
 mod player_take_damage;
+mod spatial_grid;
 mod sync_hearts_containers_with_health;
 mod timer;
 
@@ -20,7 +24,10 @@ pub mod prelude {
 
     pub use super::bullet::BulletSystem;
     pub use super::bullet_creator::BulletCreatorSystem;
+    pub use super::collision::GridCollisionSystem;
+    pub use super::damage_analytics::DamageAnalyticsSystem;
     pub use super::debug::DebugSystem;
+    pub use super::debug_overlay::DebugOverlaySystem;
     pub use super::enemy_ai::EnemyAiSystem;
     pub use super::goal::GoalSystem;
     pub use super::harmful::HarmfulSystem;
@@ -32,6 +39,7 @@ pub mod prelude {
     pub use super::player_controls::PlayerControlsSystem;
     pub use super::player_dash::PlayerDashSystem;
     pub use super::player_take_damage::PlayerTakeDamageSystem;
+    pub use super::spatial_grid::SpatialGridSystem;
     pub use super::sync_hearts_containers_with_health::SyncHeartsContainersWithHealthSystem;
     pub use super::timer::TimerSystem;
 }
@@ -44,14 +52,17 @@ mod system_prelude {
     pub use deathframe::systems::system_prelude::*;
 
     pub use super::helpers::*;
+    pub use crate::analytics::prelude::*;
     pub use crate::bullet_creator::prelude::*;
     pub use crate::components::helpers as component_helpers;
     pub use crate::components::prelude::*;
     pub use crate::settings::prelude::*;
     pub use crate::solid_tag::SolidTag;
+    pub use crate::spatial_grid::{rect_around, EntityGrid};
     pub use crate::states::helpers::*;
 }
 
 mod helpers;
 
+pub use helpers::{draw_debug_rect, SidesTouching};
 pub use prelude::*;
@@ -56,7 +56,7 @@ impl<'a> System<'a> for NoclipSystem {
             if input_manager.is_up("noclip_toggle") {
                 if is_noclip {
                     // DISABLE NOCLIP
-                    eprintln!("noclip: DISABLED");
+                    info!("noclip: DISABLED");
                     is_noclip = false;
                     noclips.remove(player_entity).unwrap();
                     if !gravities.contains(player_entity) {
@@ -83,7 +83,7 @@ impl<'a> System<'a> for NoclipSystem {
                     }
                 } else {
                     // ENABLE NOCLIP
-                    eprintln!("noclip: ENABLED");
+                    info!("noclip: ENABLED");
                     is_noclip = true;
                     noclips.insert(player_entity, Noclip::default()).unwrap();
                     if gravities.contains(player_entity) {
@@ -134,8 +134,8 @@ impl<'a> System<'a> for NoclipSystem {
                         player_velocity.x = 0.0;
                     }
                 } else {
-                    eprintln!(
-                        "WARNING: `noclip_x` axis is not defined in \
+                    warn!(
+                        "`noclip_x` axis is not defined in \
                          bindings.ron"
                     );
                 }
@@ -159,8 +159,8 @@ impl<'a> System<'a> for NoclipSystem {
                         player_velocity.y = 0.0;
                     }
                 } else {
-                    eprintln!(
-                        "WARNING: `noclip_y` axis is not defined in \
+                    warn!(
+                        "`noclip_y` axis is not defined in \
                          bindings.ron"
                     );
                 }
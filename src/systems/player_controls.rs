@@ -12,6 +12,7 @@ impl<'a> System<'a> for PlayerControlsSystem {
         Read<'a, CurrentLevelName>,
         Write<'a, BulletCreator>,
         Write<'a, Stats>,
+        Write<'a, PlayerDamageEvents>,
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Collision>,
         ReadStorage<'a, Solid<SolidTag>>,
@@ -37,6 +38,7 @@ impl<'a> System<'a> for PlayerControlsSystem {
             current_level_name,
             mut bullet_creator,
             mut stats,
+            mut damage_events,
             transforms,
             collisions,
             solids,
@@ -108,7 +110,14 @@ impl<'a> System<'a> for PlayerControlsSystem {
 
                 // Kill the player, if they fall below the death_floor
                 if transform.translation().y < settings.death_floor {
+                    let health_before = player.health;
                     player.health = 0;
+                    damage_events.push(
+                        DamageCause::DeathFloor,
+                        health_before,
+                        player,
+                        transform,
+                    );
                 }
             }
 
@@ -10,6 +10,7 @@ impl<'a> System<'a> for PlayerTakeDamageSystem {
         ReadStorage<'a, Enemy>,
         ReadStorage<'a, NoAttack>,
         ReadStorage<'a, Invincible>,
+        Write<'a, PlayerDamageEvents>,
         WriteStorage<'a, Player>,
         WriteStorage<'a, Velocity>,
         WriteStorage<'a, AnimationsContainer>,
@@ -24,6 +25,7 @@ impl<'a> System<'a> for PlayerTakeDamageSystem {
             enemies,
             no_attacks,
             invincibles,
+            mut damage_events,
             mut players,
             mut velocities,
             mut animations_containers,
@@ -58,7 +60,14 @@ impl<'a> System<'a> for PlayerTakeDamageSystem {
                     }) = player_collision.collision_with(enemy_id)
                     {
                         // Take damage
+                        let health_before = player.health;
                         enemy.deal_damage_to(player);
+                        damage_events.push(
+                            DamageCause::enemy(&enemy.enemy_type),
+                            health_before,
+                            player,
+                            player_transform,
+                        );
 
                         // Knockback
                         // Figure out which direction to knock the player into by comparing the
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use amethyst::ecs::storage::ComponentEvent;
+use amethyst::ecs::world::Index;
+use amethyst::ecs::{BitSet, ReaderId, Resources, SystemData};
+
+use super::system_prelude::*;
+
+/// Keeps the `EntityGrid` up to date with every entity's `Transform` and `Size`.
+/// Only entities whose `Transform` or `Size` was inserted, changed or removed
+/// are updated, so static entities (like tiles) cost nothing after they were inserted.
+#[derive(Default)]
+pub struct SpatialGridSystem {
+    transform_events: Option<ReaderId<ComponentEvent>>,
+    /// Sizes can change on their own, for example in the entity inspector.
+    size_events:      Option<ReaderId<ComponentEvent>>,
+    /// The entity in the grid for every entity index;
+    /// `ComponentEvent`s only have the index.
+    tracked:          HashMap<Index, Entity>,
+    /// If every entity needs to be (re)inserted; set initially,
+    /// and when the cell size changed, which empties the grid.
+    needs_rebuild:    bool,
+}
+
+impl<'a> System<'a> for SpatialGridSystem {
+    type SystemData = (
+        ReadExpect<'a, Settings>,
+        Entities<'a>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Size>,
+        Write<'a, EntityGrid>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.transform_events =
+            Some(WriteStorage::<Transform>::fetch(res).register_reader());
+        self.size_events =
+            Some(WriteStorage::<Size>::fetch(res).register_reader());
+        self.needs_rebuild = true;
+    }
+
+    fn run(
+        &mut self,
+        (settings, entities, transforms, sizes, mut grid): Self::SystemData,
+    ) {
+        let mut changed = BitSet::new();
+        let mut removed = BitSet::new();
+        for event in transforms.channel().read(
+            self.transform_events
+                .as_mut()
+                .expect("SpatialGridSystem should be set up"),
+        ) {
+            match event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    changed.add(*id);
+                }
+                ComponentEvent::Removed(id) => {
+                    removed.add(*id);
+                }
+            }
+        }
+        // A removed size only shrinks the entity's rect;
+        // entities without a transform aren't in the grid anyway.
+        for event in sizes.channel().read(
+            self.size_events
+                .as_mut()
+                .expect("SpatialGridSystem should be set up"),
+        ) {
+            match event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => {
+                    changed.add(*id);
+                }
+            }
+        }
+
+        if grid.set_cell_size(settings.entity_loader.grid_cell_size) {
+            self.needs_rebuild = true;
+        }
+        if self.needs_rebuild {
+            self.needs_rebuild = false;
+            grid.clear();
+            self.tracked.clear();
+            for (entity, _) in (&entities, &transforms).join() {
+                changed.add(entity.id());
+            }
+        }
+
+        // Removed transforms (including deleted entities), unless they were
+        // inserted again for a new entity with the same index.
+        for id in (&removed).join() {
+            if let Some(entity) = self.tracked.remove(&id) {
+                grid.remove(entity);
+            }
+        }
+
+        for (entity, transform, size_opt, _) in
+            (&entities, &transforms, sizes.maybe(), &changed).join()
+        {
+            if let Some(old_entity) = self.tracked.insert(entity.id(), entity) {
+                if old_entity != entity {
+                    grid.remove(old_entity);
+                }
+            }
+            let pos = transform.translation();
+            let size =
+                size_opt.map(|size| (size.w, size.h)).unwrap_or((0.0, 0.0));
+            grid.update(entity, &rect_around((pos.x, pos.y), size));
+        }
+    }
+}
@@ -51,9 +51,9 @@ impl<'a> System<'a> for TimerSystem {
 
 impl TimerSystem {
     fn print_to_stdout(&self, timers: &Timers) {
-        println!("level: {}", timers.level.time_output());
+        info!("level: {}", timers.level.time_output());
         if let Some(global_timer) = timers.global.as_ref() {
-            println!("global: {}", global_timer.time_output());
+            info!("global: {}", global_timer.time_output());
         }
     }
 }
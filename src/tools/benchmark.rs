@@ -0,0 +1,232 @@
+//! Benchmarks the `EntityGrid`'s spatial hash against checking every entity,
+//! on the largest level of all campaigns.
+//! A camera is moved across the level, and every frame the entities to load
+//! (like the `LoaderSystem`) and the harmful tiles touching the player
+//! (like the `HarmfulSystem`) are found, once with and once without the grid.
+//! The level's objects (the player, enemies and items) move every frame,
+//! so the grid's time includes updating them, like the `SpatialGridSystem`.
+
+use std::time::{Duration, Instant};
+
+use deathframe::geo::Rect;
+
+use super::level_file::LevelFile;
+use crate::resource_helpers::resource;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+use crate::spatial_grid::{rect_around, SpatialGrid};
+
+/// Amount of simulated frames.
+const FRAMES: u32 = 2000;
+/// The window size, which the camera's size is based on.
+const WINDOW_SIZE: (f32, f32) = (1280.0, 720.0);
+/// Moving entities move to the right by up to this distance, then start over.
+const MOVE_DISTANCE: f32 = 128.0;
+
+/// An entity of the level, as the grid sees it.
+struct BenchEntity {
+    pos:     (f32, f32),
+    size:    (f32, f32),
+    harmful: bool,
+    moving:  bool,
+}
+
+impl BenchEntity {
+    /// Returns the entity's rect at the given frame.
+    fn rect_at(&self, frame: usize) -> Rect {
+        if self.moving {
+            let offset = (frame % MOVE_DISTANCE as usize) as f32;
+            rect_around((self.pos.0 + offset, self.pos.1), self.size)
+        } else {
+            rect_around(self.pos, self.size)
+        }
+    }
+}
+
+pub fn run() -> Result<(), String> {
+    let settings = load_settings()?;
+
+    // Find the level with the most entities.
+    let mut largest: Option<(String, LevelFile, (f32, f32))> = None;
+    for (_, campaign_settings) in super::campaigns(&settings) {
+        for level_name in &campaign_settings.level_names {
+            let level = LevelFile::load(&resource(format!(
+                "{}/{}",
+                campaign_settings.levels_dir, level_name
+            )))?;
+            let count = level.tiles.len() + level.objects.len();
+            let is_larger = largest.as_ref().map_or(true, |(_, largest, _)| {
+                count > largest.tiles.len() + largest.objects.len()
+            });
+            if is_larger {
+                let tile_size = campaign_settings.tile_size;
+                largest = Some((
+                    level_name.clone(),
+                    level,
+                    (tile_size.0, tile_size.1),
+                ));
+            }
+        }
+    }
+    let (level_name, level, tile_size) =
+        largest.ok_or_else(|| "There are no levels".to_string())?;
+
+    let entities = level_entities(&level, tile_size);
+    let camera_size = (
+        WINDOW_SIZE.0 * settings.camera.size_mult.0,
+        WINDOW_SIZE.1 * settings.camera.size_mult.1,
+    );
+    let padding = settings.camera.loader_padding;
+    let difference = settings.entity_loader.enemy_load_distance_difference;
+    let load_distance = (
+        camera_size.0 * 0.5 + padding.0 + difference.0.max(0.0),
+        camera_size.1 * 0.5 + padding.1 + difference.1.max(0.0),
+    );
+    let player_size = settings.player.size;
+    let player_size = (player_size.0, player_size.1);
+    let path = camera_path(&level);
+
+    let moving: Vec<usize> = entities
+        .iter()
+        .enumerate()
+        .filter(|(_, entity)| entity.moving)
+        .map(|(index, _)| index)
+        .collect();
+
+    println!(
+        "Level {}: {} entities ({} harmful, {} moving), {} frames",
+        level_name,
+        entities.len(),
+        entities.iter().filter(|entity| entity.harmful).count(),
+        moving.len(),
+        FRAMES
+    );
+
+    // Without the grid: check every entity, every frame.
+    let start = Instant::now();
+    let mut brute_counts = (0, 0);
+    for (frame, &pos) in path.iter().enumerate() {
+        let loader_rect = rect_around(pos, load_distance_size(load_distance));
+        let player_rect = rect_around(pos, player_size);
+        for entity in &entities {
+            let rect = entity.rect_at(frame);
+            if overlaps(&loader_rect, &rect) {
+                brute_counts.0 += 1;
+            }
+            if entity.harmful && overlaps(&player_rect, &rect) {
+                brute_counts.1 += 1;
+            }
+        }
+    }
+    let brute_time = start.elapsed();
+
+    // With the grid: insert every entity once (when the level is loaded),
+    // then update the moving entities and query nearby entities every frame.
+    let start = Instant::now();
+    let mut grid = SpatialGrid::new(settings.entity_loader.grid_cell_size);
+    for (index, entity) in entities.iter().enumerate() {
+        grid.update(index, &entity.rect_at(0));
+    }
+    let build_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut grid_counts = (0, 0);
+    for (frame, &pos) in path.iter().enumerate() {
+        for &index in &moving {
+            grid.update(index, &entities[index].rect_at(frame));
+        }
+        let loader_rect = rect_around(pos, load_distance_size(load_distance));
+        let player_rect = rect_around(pos, player_size);
+        for index in grid.query(&loader_rect) {
+            if overlaps(&loader_rect, &entities[index].rect_at(frame)) {
+                grid_counts.0 += 1;
+            }
+        }
+        for index in grid.query(&player_rect) {
+            let entity = &entities[index];
+            if entity.harmful && overlaps(&player_rect, &entity.rect_at(frame))
+            {
+                grid_counts.1 += 1;
+            }
+        }
+    }
+    let grid_time = start.elapsed();
+
+    if brute_counts != grid_counts {
+        return Err(format!(
+            "The grid found different entities than checking every entity: \
+             {:?} (loaded, harmful) instead of {:?}",
+            grid_counts, brute_counts
+        ));
+    }
+
+    println!(
+        "Without grid: {:.3} ms/frame",
+        millis(brute_time) / f64::from(FRAMES)
+    );
+    println!(
+        "With grid:    {:.3} ms/frame, including updating moving entities \
+         (+ {:.3} ms to build the grid when the level is loaded)",
+        millis(grid_time) / f64::from(FRAMES),
+        millis(build_time)
+    );
+    println!(
+        "Speedup:      {:.1}x",
+        millis(brute_time) / millis(grid_time).max(std::f64::EPSILON)
+    );
+    Ok(())
+}
+
+/// Returns the tiles and objects of the level, with their centers and sizes.
+fn level_entities(
+    level: &LevelFile,
+    tile_size: (f32, f32),
+) -> Vec<BenchEntity> {
+    let tiles = level.tiles.iter().map(|tile| BenchEntity {
+        pos:     (
+            tile.pos.0 + tile_size.0 * 0.5,
+            tile.pos.1 - tile_size.1 * 0.5,
+        ),
+        size:    tile_size,
+        harmful: tile.is_harmful(),
+        moving:  false,
+    });
+    let objects = level.objects.iter().map(|object| BenchEntity {
+        pos:     (
+            object.pos.0 + object.size.0 * 0.5,
+            object.pos.1 - object.size.1 * 0.5,
+        ),
+        size:    object.size,
+        harmful: false,
+        moving:  true,
+    });
+    tiles.chain(objects).collect()
+}
+
+/// Returns the camera's center for every frame: it moves from the left
+/// to the right of the level, at the height of the player's start position.
+fn camera_path(level: &LevelFile) -> Vec<(f32, f32)> {
+    let y = level
+        .object("Player")
+        .map(|player| player.pos.1)
+        .unwrap_or(level.size.1 * 0.5);
+    (0 .. FRAMES)
+        .map(|frame| (level.size.0 * frame as f32 / FRAMES as f32, y))
+        .collect()
+}
+
+fn load_distance_size(load_distance: (f32, f32)) -> (f32, f32) {
+    (load_distance.0 * 2.0, load_distance.1 * 2.0)
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.left <= b.right
+        && a.right >= b.left
+        && a.bottom <= b.top
+        && a.top >= b.bottom
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0
+        + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
@@ -0,0 +1,166 @@
+//! An RGB image of a level, which is drawn to in level coordinates.
+
+use image::{Rgb, RgbImage};
+
+use super::spritesheet::Spritesheet;
+
+pub struct Canvas {
+    image:        RgbImage,
+    level_height: f32,
+    scale:        f32,
+}
+
+impl Canvas {
+    /// `scale` is the amount of pixels per level unit.
+    pub fn new(
+        level_size: (f32, f32),
+        scale: f32,
+        background: [u8; 3],
+    ) -> Self {
+        let width = ((level_size.0 * scale).ceil() as u32).max(1);
+        let height = ((level_size.1 * scale).ceil() as u32).max(1);
+        Self {
+            image:        RgbImage::from_pixel(width, height, Rgb(background)),
+            level_height: level_size.1,
+            scale:        scale,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Converts level coordinates (y points up) to pixel coordinates (y points down).
+    pub fn to_pixel(&self, pos: (f32, f32)) -> (f32, f32) {
+        (pos.0 * self.scale, (self.level_height - pos.1) * self.scale)
+    }
+
+    /// Fills the rectangle with the given top-left corner and size,
+    /// in level coordinates.
+    pub fn fill_rect(
+        &mut self,
+        top_left: (f32, f32),
+        size: (f32, f32),
+        color: [u8; 3],
+    ) {
+        let (left, top) = self.to_pixel(top_left);
+        let right = left + size.0 * self.scale;
+        let bottom = top + size.1 * self.scale;
+        for y in (top.floor() as i64) .. (bottom.ceil() as i64) {
+            for x in (left.floor() as i64) .. (right.ceil() as i64) {
+                self.put_pixel(x, y, color);
+            }
+        }
+    }
+
+    /// Draws the outline of the rectangle with the given top-left corner
+    /// and size, in level coordinates.
+    pub fn outline_rect(
+        &mut self,
+        top_left: (f32, f32),
+        size: (f32, f32),
+        color: [u8; 3],
+    ) {
+        let (left, top) = self.to_pixel(top_left);
+        let left = left.floor() as i64;
+        let top = top.floor() as i64;
+        let right = left + ((size.0 * self.scale).ceil() as i64).max(1) - 1;
+        let bottom = top + ((size.1 * self.scale).ceil() as i64).max(1) - 1;
+        for x in left ..= right {
+            self.put_pixel(x, top, color);
+            self.put_pixel(x, bottom, color);
+        }
+        for y in top ..= bottom {
+            self.put_pixel(left, y, color);
+            self.put_pixel(right, y, color);
+        }
+    }
+
+    /// Draws an `X` centered on the given position, in level coordinates.
+    pub fn cross(&mut self, center: (f32, f32), size: u32, color: [u8; 3]) {
+        let (x, y) = self.to_pixel(center);
+        let (x, y) = (x as i64, y as i64);
+        let half = (size / 2) as i64;
+        for offset in -half ..= half {
+            self.put_pixel(x + offset, y + offset, color);
+            self.put_pixel(x + offset, y - offset, color);
+        }
+    }
+
+    /// Draws the sprite centered on the given position, in level coordinates.
+    /// Sprites are drawn at their pixel size in level units, like in the game,
+    /// and blended onto the image by their alpha.
+    pub fn draw_sprite(
+        &mut self,
+        center: (f32, f32),
+        spritesheet: &Spritesheet,
+        sprite_id: usize,
+    ) {
+        let rect = match spritesheet.sprite(sprite_id) {
+            Some(rect) => rect.clone(),
+            None => return,
+        };
+        let (center_x, center_y) = self.to_pixel(center);
+        let width = rect.width as f32 * self.scale;
+        let height = rect.height as f32 * self.scale;
+        let (left, top) = (center_x - width * 0.5, center_y - height * 0.5);
+
+        for y in (top.floor() as i64) .. ((top + height).ceil() as i64) {
+            for x in (left.floor() as i64) .. ((left + width).ceil() as i64) {
+                let sprite_x = ((x as f32 + 0.5 - left) / self.scale) as u32;
+                let sprite_y = ((y as f32 + 0.5 - top) / self.scale) as u32;
+                if sprite_x >= rect.width || sprite_y >= rect.height {
+                    continue;
+                }
+                if let Some([r, g, b, a]) =
+                    spritesheet.pixel(&rect, sprite_x, sprite_y)
+                {
+                    if a > 0 {
+                        self.blend_pixel(x, y, [r, g, b], f32::from(a) / 255.0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the pixel, if it is inside the image.
+    pub fn put_pixel(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if self.contains(x, y) {
+            self.image.put_pixel(x as u32, y as u32, Rgb(color));
+        }
+    }
+
+    /// Blends the color onto the pixel, if it is inside the image.
+    /// `alpha` is between `0.0` (unchanged) and `1.0` (replaced).
+    pub fn blend_pixel(&mut self, x: i64, y: i64, color: [u8; 3], alpha: f32) {
+        if self.contains(x, y) {
+            let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+            for (channel, new) in pixel.data.iter_mut().zip(color.iter()) {
+                *channel = (f32::from(*channel) * (1.0 - alpha)
+                    + f32::from(*new) * alpha)
+                    .round() as u8;
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        self.image
+            .save(path)
+            .map_err(|err| format!("Couldn't write image `{}`: {}", path, err))
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && x < i64::from(self.image.width())
+            && y < i64::from(self.image.height())
+    }
+}
@@ -0,0 +1,266 @@
+//! Reports the health economy of a campaign's levels.
+//! Health is also the currency: items cost health, and killing enemies rewards health.
+//!
+//! Per level, the report lists the enemy rewards by enemy type, the item costs,
+//! and the player's health budget, carried over from the previous levels:
+//! - __best case__: the player kills every enemy, takes no damage,
+//!   and only buys the required items.
+//! - __worst case__: the player kills no enemies, and only buys the required items,
+//!   dying (for `health_increase_on_death`) until they can afford them.
+//!
+//! The first item of each type in `economy.required_items` in the campaign
+//! is required, unless an item's level object has the property `required`,
+//! which overrides this.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::level_file::LevelFile;
+use crate::components::prelude::ItemType;
+use crate::resource_helpers::resource;
+use crate::settings::prelude::*;
+use crate::settings::SettingsEnemy;
+use crate::settings_loader::load_settings;
+use crate::states::helpers::CampaignType;
+
+/// Reports the campaign's levels. Fails if required items of a level
+/// can't be afforded, even in the best case.
+pub fn report_campaign(campaign: &CampaignType) -> Result<(), String> {
+    let settings = load_settings()?;
+    let campaign_settings = super::campaigns(&settings)
+        .into_iter()
+        .find(|(campaign_type, _)| campaign_type == campaign)
+        .map(|(_, campaign_settings)| campaign_settings)
+        .ok_or_else(|| format!("Campaign `{:?}` doesn't exist", campaign))?;
+
+    let max_health = settings.player.max_health;
+    let increase = campaign_settings.health_increase_on_death;
+    println!(
+        "Campaign {:?}: start health {}, max health {}, health on death +{}",
+        campaign, settings.player.health, max_health, increase
+    );
+
+    let mut best = settings.player.health.min(max_health);
+    let mut worst = best;
+    let mut unaffordable = Vec::new();
+    // Required item types, which the player already has from previous levels.
+    let mut owned_items = HashSet::new();
+
+    for level_name in &campaign_settings.level_names {
+        let level = LevelFile::load(&resource(format!(
+            "{}/{}",
+            campaign_settings.levels_dir, level_name
+        )))?;
+        let economy = LevelEconomy::new(&level, &settings, &mut owned_items)?;
+
+        println!();
+        println!("{}", level_name);
+        println!("  enemy rewards: {}", economy.rewards_summary());
+        println!("  item costs:    {}", economy.items_summary());
+
+        let (best_start, worst_start) = (best, worst);
+        let required_cost = economy.required_cost();
+
+        // Health must stay above the cost to buy an item,
+        // so buying all required items needs more health than their total cost.
+        best = (best + economy.total_reward()).min(max_health);
+        let best_affordable = best > required_cost;
+        best = best.saturating_sub(required_cost).max(1);
+
+        let deaths = if worst > required_cost {
+            Some(0)
+        } else if increase > 0 && max_health > required_cost {
+            let missing = required_cost + 1 - worst;
+            Some((missing + increase - 1) / increase)
+        } else {
+            None
+        };
+        if let Some(deaths) = deaths {
+            worst = (worst + deaths * increase).min(max_health);
+        }
+        worst = worst.saturating_sub(required_cost).max(1);
+
+        println!(
+            "  health:        best {} -> {}, worst {} -> {}, on death +{}",
+            best_start, best, worst_start, worst, increase
+        );
+
+        if !best_affordable {
+            println!(
+                "  ! required items ({} health) are unaffordable, even when \
+                 killing every enemy",
+                required_cost
+            );
+            unaffordable.push(level_name.clone());
+        }
+        match deaths {
+            Some(0) => (),
+            Some(deaths) => println!(
+                "  ! worst case: required items ({} health) need {} death(s) \
+                 to afford",
+                required_cost, deaths
+            ),
+            None => println!(
+                "  ! worst case: required items ({} health) are unaffordable \
+                 without killing enemies",
+                required_cost
+            ),
+        }
+    }
+
+    if unaffordable.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Required items are unaffordable in {}",
+            unaffordable.join(", ")
+        ))
+    }
+}
+
+/// A level's enemy rewards and item costs.
+struct LevelEconomy {
+    /// Enemy type name to amount of enemies and reward per enemy.
+    rewards: BTreeMap<String, (u32, u32)>,
+    /// Item type name, cost and if the item is required, in level order.
+    items:   Vec<(String, u32, bool)>,
+}
+
+impl LevelEconomy {
+    /// Items are required, if they are the first of their type in
+    /// `economy.required_items`, which the player doesn't own yet,
+    /// unless their `required` property says otherwise.
+    /// Adds the required items' types to the owned item types.
+    fn new(
+        level: &LevelFile,
+        settings: &Settings,
+        owned_items: &mut HashSet<String>,
+    ) -> Result<Self, String> {
+        let mut rewards = BTreeMap::new();
+        let mut items = Vec::new();
+
+        for object in &level.objects {
+            match object.object_type.as_str() {
+                "Enemy" => {
+                    let enemy_type = object.properties["enemy_type"]
+                        .as_str()
+                        .unwrap_or_default();
+                    let reward = enemy_settings(settings, enemy_type)
+                        .ok_or_else(|| {
+                            format!("Unknown enemy type `{}`", enemy_type)
+                        })?
+                        .reward;
+                    rewards
+                        .entry(enemy_type.to_string())
+                        .or_insert((0, reward))
+                        .0 += 1;
+                }
+                "Item" => {
+                    let item_type = object.properties["item_type"]
+                        .as_str()
+                        .unwrap_or_default();
+                    let cost = ItemType::from_name(item_type)
+                        .ok_or_else(|| {
+                            format!("Unknown item type `{}`", item_type)
+                        })?
+                        .settings(&settings.items)
+                        .cost;
+                    let required = object.properties["required"]
+                        .as_bool()
+                        .unwrap_or_else(|| {
+                            settings
+                                .economy
+                                .required_items
+                                .iter()
+                                .any(|required| required == item_type)
+                                && !owned_items.contains(item_type)
+                        });
+                    if required {
+                        owned_items.insert(item_type.to_string());
+                    }
+                    items.push((item_type.to_string(), cost, required));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            rewards: rewards,
+            items:   items,
+        })
+    }
+
+    fn total_reward(&self) -> u32 {
+        self.rewards
+            .values()
+            .map(|(amount, reward)| amount * reward)
+            .sum()
+    }
+
+    fn required_cost(&self) -> u32 {
+        self.items
+            .iter()
+            .filter(|(_, _, required)| *required)
+            .map(|(_, cost, _)| cost)
+            .sum()
+    }
+
+    fn rewards_summary(&self) -> String {
+        if self.rewards.is_empty() {
+            return "none".to_string();
+        }
+        let rewards = self
+            .rewards
+            .iter()
+            .map(|(enemy_type, (amount, reward))| {
+                format!(
+                    "{} {} x {} = {}",
+                    enemy_type,
+                    amount,
+                    reward,
+                    amount * reward
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        format!("{} (total {})", rewards, self.total_reward())
+    }
+
+    fn items_summary(&self) -> String {
+        if self.items.is_empty() {
+            return "none".to_string();
+        }
+        let items = self
+            .items
+            .iter()
+            .map(|(item_type, cost, required)| {
+                if *required {
+                    format!("{} {} (required)", item_type, cost)
+                } else {
+                    format!("{} {}", item_type, cost)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let total: u32 = self.items.iter().map(|(_, cost, _)| cost).sum();
+        format!(
+            "{} (total {}, required {})",
+            items,
+            total,
+            self.required_cost()
+        )
+    }
+}
+
+fn enemy_settings<'a>(
+    settings: &'a Settings,
+    enemy_type: &str,
+) -> Option<&'a SettingsEnemy> {
+    match enemy_type {
+        "Normal" => Some(&settings.enemies.normal),
+        "Charger" => Some(&settings.enemies.charger),
+        "Flying" => Some(&settings.enemies.flying),
+        "Reaper" => Some(&settings.enemies.reaper),
+        "Turret" => Some(&settings.enemies.turret),
+        _ => None,
+    }
+}
@@ -0,0 +1,210 @@
+//! Renders a heatmap image of every level, from the level's tiles and
+//! the recorded player damage and deaths (see `analytics`).
+
+use std::collections::HashMap;
+use std::fs::create_dir_all;
+
+use amethyst::utils::application_root_dir;
+
+use super::canvas::Canvas;
+use super::level_file::LevelFile;
+use crate::analytics::{read_records, DamageRecord};
+use crate::resource_helpers::resource;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+use crate::states::helpers::CampaignType;
+
+/// Heat colors, from the least to the most damage.
+const HEAT_GRADIENT: [[u8; 3]; 3] = [[32, 64, 255], [255, 224, 0], [255, 0, 0]];
+/// Opacity of the most damaged spot.
+const MAX_HEAT_ALPHA: f32 = 0.85;
+
+/// Renders the heatmaps of all levels of all campaigns.
+pub fn render_all() -> Result<(), String> {
+    let settings = load_settings()?;
+    let analytics_settings = settings.analytics.clone();
+    let records = read_records(&analytics_settings)?;
+
+    // Levels of different campaigns, mod packs' in particular,
+    // may have the same name.
+    let mut records_by_level: HashMap<
+        (CampaignType, String),
+        Vec<DamageRecord>,
+    > = HashMap::new();
+    for record in records {
+        records_by_level
+            .entry((record.campaign.clone(), record.level.clone()))
+            .or_insert_with(Vec::new)
+            .push(record);
+    }
+
+    let dir = format!(
+        "{}/{}",
+        application_root_dir(),
+        analytics_settings.heatmaps_dir
+    );
+    create_dir_all(&dir).map_err(|err| {
+        format!("Couldn't create directory `{}`: {}", dir, err)
+    })?;
+
+    let mut failed = Vec::new();
+    for (campaign, campaign_settings) in super::campaigns(&settings) {
+        for level_name in &campaign_settings.level_names {
+            let records = records_by_level
+                .remove(&(campaign.clone(), level_name.clone()))
+                .unwrap_or_else(Vec::new);
+            let level_path = resource(format!(
+                "{}/{}",
+                campaign_settings.levels_dir, level_name
+            ));
+            let image_path =
+                format!("{}/{}.png", dir, level_name.trim_end_matches(".json"));
+
+            let result = render(
+                &level_path,
+                &campaign_settings,
+                &analytics_settings.heatmap,
+                &records,
+            )
+            .and_then(|canvas| canvas.save(&image_path));
+            match result {
+                Ok(()) => info!(
+                    "Wrote heatmap of {} ({} records) to {}",
+                    level_name,
+                    records.len(),
+                    image_path
+                ),
+                Err(err) => {
+                    error!("{}", err);
+                    failed.push(level_name.clone());
+                }
+            }
+        }
+    }
+
+    for ((campaign, level_name), records) in records_by_level {
+        warn!(
+            "Skipping {} records of unknown level {} of the {:?} campaign",
+            records.len(),
+            level_name,
+            campaign
+        );
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Couldn't render heatmaps of {}", failed.join(", ")))
+    }
+}
+
+fn render(
+    level_path: &str,
+    campaign_settings: &SettingsLevelManagerCampaign,
+    settings: &SettingsHeatmap,
+    records: &[DamageRecord],
+) -> Result<Canvas, String> {
+    let level = LevelFile::load(level_path)?;
+    let mut canvas =
+        Canvas::new(level.size, settings.scale, settings.background_color);
+
+    for tile in &level.tiles {
+        let color = if tile.is_harmful() {
+            settings.harmful_tile_color
+        } else if tile.is_solid() {
+            settings.solid_tile_color
+        } else {
+            settings.tile_color
+        };
+        let tile_size = campaign_settings.tile_size;
+        canvas.fill_rect(tile.pos, (tile_size.0, tile_size.1), color);
+    }
+
+    draw_heat(&mut canvas, settings, records);
+
+    for record in records.iter().filter(|record| record.fatal) {
+        canvas.cross(
+            position(record),
+            settings.death_marker_size,
+            settings.death_color,
+        );
+    }
+    if let Some(player) = level.object("Player") {
+        canvas.outline_rect(player.pos, player.size, settings.player_color);
+    }
+    if let Some(goal) = level.object("Goal") {
+        canvas.outline_rect(goal.pos, goal.size, settings.goal_color);
+    }
+
+    Ok(canvas)
+}
+
+/// Blends the damage heat onto the canvas. Every record adds heat
+/// (its damage amount) around its position, falling off towards `radius`.
+fn draw_heat(
+    canvas: &mut Canvas,
+    settings: &SettingsHeatmap,
+    records: &[DamageRecord],
+) {
+    let (width, height) = (canvas.width() as i64, canvas.height() as i64);
+    let radius = (settings.radius * canvas.scale()).max(1.0);
+    let mut heat = vec![0.0_f32; (width * height) as usize];
+
+    for record in records {
+        let (center_x, center_y) = canvas.to_pixel(position(record));
+        let min_x = ((center_x - radius).floor() as i64).max(0);
+        let max_x = ((center_x + radius).ceil() as i64).min(width - 1);
+        let min_y = ((center_y - radius).floor() as i64).max(0);
+        let max_y = ((center_y + radius).ceil() as i64).min(height - 1);
+        for y in min_y ..= max_y {
+            for x in min_x ..= max_x {
+                let distance = ((x as f32 - center_x).powi(2)
+                    + (y as f32 - center_y).powi(2))
+                .sqrt();
+                if distance < radius {
+                    let falloff = 1.0 - distance / radius;
+                    heat[(y * width + x) as usize] +=
+                        falloff * falloff * record.damage as f32;
+                }
+            }
+        }
+    }
+
+    let max_heat = heat.iter().cloned().fold(0.0, f32::max);
+    if max_heat <= 0.0 {
+        return;
+    }
+    for y in 0 .. height {
+        for x in 0 .. width {
+            let amount = heat[(y * width + x) as usize] / max_heat;
+            if amount > 0.0 {
+                canvas.blend_pixel(
+                    x,
+                    y,
+                    gradient(amount),
+                    amount.sqrt() * MAX_HEAT_ALPHA,
+                );
+            }
+        }
+    }
+}
+
+/// Returns the record's position. Deaths from falling below the death floor
+/// are moved up to the bottom edge of the level, so they are visible.
+fn position(record: &DamageRecord) -> (f32, f32) {
+    (record.x, record.y.max(0.0))
+}
+
+/// Returns the heat color for the amount between `0.0` and `1.0`.
+fn gradient(amount: f32) -> [u8; 3] {
+    let position = amount.max(0.0).min(1.0) * (HEAT_GRADIENT.len() - 1) as f32;
+    let index = (position.floor() as usize).min(HEAT_GRADIENT.len() - 2);
+    let t = position - index as f32;
+    let (from, to) = (HEAT_GRADIENT[index], HEAT_GRADIENT[index + 1]);
+    let mut color = [0; 3];
+    for ((channel, from), to) in color.iter_mut().zip(&from).zip(&to) {
+        *channel =
+            (f32::from(*from) * (1.0 - t) + f32::from(*to) * t).round() as u8;
+    }
+    color
+}
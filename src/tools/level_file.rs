@@ -0,0 +1,114 @@
+//! Reads a level file's data, without building any entities.
+
+use json::JsonValue;
+
+use crate::resource_helpers::read_file;
+
+pub struct LevelFile {
+    pub size:    (f32, f32),
+    pub tiles:   Vec<LevelTile>,
+    pub objects: Vec<LevelObject>,
+}
+
+pub struct LevelTile {
+    pub id:         usize,
+    pub tileset:    String,
+    /// Top-left corner, in level coordinates (y points up).
+    pub pos:        (f32, f32),
+    pub properties: JsonValue,
+}
+
+pub struct LevelObject {
+    pub object_type: String,
+    /// Top-left corner, in level coordinates (y points up).
+    pub pos:         (f32, f32),
+    pub size:        (f32, f32),
+    pub properties:  JsonValue,
+}
+
+impl LevelFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = read_file(path).map_err(|err| {
+            format!("Couldn't read level `{}`: {}", path, err)
+        })?;
+        let json = json::parse(&raw).map_err(|err| {
+            format!("Couldn't parse level `{}`: {}", path, err)
+        })?;
+
+        let size = match (
+            json["level"]["size"]["w"].as_f32(),
+            json["level"]["size"]["h"].as_f32(),
+        ) {
+            (Some(w), Some(h)) => (w, h),
+            _ => return Err(format!("Level `{}` has no size", path)),
+        };
+
+        let tiles = json["tiles"]
+            .members()
+            .filter_map(|tile| {
+                match (
+                    tile["id"].as_usize(),
+                    tile["ts"].as_str(),
+                    tile["pos"]["x"].as_f32(),
+                    tile["pos"]["y"].as_f32(),
+                ) {
+                    (Some(id), Some(tileset), Some(x), Some(y)) => {
+                        Some(LevelTile {
+                            id:         id,
+                            tileset:    tileset.to_string(),
+                            pos:        (x, y),
+                            properties: tile["properties"].clone(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let objects = json["objects"]
+            .members()
+            .filter_map(|object| {
+                match (
+                    object["type"].as_str(),
+                    object["pos"]["x"].as_f32(),
+                    object["pos"]["y"].as_f32(),
+                    object["size"]["w"].as_f32(),
+                    object["size"]["h"].as_f32(),
+                ) {
+                    (Some(object_type), Some(x), Some(y), Some(w), Some(h)) => {
+                        Some(LevelObject {
+                            object_type: object_type.to_string(),
+                            pos:         (x, y),
+                            size:        (w, h),
+                            properties:  object["properties"].clone(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            size:    size,
+            tiles:   tiles,
+            objects: objects,
+        })
+    }
+
+    /// Returns the first object of the given type, for example `"Player"`.
+    pub fn object(&self, object_type: &str) -> Option<&LevelObject> {
+        self.objects
+            .iter()
+            .find(|object| object.object_type == object_type)
+    }
+}
+
+impl LevelTile {
+    pub fn is_solid(&self) -> bool {
+        self.properties["solid"].as_bool().unwrap_or(false)
+    }
+
+    pub fn is_harmful(&self) -> bool {
+        self.properties["harmful"].as_u32().is_some()
+    }
+}
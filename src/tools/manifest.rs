@@ -0,0 +1,148 @@
+//! Generates and verifies the campaigns' asset manifests.
+//! The assets are found by scanning the level files
+//! (tilesets, enemies, items and parallax backgrounds) and the campaign settings (songs).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::level_file::LevelFile;
+use crate::asset_manifest::{spritesheet_ron_path, AssetManifest};
+use crate::level_manager::{object_assets, tileset_spritesheet, ObjectAsset};
+use crate::resource_helpers::resource;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+
+/// The assets of a campaign, and which levels (or the settings) reference each file.
+struct CampaignAssets {
+    manifest:  AssetManifest,
+    referrers: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Writes the asset manifest of every campaign.
+pub fn generate_all() -> Result<(), String> {
+    let settings = load_settings()?;
+    for (campaign, campaign_settings) in super::campaigns(&settings) {
+        let assets = scan_campaign(&campaign_settings)?;
+        assets.manifest.save(&campaign_settings.asset_manifest)?;
+        println!(
+            "{:?}: {} spritesheets, {} textures, {} songs -> {}",
+            campaign,
+            assets.manifest.spritesheets.len(),
+            assets.manifest.textures.len(),
+            assets.manifest.songs.len(),
+            resource(&campaign_settings.asset_manifest)
+        );
+    }
+    Ok(())
+}
+
+/// Reports referenced assets, which don't exist,
+/// and manifests, which are missing or out of date.
+pub fn verify_all() -> Result<(), String> {
+    let settings = load_settings()?;
+    let mut problems = 0;
+
+    for (campaign, campaign_settings) in super::campaigns(&settings) {
+        let assets = scan_campaign(&campaign_settings)?;
+        println!("{:?}", campaign);
+
+        let missing = assets.manifest.missing_files();
+        for file in &missing {
+            println!(
+                "  missing {}, referenced by {}",
+                file,
+                assets.referrers[file]
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        }
+        problems += missing.len();
+
+        match AssetManifest::load(&campaign_settings.asset_manifest) {
+            Ok(manifest) => {
+                if manifest != assets.manifest {
+                    println!(
+                        "  manifest {} is out of date; regenerate it with \
+                         --manifests",
+                        campaign_settings.asset_manifest
+                    );
+                    problems += 1;
+                }
+            }
+            Err(err) => {
+                println!("  {}; generate it with --manifests", err);
+                problems += 1;
+            }
+        }
+
+        if missing.is_empty() {
+            println!("  all {} asset files exist", assets.referrers.len());
+        }
+    }
+
+    if problems == 0 {
+        Ok(())
+    } else {
+        Err(format!("Found {} asset problem(s)", problems))
+    }
+}
+
+fn scan_campaign(
+    campaign_settings: &SettingsLevelManagerCampaign,
+) -> Result<CampaignAssets, String> {
+    let mut assets = CampaignAssets {
+        manifest:  AssetManifest::default(),
+        referrers: BTreeMap::new(),
+    };
+
+    for level_name in &campaign_settings.level_names {
+        let level = LevelFile::load(&resource(format!(
+            "{}/{}",
+            campaign_settings.levels_dir, level_name
+        )))?;
+        for tile in &level.tiles {
+            assets.add_spritesheet(
+                tileset_spritesheet(&tile.tileset),
+                level_name,
+            );
+        }
+        for object in &level.objects {
+            for asset in object_assets(&object.object_type, &object.properties)
+            {
+                match asset {
+                    ObjectAsset::Spritesheet(path) => {
+                        assets.add_spritesheet(path, level_name)
+                    }
+                    ObjectAsset::Texture(path) => {
+                        assets.add_referrer(&path, level_name);
+                        assets.manifest.textures.insert(path);
+                    }
+                }
+            }
+        }
+    }
+
+    for song_name in &campaign_settings.song_names {
+        let path = format!("audio/{}", song_name);
+        assets.add_referrer(&path, "settings");
+        assets.manifest.songs.insert(path);
+    }
+
+    Ok(assets)
+}
+
+impl CampaignAssets {
+    fn add_spritesheet(&mut self, path: String, referrer: &str) {
+        self.add_referrer(&spritesheet_ron_path(&path), referrer);
+        self.add_referrer(&path, referrer);
+        self.manifest.spritesheets.insert(path);
+    }
+
+    fn add_referrer(&mut self, path: &str, referrer: &str) {
+        self.referrers
+            .entry(path.to_string())
+            .or_insert_with(BTreeSet::new)
+            .insert(referrer.to_string());
+    }
+}
@@ -0,0 +1,77 @@
+//! Command line tools for designers, which run instead of the game.
+//! Each tool is started with its command line argument, and exits the game when done.
+
+mod benchmark;
+mod canvas;
+mod economy;
+mod heatmap;
+mod level_file;
+mod manifest;
+mod mods;
+mod pack;
+mod preview;
+mod replay;
+mod spritesheet;
+
+use crate::settings::prelude::*;
+use crate::states::helpers::CampaignType;
+
+/// Runs the tool requested with a command line argument, and exits
+/// with a non-zero exit code if it failed. Does nothing if no tool was requested.
+pub fn run_requested_tool() {
+    let cli_args = crate::cli::args();
+    if let Some(path) = cli_args.replay.as_ref() {
+        exit_with(replay::run(path));
+    }
+    if cli_args.heatmaps {
+        exit_with(heatmap::render_all());
+    }
+    if let Some(level_name) = cli_args.preview.as_ref() {
+        exit_with(preview::render_level(
+            level_name,
+            cli_args.output.as_ref().map(String::as_str),
+        ));
+    }
+    if cli_args.previews {
+        exit_with(preview::render_all());
+    }
+    if let Some(campaign) = cli_args.economy.as_ref() {
+        exit_with(economy::report_campaign(campaign));
+    }
+    if cli_args.benchmark_grid {
+        exit_with(benchmark::run());
+    }
+    if cli_args.manifests {
+        exit_with(manifest::generate_all());
+    }
+    if cli_args.verify_assets {
+        exit_with(manifest::verify_all());
+    }
+    if cli_args.verify_mods {
+        exit_with(mods::verify_all());
+    }
+    if cli_args.pack_resources {
+        exit_with(pack::pack_resources(!cli_args.no_compression));
+    }
+}
+
+fn exit_with(result: Result<(), String>) {
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(err) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Returns the settings of every campaign.
+fn campaigns(
+    settings: &Settings,
+) -> Vec<(CampaignType, SettingsLevelManagerCampaign)> {
+    vec![
+        (CampaignType::Normal, settings.level_manager.normal.clone()),
+        (CampaignType::BonusA, settings.level_manager.bonus_a.clone()),
+        (CampaignType::BonusB, settings.level_manager.bonus_b.clone()),
+    ]
+}
@@ -0,0 +1,39 @@
+//! Reports the problems of the installed mod packs,
+//! so pack authors can check their packs without starting the game.
+
+use crate::mod_packs::{mods_dir, ModPacks};
+use crate::settings_loader::load_settings;
+
+/// Validates every pack in the `mods` directory.
+/// Fails if any pack can't be played.
+pub fn verify_all() -> Result<(), String> {
+    let settings = load_settings()?;
+    let mod_packs = ModPacks::scan(&settings.level_manager.normal);
+    if mod_packs.packs().is_empty() {
+        println!("No mod packs installed in {}", mods_dir());
+        return Ok(());
+    }
+
+    let mut broken = 0;
+    for pack in mod_packs.packs() {
+        if pack.is_playable() {
+            let levels = pack
+                .campaign
+                .as_ref()
+                .map_or(0, |campaign| campaign.level_names.len());
+            println!("{} ({}): ok, {} levels", pack.name(), pack.id, levels);
+        } else {
+            println!("{} ({}):", pack.name(), pack.id);
+            for problem in &pack.problems {
+                println!("  {}", problem);
+            }
+            broken += 1;
+        }
+    }
+
+    if broken == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} mod pack(s) can't be played", broken))
+    }
+}
@@ -0,0 +1,27 @@
+//! Packs the `resources` directory into the resource archive,
+//! which release builds are distributed with instead of the loose files.
+
+use amethyst::utils::application_root_dir;
+
+use crate::resource_archive::{ResourceArchive, ARCHIVE_FILENAME};
+use crate::resource_helpers::resources_dir;
+
+/// Writes `resources.pak` next to the executable,
+/// and checks that every packed file can be read back.
+pub fn pack_resources(compress: bool) -> Result<(), String> {
+    let output = format!("{}/{}", application_root_dir(), ARCHIVE_FILENAME);
+    let summary = ResourceArchive::pack(&resources_dir(), &output, compress)?;
+
+    let archive = ResourceArchive::open(&output)?;
+    archive.verify()?;
+
+    println!(
+        "Packed {} files ({} KiB -> {} KiB{}) -> {}",
+        summary.files,
+        summary.original_size / 1024,
+        summary.packed_size / 1024,
+        if compress { "" } else { ", uncompressed" },
+        output
+    );
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Renders a preview image of a level on the CPU, without a window or GPU.
+//! Tiles are drawn with their sprites from the tilesets' spritesheets,
+//! and the player, goal, enemies and items are drawn as outlined markers.
+
+use std::collections::HashMap;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use amethyst::utils::application_root_dir;
+
+use super::canvas::Canvas;
+use super::level_file::{LevelFile, LevelTile};
+use super::spritesheet::Spritesheet;
+use crate::resource_helpers::resource;
+use crate::settings::prelude::*;
+use crate::settings_loader::load_settings;
+
+/// Default z of tiles without a `z` property, same as in the level loader.
+const TILE_Z: f32 = 0.0;
+
+/// Renders the preview of the level with the given name, from any campaign.
+/// Writes it to `output`, or to the previews directory if not given.
+pub fn render_level(
+    level_name: &str,
+    output: Option<&str>,
+) -> Result<(), String> {
+    let settings = load_settings()?;
+    let preview_settings = &settings.level_preview;
+    let level_name = if level_name.ends_with(".json") {
+        level_name.to_string()
+    } else {
+        format!("{}.json", level_name)
+    };
+
+    let campaign_settings = super::campaigns(&settings)
+        .into_iter()
+        .map(|(_, campaign_settings)| campaign_settings)
+        .find(|campaign_settings| {
+            campaign_settings.level_names.contains(&level_name)
+        })
+        .ok_or_else(|| format!("Level `{}` doesn't exist", level_name))?;
+
+    let image_path = match output {
+        Some(output) => output.to_string(),
+        None => default_image_path(preview_settings, &level_name)?,
+    };
+    let mut spritesheets = HashMap::new();
+    render(
+        &level_path(&campaign_settings, &level_name),
+        &campaign_settings,
+        preview_settings,
+        &mut spritesheets,
+    )?
+    .save(&image_path)?;
+    info!("Wrote preview of {} to {}", level_name, image_path);
+    Ok(())
+}
+
+/// Renders the previews of all levels of all campaigns.
+pub fn render_all() -> Result<(), String> {
+    let settings = load_settings()?;
+    let preview_settings = &settings.level_preview;
+
+    let mut spritesheets = HashMap::new();
+    let mut failed = Vec::new();
+    for (_, campaign_settings) in super::campaigns(&settings) {
+        for level_name in &campaign_settings.level_names {
+            let result = default_image_path(preview_settings, level_name)
+                .and_then(|image_path| {
+                    render(
+                        &level_path(&campaign_settings, level_name),
+                        &campaign_settings,
+                        preview_settings,
+                        &mut spritesheets,
+                    )?
+                    .save(&image_path)?;
+                    Ok(image_path)
+                });
+            match result {
+                Ok(image_path) => {
+                    info!("Wrote preview of {} to {}", level_name, image_path)
+                }
+                Err(err) => {
+                    error!("{}", err);
+                    failed.push(level_name.clone());
+                }
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Couldn't render previews of {}", failed.join(", ")))
+    }
+}
+
+fn level_path(
+    campaign_settings: &SettingsLevelManagerCampaign,
+    level_name: &str,
+) -> String {
+    resource(format!("{}/{}", campaign_settings.levels_dir, level_name))
+}
+
+/// Returns `PREVIEWS_DIR/LEVEL_NAME.png`, and creates the directory.
+fn default_image_path(
+    settings: &SettingsLevelPreview,
+    level_name: &str,
+) -> Result<String, String> {
+    let dir = format!("{}/{}", application_root_dir(), settings.output_dir);
+    create_dir_all(&dir).map_err(|err| {
+        format!("Couldn't create directory `{}`: {}", dir, err)
+    })?;
+    Ok(format!(
+        "{}/{}.png",
+        dir,
+        Path::new(level_name)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(level_name)
+    ))
+}
+
+/// Renders the level. Spritesheets are loaded once, and cached in `spritesheets`.
+fn render(
+    level_path: &str,
+    campaign_settings: &SettingsLevelManagerCampaign,
+    settings: &SettingsLevelPreview,
+    spritesheets: &mut HashMap<String, Spritesheet>,
+) -> Result<Canvas, String> {
+    let level = LevelFile::load(level_path)?;
+    let mut canvas =
+        Canvas::new(level.size, settings.scale, settings.background_color);
+
+    // Draw tiles from back to front, like the game does.
+    let mut tiles: Vec<&LevelTile> = level.tiles.iter().collect();
+    tiles.sort_by(|a, b| {
+        tile_z(a)
+            .partial_cmp(&tile_z(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let tile_size = campaign_settings.tile_size;
+    for tile in tiles {
+        if !spritesheets.contains_key(&tile.tileset) {
+            let spritesheet = Spritesheet::load(&tile.tileset)?;
+            spritesheets.insert(tile.tileset.clone(), spritesheet);
+        }
+        let center = (
+            tile.pos.0 + tile_size.0 * 0.5,
+            tile.pos.1 - tile_size.1 * 0.5,
+        );
+        canvas.draw_sprite(center, &spritesheets[&tile.tileset], tile.id);
+    }
+
+    if settings.draw_markers {
+        for object in &level.objects {
+            let color = match object.object_type.as_str() {
+                "Player" => settings.player_color,
+                "Goal" => settings.goal_color,
+                "Enemy" => settings.enemy_color,
+                "Item" => settings.item_color,
+                _ => continue,
+            };
+            canvas.outline_rect(object.pos, object.size, color);
+        }
+    }
+
+    Ok(canvas)
+}
+
+fn tile_z(tile: &LevelTile) -> f32 {
+    tile.properties["z"].as_f32().unwrap_or(TILE_Z)
+}
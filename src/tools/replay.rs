@@ -0,0 +1,73 @@
+//! Plays back a level run recorded with `--record` in the headless `Simulation`,
+//! and reports where the player ended up, and whether they beat the level.
+//! Each frame is run with its recorded delta time and keyboard keys.
+
+use std::collections::HashSet;
+
+use crate::mod_packs::ModPacks;
+use crate::replay::Replay;
+use crate::simulation::Simulation;
+use crate::states::helpers::{campaign_settings, CampaignType};
+
+pub fn run(path: &str) -> Result<(), String> {
+    let replay = Replay::load(path)?;
+    let mut sim = Simulation::new()?;
+
+    if let CampaignType::Mod(id) = &replay.campaign {
+        if sim
+            .world()
+            .read_resource::<ModPacks>()
+            .campaign(id)
+            .is_none()
+        {
+            return Err(format!("Mod pack `{}` isn't playable", id));
+        }
+    }
+    let campaign = campaign_settings(sim.world(), &replay.campaign);
+    if !campaign.level_names.contains(&replay.level) {
+        return Err(format!(
+            "Level `{}` doesn't exist in the {:?} campaign",
+            replay.level, replay.campaign
+        ));
+    }
+    let level_filepath =
+        campaign.resource(format!("{}/{}", campaign.levels_dir, replay.level));
+    sim.load_level(replay.campaign.clone(), level_filepath)?;
+    if let Some(player) = replay.player.clone() {
+        sim.set_player(player);
+    }
+
+    let mut completed_at = None;
+    for (i, frame) in replay.frames.iter().enumerate() {
+        let keys: HashSet<_> = frame.keys.iter().cloned().collect();
+        sim.tick_keys(keys, frame.delta())?;
+        if sim.has_completed_level() {
+            completed_at = Some(i + 1);
+            break;
+        }
+    }
+
+    let player = sim
+        .player()
+        .ok_or_else(|| "There is no player".to_string())?;
+    let (x, y) = sim
+        .player_position()
+        .ok_or_else(|| "There is no player".to_string())?;
+    println!("Replayed {} of level {}", path, replay.level);
+    println!(
+        "Player at ({:.1}, {:.1}) with {} of {} health",
+        x, y, player.health, player.max_health
+    );
+    match completed_at {
+        Some(frame) => println!(
+            "Beat the level after {} of {} frames",
+            frame,
+            replay.frames.len()
+        ),
+        None => println!(
+            "Didn't beat the level within {} frames",
+            replay.frames.len()
+        ),
+    }
+    Ok(())
+}
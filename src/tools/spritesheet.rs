@@ -0,0 +1,88 @@
+//! Reads a spritesheet's image and its sprite rects (the `.ron` file next to it),
+//! without loading it onto the GPU.
+
+use image::RgbaImage;
+
+use crate::resource_helpers::{read_file, read_file_bytes, resource};
+
+/// The `.ron` file of a spritesheet, in amethyst's `SpriteSheetFormat`.
+#[derive(Deserialize)]
+struct SpritesheetRon {
+    spritesheet_width:  u32,
+    spritesheet_height: u32,
+    sprites:            Vec<SpriteRect>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SpriteRect {
+    pub x:      u32,
+    pub y:      u32,
+    pub width:  u32,
+    pub height: u32,
+}
+
+pub struct Spritesheet {
+    image:   RgbaImage,
+    sprites: Vec<SpriteRect>,
+}
+
+impl Spritesheet {
+    /// Loads the spritesheet `resources/spritesheets/NAME.png`
+    /// with its sprite rects from `NAME.ron`.
+    pub fn load(name: &str) -> Result<Self, String> {
+        let ron_path = resource(format!("spritesheets/{}.ron", name));
+        let image_path = resource(format!("spritesheets/{}.png", name));
+
+        let raw = read_file(&ron_path).map_err(|err| {
+            format!("Couldn't read spritesheet `{}`: {}", ron_path, err)
+        })?;
+        let ron: SpritesheetRon = ron::de::from_str(&raw).map_err(|err| {
+            format!("Couldn't parse spritesheet `{}`: {}", ron_path, err)
+        })?;
+        let image_bytes = read_file_bytes(&image_path).map_err(|err| {
+            format!("Couldn't read image `{}`: {}", image_path, err)
+        })?;
+        let image = image::load_from_memory(&image_bytes)
+            .map_err(|err| {
+                format!("Couldn't decode image `{}`: {}", image_path, err)
+            })?
+            .to_rgba();
+
+        // The rects are relative to `spritesheet_width` and `spritesheet_height`,
+        // which may differ from the image's actual size; amethyst scales them.
+        let (scale_x, scale_y) = (
+            image.width() as f32 / ron.spritesheet_width.max(1) as f32,
+            image.height() as f32 / ron.spritesheet_height.max(1) as f32,
+        );
+        let sprites = ron
+            .sprites
+            .into_iter()
+            .map(|rect| SpriteRect {
+                x:      (rect.x as f32 * scale_x) as u32,
+                y:      (rect.y as f32 * scale_y) as u32,
+                width:  (rect.width as f32 * scale_x) as u32,
+                height: (rect.height as f32 * scale_y) as u32,
+            })
+            .collect();
+
+        Ok(Self {
+            image:   image,
+            sprites: sprites,
+        })
+    }
+
+    pub fn sprite(&self, id: usize) -> Option<&SpriteRect> {
+        self.sprites.get(id)
+    }
+
+    /// Returns the RGBA color of the sprite's pixel, relative to the
+    /// sprite's top-left corner, or `None` if it is outside the image.
+    pub fn pixel(&self, rect: &SpriteRect, x: u32, y: u32) -> Option<[u8; 4]> {
+        let (x, y) = (rect.x + x, rect.y + y);
+        if x < self.image.width() && y < self.image.height() {
+            Some(self.image.get_pixel(x, y).data)
+        } else {
+            None
+        }
+    }
+}
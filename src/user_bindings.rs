@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use amethyst::input::{Axis, Bindings, Button};
+#[cfg(test)]
+use amethyst::renderer::VirtualKeyCode;
+use amethyst::utils::application_root_dir;
+
+use crate::resource_helpers::*;
+
+pub mod prelude {
+    pub use super::AxisSide;
+    pub use super::BindingGroup;
+    pub use super::BindingsConfig;
+    pub use super::ButtonKind;
+    pub use super::Rebindable;
+    pub use super::UserBindings;
+    pub use super::REBINDABLES;
+}
+
+const USER_BINDINGS_FILENAME: &str = "user_bindings.ron";
+
+/// Rebindables of the same group may not share buttons.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BindingGroup {
+    Ingame,
+    Menu,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ButtonKind {
+    Keyboard,
+    Controller,
+}
+
+impl ButtonKind {
+    pub fn of(button: &Button) -> Option<Self> {
+        match button {
+            Button::Key(_) | Button::ScanCode(_) => Some(ButtonKind::Keyboard),
+            Button::Controller(_, _) => Some(ButtonKind::Controller),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum AxisSide {
+    Neg,
+    Pos,
+}
+
+/// Something the player can rebind in the controls menu.
+/// One rebindable may change the bindings of multiple actions and axes,
+/// for example "Left" changes the movement axes and the left dash action.
+pub struct Rebindable {
+    pub id:      &'static str,
+    pub label:   &'static str,
+    pub group:   BindingGroup,
+    pub actions: &'static [&'static str],
+    pub axes:    &'static [(&'static str, AxisSide)],
+}
+
+#[rustfmt::skip]
+pub const REBINDABLES: &[Rebindable] = &[
+    Rebindable { id: "left",         label: "Move / Dash Left",  group: BindingGroup::Ingame, actions: &["player_dash_left"],    axes: &[("player_x_keyboard", AxisSide::Neg), ("player_x_controller_dpad", AxisSide::Neg)] },
+    Rebindable { id: "right",        label: "Move / Dash Right", group: BindingGroup::Ingame, actions: &["player_dash_right"],   axes: &[("player_x_keyboard", AxisSide::Pos), ("player_x_controller_dpad", AxisSide::Pos)] },
+    Rebindable { id: "dash_up",      label: "Dash Up",           group: BindingGroup::Ingame, actions: &["player_dash_up"],      axes: &[] },
+    Rebindable { id: "dash_down",    label: "Dash Down",         group: BindingGroup::Ingame, actions: &["player_dash_down"],    axes: &[] },
+    Rebindable { id: "dash",         label: "Dash",              group: BindingGroup::Ingame, actions: &["player_dash_trigger"], axes: &[] },
+    Rebindable { id: "jump",         label: "Jump",              group: BindingGroup::Ingame, actions: &["player_jump"],         axes: &[] },
+    Rebindable { id: "attack",       label: "Attack",            group: BindingGroup::Ingame, actions: &["player_attack"],       axes: &[] },
+    Rebindable { id: "attack_left",  label: "Attack Left",       group: BindingGroup::Ingame, actions: &["player_attack_left"],  axes: &[] },
+    Rebindable { id: "attack_right", label: "Attack Right",      group: BindingGroup::Ingame, actions: &["player_attack_right"], axes: &[] },
+    Rebindable { id: "buy_item",     label: "Buy Item",          group: BindingGroup::Ingame, actions: &["player_buy_item"],     axes: &[] },
+    Rebindable { id: "pause",        label: "Pause",             group: BindingGroup::Ingame, actions: &["pause"],               axes: &[] },
+    Rebindable { id: "accept",       label: "Menu Accept",       group: BindingGroup::Menu,   actions: &["accept"],              axes: &[] },
+    Rebindable { id: "decline",      label: "Menu Back",         group: BindingGroup::Menu,   actions: &["decline"],             axes: &[] },
+    Rebindable { id: "options",      label: "Options",           group: BindingGroup::Menu,   actions: &["options"],             axes: &[] },
+    Rebindable { id: "menu_up",      label: "Menu Up",           group: BindingGroup::Menu,   actions: &["menu_up"],             axes: &[] },
+    Rebindable { id: "menu_down",    label: "Menu Down",         group: BindingGroup::Menu,   actions: &["menu_down"],           axes: &[] },
+    Rebindable { id: "menu_left",    label: "Menu Left",         group: BindingGroup::Menu,   actions: &["menu_left"],           axes: &[] },
+    Rebindable { id: "menu_right",   label: "Menu Right",        group: BindingGroup::Menu,   actions: &["menu_right"],          axes: &[] },
+];
+
+/// Diagonal dash actions, which are bound to combinations of
+/// the vertical and horizontal dash actions' buttons:
+/// `(diagonal_action, vertical_action, horizontal_action)`
+const DIAGONAL_DASHES: [(&str, &str, &str); 4] = [
+    ("player_dash_up_left", "player_dash_up", "player_dash_left"),
+    (
+        "player_dash_up_right",
+        "player_dash_up",
+        "player_dash_right",
+    ),
+    (
+        "player_dash_down_left",
+        "player_dash_down",
+        "player_dash_left",
+    ),
+    (
+        "player_dash_down_right",
+        "player_dash_down",
+        "player_dash_right",
+    ),
+];
+
+/// Mirrors the structure of amethyst's `Bindings`, so we can
+/// inspect and modify the bindings before handing them to amethyst.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BindingsConfig {
+    pub axes:    HashMap<String, Axis>,
+    pub actions: HashMap<String, Vec<Vec<Button>>>,
+}
+
+impl BindingsConfig {
+    /// Loads the default bindings from `bindings.ron`.
+    pub fn load_default() -> Result<Self, String> {
+        let path = resource("config/bindings.ron");
+        let raw = read_file(&path).map_err(|err| {
+            format!("Couldn't read bindings file `{}`: {}", path, err)
+        })?;
+        ron::de::from_str(&raw).map_err(|err| {
+            format!("Couldn't parse bindings file `{}`: {}", path, err)
+        })
+    }
+
+    /// Loads the default bindings, with the user's overrides applied.
+    pub fn load_with_user_bindings(
+        user_bindings: &UserBindings,
+    ) -> Result<Self, String> {
+        let mut config = Self::load_default()?;
+        user_bindings.apply_to(&mut config);
+        Ok(config)
+    }
+
+    /// Converts this config to amethyst's `Bindings`.
+    pub fn to_bindings(&self) -> Result<Bindings<String, String>, String> {
+        let serialized =
+            ron::ser::to_string(self).map_err(|err| err.to_string())?;
+        ron::de::from_str(&serialized).map_err(|err| err.to_string())
+    }
+
+    /// Returns the buttons of the given kind, the rebindable is bound to.
+    pub fn buttons_for(
+        &self,
+        rebindable: &Rebindable,
+        kind: ButtonKind,
+    ) -> Vec<Button> {
+        if let Some(action) = rebindable.actions.first() {
+            self.actions
+                .get(*action)
+                .map(|bindings| {
+                    bindings
+                        .iter()
+                        .filter_map(|binding| single_button(binding, kind))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            rebindable
+                .axes
+                .iter()
+                .filter_map(|(axis, side)| {
+                    axis_button(self.axes.get(*axis)?, *side)
+                })
+                .filter(|button| ButtonKind::of(button) == Some(kind))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Returns the first key, the action is bound to on its own.
+    /// Used by the headless `Simulation` to press actions.
+    #[cfg(test)]
+    pub fn key_for_action(&self, action: &str) -> Option<VirtualKeyCode> {
+        self.actions.get(action)?.iter().find_map(
+            |binding| match single_button(binding, ButtonKind::Keyboard) {
+                Some(Button::Key(key)) => Some(*key),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns the key, the given side of the emulated axis is bound to.
+    #[cfg(test)]
+    pub fn key_for_axis(
+        &self,
+        axis: &str,
+        side: AxisSide,
+    ) -> Option<VirtualKeyCode> {
+        match axis_button(self.axes.get(axis)?, side) {
+            Some(Button::Key(key)) => Some(*key),
+            _ => None,
+        }
+    }
+
+    /// Replaces all of the rebindable's buttons of the button's kind
+    /// with the given button.
+    pub fn set_button_for(&mut self, rebindable: &Rebindable, button: Button) {
+        let kind = match ButtonKind::of(&button) {
+            Some(kind) => kind,
+            None => return,
+        };
+
+        for action in rebindable.actions {
+            let bindings = self.actions.entry(action.to_string()).or_default();
+            bindings.retain(|binding| single_button(binding, kind).is_none());
+            bindings.push(vec![button.clone()]);
+        }
+
+        for (axis_name, side) in rebindable.axes {
+            if let Some(Axis::Emulated { pos, neg }) =
+                self.axes.get_mut(*axis_name)
+            {
+                let axis_button = match side {
+                    AxisSide::Neg => neg,
+                    AxisSide::Pos => pos,
+                };
+                if ButtonKind::of(axis_button) == Some(kind) {
+                    *axis_button = button.clone();
+                }
+            }
+        }
+
+        self.rebuild_diagonal_dashes();
+    }
+
+    /// Returns the rebindable, which already uses the given button
+    /// and conflicts with the given rebindable, if there is one.
+    pub fn conflict_for(
+        &self,
+        rebindable: &Rebindable,
+        button: &Button,
+    ) -> Option<&'static Rebindable> {
+        let kind = ButtonKind::of(button)?;
+        REBINDABLES.iter().find(|other| {
+            other.id != rebindable.id
+                && other.group == rebindable.group
+                && self.buttons_for(other, kind).contains(button)
+        })
+    }
+
+    /// Binds each diagonal dash action to the combinations of the
+    /// buttons of its vertical and horizontal dash actions.
+    fn rebuild_diagonal_dashes(&mut self) {
+        for (diagonal, vertical, horizontal) in DIAGONAL_DASHES.iter() {
+            let mut combos = Vec::new();
+            for kind in [ButtonKind::Keyboard, ButtonKind::Controller].iter() {
+                let first_button = |action: &str| {
+                    self.actions.get(action).and_then(|bindings| {
+                        bindings
+                            .iter()
+                            .find_map(|binding| single_button(binding, *kind))
+                    })
+                };
+                if let (Some(v), Some(h)) =
+                    (first_button(vertical), first_button(horizontal))
+                {
+                    combos.push(vec![v.clone(), h.clone()]);
+                }
+            }
+            self.actions.insert(diagonal.to_string(), combos);
+        }
+    }
+}
+
+/// The player's binding overrides, which are saved to the user bindings file
+/// and are merged over the default bindings from `bindings.ron` at startup.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserBindings {
+    /// Overrides by `Rebindable` id.
+    pub overrides: HashMap<String, BindingOverride>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindingOverride {
+    pub keyboard:   Option<Button>,
+    pub controller: Option<Button>,
+}
+
+impl UserBindings {
+    /// Loads the user bindings file, falling back to no overrides if it
+    /// does not exist or could not be parsed.
+    pub fn load() -> Self {
+        let path = user_bindings_path();
+        match read_file(&path) {
+            Ok(raw) => match ron::de::from_str(&raw) {
+                Ok(user_bindings) => user_bindings,
+                Err(err) => {
+                    warn!(
+                        "Couldn't parse user bindings file `{}`, using \
+                         default bindings: {}",
+                        path, err
+                    );
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the user bindings to the user bindings file.
+    pub fn save(&self) -> Result<(), String> {
+        let serialized =
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|err| err.to_string())?;
+        write_file(user_bindings_path(), serialized)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Overrides the rebindable's binding for the button's kind.
+    pub fn set(&mut self, rebindable: &Rebindable, button: Button) {
+        let binding_override =
+            self.overrides.entry(rebindable.id.to_string()).or_default();
+        match ButtonKind::of(&button) {
+            Some(ButtonKind::Keyboard) => {
+                binding_override.keyboard = Some(button)
+            }
+            Some(ButtonKind::Controller) => {
+                binding_override.controller = Some(button)
+            }
+            None => (),
+        }
+    }
+
+    /// Applies all overrides to the given bindings.
+    pub fn apply_to(&self, config: &mut BindingsConfig) {
+        for rebindable in REBINDABLES {
+            if let Some(binding_override) = self.overrides.get(rebindable.id) {
+                if let Some(button) = &binding_override.keyboard {
+                    config.set_button_for(rebindable, button.clone());
+                }
+                if let Some(button) = &binding_override.controller {
+                    config.set_button_for(rebindable, button.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Loads the default bindings merged with the user's bindings,
+/// as amethyst `Bindings`.
+pub fn load_bindings() -> Result<Bindings<String, String>, String> {
+    BindingsConfig::load_with_user_bindings(&UserBindings::load())?
+        .to_bindings()
+}
+
+/// Returns a human readable name for the button.
+pub fn button_name(button: &Button) -> String {
+    match button {
+        Button::Key(key) => format!("{:?}", key),
+        Button::ScanCode(code) => format!("ScanCode {}", code),
+        Button::Controller(_, controller_button) => {
+            format!("Pad {:?}", controller_button)
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Returns the button, if the binding consists of a single button
+/// of the given kind (not a combination of buttons).
+fn single_button(binding: &[Button], kind: ButtonKind) -> Option<&Button> {
+    match binding {
+        [button] if ButtonKind::of(button) == Some(kind) => Some(button),
+        _ => None,
+    }
+}
+
+fn axis_button(axis: &Axis, side: AxisSide) -> Option<&Button> {
+    match (axis, side) {
+        (Axis::Emulated { neg, .. }, AxisSide::Neg) => Some(neg),
+        (Axis::Emulated { pos, .. }, AxisSide::Pos) => Some(pos),
+        _ => None,
+    }
+}
+
+fn user_bindings_path() -> String {
+    format!("{}/{}", application_root_dir(), USER_BINDINGS_FILENAME)
+}
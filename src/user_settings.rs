@@ -0,0 +1,143 @@
+use amethyst::renderer::DisplayConfig;
+use amethyst::utils::application_root_dir;
+
+use crate::resource_helpers::*;
+
+pub mod prelude {
+    pub use super::UserSettings;
+    pub use super::UserSettingsAudio;
+    pub use super::UserSettingsDisplay;
+}
+
+const USER_SETTINGS_FILENAME: &str = "user_settings.ron";
+
+/// Amount by which a volume slider changes per step.
+pub const VOLUME_STEP: f32 = 0.05;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub audio:   UserSettingsAudio,
+    pub display: UserSettingsDisplay,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettingsAudio {
+    pub master_volume: f32,
+    pub music_volume:  f32,
+    /// Saved for when the game plays sound effects; it doesn't have any yet.
+    pub sfx_volume:    f32,
+    pub muted:         bool,
+}
+
+/// Display options overwrite the values from `display.ron`;
+/// any field which is `None` keeps the value from `display.ron`.
+/// Changes only take effect after restarting the game.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettingsDisplay {
+    pub resolution:  Option<(u32, u32)>,
+    pub fullscreen:  Option<bool>,
+    pub vsync:       Option<bool>,
+    /// Maximum frames per second; `0` means unlimited.
+    pub frame_limit: Option<u32>,
+}
+
+impl UserSettings {
+    /// Loads the user settings file, falling back to defaults if it
+    /// does not exist or could not be parsed.
+    pub fn load() -> Self {
+        let path = user_settings_path();
+        match read_file(&path) {
+            Ok(raw) => match ron::de::from_str(&raw) {
+                Ok(user_settings) => user_settings,
+                Err(err) => {
+                    warn!(
+                        "Couldn't parse user settings file `{}`, using \
+                         defaults: {}",
+                        path, err
+                    );
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Writes the user settings to the user settings file.
+    pub fn save(&self) -> Result<(), String> {
+        let serialized =
+            ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|err| err.to_string())?;
+        write_file(user_settings_path(), serialized)
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl UserSettingsAudio {
+    /// Returns the music volume to set on the `AudioSink`,
+    /// given the base volume from the `Settings`.
+    pub fn music_volume_for(&self, base_volume: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            base_volume * self.master_volume * self.music_volume
+        }
+    }
+}
+
+impl Default for UserSettingsAudio {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume:  1.0,
+            sfx_volume:    1.0,
+            muted:         false,
+        }
+    }
+}
+
+impl UserSettingsDisplay {
+    /// Overwrites the given `DisplayConfig`'s values with the user's values.
+    pub fn apply_to(&self, display_config: &mut DisplayConfig) {
+        if let Some(resolution) = self.resolution {
+            set_resolution(display_config, resolution);
+        }
+        if let Some(fullscreen) = self.fullscreen {
+            display_config.fullscreen = fullscreen;
+        }
+        if let Some(vsync) = self.vsync {
+            display_config.vsync = vsync;
+        }
+    }
+}
+
+/// Sets the `DisplayConfig`'s dimensions to the given resolution.
+/// Also overwrites the min and max dimensions, if they are set.
+pub fn set_resolution(
+    display_config: &mut DisplayConfig,
+    resolution: (u32, u32),
+) {
+    display_config.dimensions = Some(resolution);
+    if display_config.max_dimensions.is_some() {
+        display_config.max_dimensions = Some(resolution);
+    }
+    if display_config.min_dimensions.is_some() {
+        display_config.min_dimensions = Some(resolution);
+    }
+}
+
+/// Increases or decreases the given volume by `VOLUME_STEP`,
+/// keeping it within `0.0` and `1.0`.
+pub fn step_volume(volume: f32, steps: i32) -> f32 {
+    let stepped = volume + VOLUME_STEP * steps as f32;
+    // Round to the nearest step, to avoid floating point drift.
+    ((stepped / VOLUME_STEP).round() * VOLUME_STEP)
+        .max(0.0)
+        .min(1.0)
+}
+
+fn user_settings_path() -> String {
+    format!("{}/{}", application_root_dir(), USER_SETTINGS_FILENAME)
+}
@@ -5,11 +5,17 @@ use amethyst::shred::Fetch;
 use deathframe::input_manager::InputManager;
 
 use crate::settings::Settings;
+use crate::user_settings::UserSettings;
 
 pub trait WorldHelpers {
     fn settings(&self) -> Settings;
+    fn user_settings(&self) -> UserSettings;
     fn input(&self) -> Fetch<InputHandler<String, String>>;
     fn input_manager(&self) -> Fetch<InputManager>;
+    /// Music volume while playing, with the user's audio settings applied.
+    fn music_volume(&self) -> f32;
+    /// Music volume while paused, with the user's audio settings applied.
+    fn music_volume_paused(&self) -> f32;
 }
 
 impl WorldHelpers for World {
@@ -17,6 +23,10 @@ impl WorldHelpers for World {
         self.read_resource::<Settings>().clone()
     }
 
+    fn user_settings(&self) -> UserSettings {
+        self.read_resource::<UserSettings>().clone()
+    }
+
     fn input(&self) -> Fetch<InputHandler<String, String>> {
         self.read_resource::<InputHandler<String, String>>()
     }
@@ -24,4 +34,18 @@ impl WorldHelpers for World {
     fn input_manager(&self) -> Fetch<InputManager> {
         self.read_resource::<InputManager>()
     }
+
+    fn music_volume(&self) -> f32 {
+        let base_volume = self.read_resource::<Settings>().music_volume;
+        self.read_resource::<UserSettings>()
+            .audio
+            .music_volume_for(base_volume)
+    }
+
+    fn music_volume_paused(&self) -> f32 {
+        let base_volume = self.read_resource::<Settings>().music_volume_paused;
+        self.read_resource::<UserSettings>()
+            .audio
+            .music_volume_for(base_volume)
+    }
 }